@@ -0,0 +1,158 @@
+//! Command line arguments for the desktop app.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::host::Mozlz4Mode;
+
+pub const USAGE: &str = "\
+Usage: firefox-session-ui-iced [OPTIONS] [FILE]
+       firefox-session-ui-iced mozlz4 <compress|decompress> [--force] <INPUT> [OUTPUT]
+
+Arguments:
+  [FILE]  Session file to open at startup
+
+Options:
+  --preset <NAME>  Select windows and output options from a saved preset
+  -h, --help       Print this help
+
+The mozlz4 command compresses or decompresses any file in Firefox's mozlz4
+format, for example search.json.mozlz4, without opening a window. OUTPUT
+defaults to INPUT with the .mozlz4 or .jsonlz4 extension changed and
+--force overwrites an existing OUTPUT.
+";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Args {
+    pub file: Option<PathBuf>,
+    pub preset: Option<String>,
+    pub help: bool,
+    /// Convert a file instead of starting the app.
+    pub mozlz4: Option<Mozlz4Args>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mozlz4Args {
+    pub mode: Mozlz4Mode,
+    pub input: PathBuf,
+    pub output: Option<PathBuf>,
+    pub force: bool,
+}
+impl Mozlz4Args {
+    /// Parse the arguments after `mozlz4`.
+    fn parse(mut args: impl Iterator<Item = OsString>) -> Result<Self, String> {
+        let mode = args
+            .next()
+            .and_then(|mode| {
+                Mozlz4Mode::all()
+                    .iter()
+                    .copied()
+                    .find(|m| mode.to_str() == Some(m.as_str()))
+            })
+            .ok_or("mozlz4 requires \"compress\" or \"decompress\"")?;
+        let mut force = false;
+        let mut paths = Vec::new();
+        for arg in args {
+            match arg.to_str() {
+                Some("-f" | "--force") => force = true,
+                Some(flag) if flag.starts_with('-') && flag != "-" => {
+                    return Err(format!("unknown option \"{flag}\""));
+                }
+                _ => paths.push(PathBuf::from(arg)),
+            }
+        }
+        let mut paths = paths.into_iter();
+        let input = paths.next().ok_or("mozlz4 requires an input file")?;
+        let output = paths.next();
+        if let Some(extra) = paths.next() {
+            return Err(format!(
+                "unexpected argument \"{}\", mozlz4 takes one input and one output file",
+                extra.display()
+            ));
+        }
+        Ok(Self {
+            mode,
+            input,
+            output,
+            force,
+        })
+    }
+
+    /// Convert the file and print where the result was written.
+    pub fn run(&self) -> Result<(), crate::host::Error> {
+        let output = crate::host::convert_mozlz4_file(
+            self.mode,
+            &self.input,
+            self.output.as_deref(),
+            self.force,
+        )?;
+        println!("Wrote {}", output.display());
+        Ok(())
+    }
+}
+
+impl Args {
+    /// Parse arguments, not including the program name.
+    pub fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().peekable();
+        if args.peek().and_then(|arg| arg.to_str()) == Some("mozlz4") {
+            args.next();
+            if matches!(
+                args.peek().and_then(|arg| arg.to_str()),
+                Some("-h" | "--help")
+            ) {
+                parsed.help = true;
+            } else {
+                parsed.mozlz4 = Some(Mozlz4Args::parse(args)?);
+            }
+            return Ok(parsed);
+        }
+        while let Some(arg) = args.next() {
+            match arg.to_str() {
+                Some("-h" | "--help") => parsed.help = true,
+                Some("--preset") => {
+                    let name = args.next().ok_or("--preset requires a name")?;
+                    parsed.preset = Some(
+                        name.into_string()
+                            .map_err(|_| "preset name must be valid UTF-8")?,
+                    );
+                }
+                Some(flag) if flag.starts_with("--preset=") => {
+                    parsed.preset = Some(flag["--preset=".len()..].to_owned());
+                }
+                Some(flag) if flag.starts_with('-') && flag != "-" => {
+                    return Err(format!("unknown option \"{flag}\""));
+                }
+                _ if parsed.file.is_some() => {
+                    return Err(format!(
+                        "unexpected argument \"{}\", only one file can be opened",
+                        arg.to_string_lossy()
+                    ));
+                }
+                _ => parsed.file = Some(PathBuf::from(arg)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Release builds use the Windows GUI subsystem so they don't open a console
+/// window, which also means anything printed is lost. Attach to the console
+/// of the terminal that started us, if any, before printing.
+pub fn attach_console() {
+    #[cfg(windows)]
+    {
+        const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+        #[link(name = "kernel32")]
+        extern "system" {
+            fn AttachConsole(process_id: u32) -> i32;
+        }
+        // Fails if we already have a console or weren't started from one,
+        // in both cases there is nothing more to do:
+        // SAFETY: takes no pointers and is fine to call at any time.
+        unsafe {
+            AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+    }
+}
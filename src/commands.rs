@@ -0,0 +1,435 @@
+//! Keyboard shortcuts and a command palette that lists every action in the app
+//! and can filter them by name.
+
+use iced::keyboard::{self, key::Named, Key, Modifiers};
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
+use iced::{event, Alignment, Element, Event, Length, Subscription, Task};
+
+use crate::host;
+use crate::selection::BulkSelection;
+
+/// An action that can be triggered from the command palette or by a keyboard
+/// shortcut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    OpenFile,
+    ReloadFile,
+    FindFirefoxProfile,
+    Timeline,
+    Mozlz4Tool,
+    ChooseSavePath,
+    SaveLinks,
+    AddToMerge,
+    ShowMerge,
+    CopyLinks,
+    CopySelectedLines,
+    SelectAllWindows,
+    SelectNoWindows,
+    InvertSelection,
+    SelectClosedWindows,
+    OpenTabsInBrowser,
+    ToggleLog,
+    ToggleJsonExplorer,
+    CopyLog,
+    SaveLog,
+    ClearLog,
+    GroupBy(host::GroupBy),
+    OutputFormat(&'static str),
+    FocusSearch,
+    ShowCommandPalette,
+}
+impl Command {
+    /// Every command in the order they are listed in the command palette.
+    pub fn all() -> Vec<Self> {
+        [
+            Self::OpenFile,
+            Self::ReloadFile,
+            Self::FindFirefoxProfile,
+            Self::Timeline,
+            Self::Mozlz4Tool,
+            Self::ChooseSavePath,
+            Self::SaveLinks,
+            Self::AddToMerge,
+            Self::ShowMerge,
+            Self::CopyLinks,
+            Self::CopySelectedLines,
+            Self::SelectAllWindows,
+            Self::SelectNoWindows,
+            Self::InvertSelection,
+            Self::SelectClosedWindows,
+            Self::FocusSearch,
+            Self::OpenTabsInBrowser,
+            Self::ToggleLog,
+            Self::ToggleJsonExplorer,
+            Self::CopyLog,
+            Self::SaveLog,
+            Self::ClearLog,
+        ]
+        .into_iter()
+        .chain(host::GroupBy::all().iter().copied().map(Self::GroupBy))
+        .chain(
+            host::OutputOptions::format_names(false)
+                .into_iter()
+                .map(Self::OutputFormat),
+        )
+        .collect()
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Command::OpenFile => "Open session file".to_owned(),
+            Command::ReloadFile => "Reload session file".to_owned(),
+            Command::FindFirefoxProfile => "Find Firefox profile".to_owned(),
+            Command::Timeline => "Show session history of profile".to_owned(),
+            Command::Mozlz4Tool => "Compress or decompress mozlz4 files".to_owned(),
+            Command::ChooseSavePath => "Choose where to save links".to_owned(),
+            Command::SaveLinks => "Save links to file".to_owned(),
+            Command::AddToMerge => "Add selected windows to merged export".to_owned(),
+            Command::ShowMerge => "Merge sessions into one export".to_owned(),
+            Command::CopyLinks => "Copy links to clipboard".to_owned(),
+            Command::CopySelectedLines => "Copy selected lines of the preview".to_owned(),
+            Command::SelectAllWindows => "Select all windows".to_owned(),
+            Command::SelectNoWindows => "Select no windows".to_owned(),
+            Command::InvertSelection => "Invert window selection".to_owned(),
+            Command::SelectClosedWindows => "Select all closed windows".to_owned(),
+            Command::OpenTabsInBrowser => "Open tabs in browser".to_owned(),
+            Command::ToggleLog => "Show or hide log".to_owned(),
+            Command::ToggleJsonExplorer => "Show session JSON or links".to_owned(),
+            Command::CopyLog => "Copy log to clipboard".to_owned(),
+            Command::SaveLog => "Save log to file".to_owned(),
+            Command::ClearLog => "Clear log".to_owned(),
+            Command::GroupBy(group_by) => format!("Group tabs by {group_by}"),
+            Command::OutputFormat(format) => format!("Use output format: {format}"),
+            Command::FocusSearch => "Select windows matching a pattern".to_owned(),
+            Command::ShowCommandPalette => "Show command palette".to_owned(),
+        }
+    }
+
+    /// The message that performs this command.
+    pub fn message(self) -> crate::Message {
+        use crate::Message as M;
+        match self {
+            Command::OpenFile => M::BrowseInputPath,
+            Command::ReloadFile => M::LoadInputData,
+            Command::FindFirefoxProfile => M::FirefoxProfileWizard(crate::wizard::Message::Show),
+            Command::Timeline => M::ShowTimeline,
+            Command::Mozlz4Tool => M::Mozlz4Tool(crate::mozlz4_tool::Message::Show),
+            Command::ChooseSavePath => M::BrowseSavePath,
+            Command::SaveLinks => M::SaveLinksToFile,
+            Command::AddToMerge => M::AddToMerge,
+            Command::ShowMerge => M::Merge(crate::merge::Message::Show),
+            Command::CopyLinks => M::CopyLinksToClipboard,
+            Command::CopySelectedLines => M::CopyPreviewSelection,
+            Command::SelectAllWindows => M::SelectTabGroups(BulkSelection::All),
+            Command::SelectNoWindows => M::SelectTabGroups(BulkSelection::None),
+            Command::InvertSelection => M::SelectTabGroups(BulkSelection::Invert),
+            Command::SelectClosedWindows => M::SelectTabGroups(BulkSelection::AllClosed),
+            Command::OpenTabsInBrowser => M::OpenTabsInBrowser,
+            Command::ToggleLog => M::ToggleStatusLog,
+            Command::ToggleJsonExplorer => M::ToggleJsonExplorer,
+            Command::CopyLog => M::CopyStatusLog,
+            Command::SaveLog => M::SaveStatusLog,
+            Command::ClearLog => M::ClearStatusLog,
+            Command::GroupBy(group_by) => M::SetGroupBy(group_by),
+            Command::OutputFormat(format) => M::SetOutputFormat(format),
+            Command::FocusSearch => M::FocusWindowPattern,
+            Command::ShowCommandPalette => M::CommandPalette(Message::Show),
+        }
+    }
+
+    /// The first keyboard shortcut for this command.
+    pub fn shortcut(&self) -> Option<Shortcut> {
+        SHORTCUTS
+            .iter()
+            .find(|(_, command)| command == self)
+            .map(|(shortcut, _)| *shortcut)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shortcut {
+    /// Ctrl, or Cmd on macOS.
+    command: bool,
+    shift: bool,
+    key: ShortcutKey,
+    /// Also trigger the shortcut while a text field is focused. Disabled for
+    /// shortcuts that text fields use for editing.
+    in_text_field: bool,
+}
+impl Shortcut {
+    const fn ctrl(key: &'static str) -> Self {
+        Self {
+            command: true,
+            shift: false,
+            key: ShortcutKey::Character(key),
+            in_text_field: true,
+        }
+    }
+    const fn named(key: Named) -> Self {
+        Self {
+            command: false,
+            shift: false,
+            key: ShortcutKey::Named(key),
+            in_text_field: true,
+        }
+    }
+    const fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+    const fn not_in_text_field(mut self) -> Self {
+        self.in_text_field = false;
+        self
+    }
+
+    fn matches(&self, key: &Key, modifiers: Modifiers) -> bool {
+        let key_matches = match (key.as_ref(), self.key) {
+            // Shift might change the character to upper case:
+            (Key::Character(pressed), ShortcutKey::Character(expected)) => {
+                pressed.eq_ignore_ascii_case(expected)
+            }
+            (Key::Named(pressed), ShortcutKey::Named(expected)) => pressed == expected,
+            _ => false,
+        };
+        key_matches
+            && modifiers.command() == self.command
+            && modifiers.shift() == self.shift
+            && !modifiers.alt()
+    }
+}
+impl std::fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.command {
+            f.write_str(if cfg!(target_os = "macos") {
+                "Cmd+"
+            } else {
+                "Ctrl+"
+            })?;
+        }
+        if self.shift {
+            f.write_str("Shift+")?;
+        }
+        match self.key {
+            ShortcutKey::Character(c) => f.write_str(&c.to_uppercase()),
+            ShortcutKey::Named(named) => write!(f, "{named:?}"),
+        }
+    }
+}
+
+/// A [`Key`] that can be copied and stored in constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShortcutKey {
+    Character(&'static str),
+    Named(Named),
+}
+
+const SHORTCUTS: &[(Shortcut, Command)] = &[
+    (Shortcut::ctrl("o"), Command::OpenFile),
+    (Shortcut::ctrl("r"), Command::ReloadFile),
+    (Shortcut::named(Named::F5), Command::ReloadFile),
+    (Shortcut::ctrl("s"), Command::SaveLinks),
+    (
+        Shortcut::ctrl("c").shift().not_in_text_field(),
+        Command::CopyLinks,
+    ),
+    (
+        Shortcut::ctrl("c").not_in_text_field(),
+        Command::CopySelectedLines,
+    ),
+    (
+        Shortcut::ctrl("a").not_in_text_field(),
+        Command::SelectAllWindows,
+    ),
+    (
+        Shortcut::ctrl("a").shift().not_in_text_field(),
+        Command::SelectNoWindows,
+    ),
+    (
+        Shortcut::ctrl("i").not_in_text_field(),
+        Command::InvertSelection,
+    ),
+    (Shortcut::ctrl("l"), Command::ToggleLog),
+    (Shortcut::ctrl("j"), Command::ToggleJsonExplorer),
+    (Shortcut::ctrl("f"), Command::FocusSearch),
+    (Shortcut::ctrl("k"), Command::ShowCommandPalette),
+    (Shortcut::ctrl("p").shift(), Command::ShowCommandPalette),
+];
+
+/// The command for a key press. `captured` is `true` if a widget, for example
+/// a text field, already handled the key press.
+pub fn command_for_key(key: &Key, modifiers: Modifiers, captured: bool) -> Option<Command> {
+    SHORTCUTS
+        .iter()
+        .find(|(shortcut, _)| {
+            (!captured || shortcut.in_text_field) && shortcut.matches(key, modifiers)
+        })
+        .map(|(_, command)| *command)
+}
+
+/// Keyboard shortcuts, navigation inside the command palette and the state of
+/// modifier keys.
+pub fn subscription() -> Subscription<crate::Message> {
+    event::listen_with(|event, status, _window| {
+        let (key, modifiers) = match event {
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => (key, modifiers),
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                return Some(crate::Message::ModifiersChanged(modifiers));
+            }
+            _ => return None,
+        };
+        let captured = status == event::Status::Captured;
+        if let Some(command) = command_for_key(&key, modifiers, captured) {
+            return Some(crate::Message::RunCommand(command));
+        }
+        let palette = match key.as_ref() {
+            // Text fields unfocus on the first escape press:
+            Key::Named(Named::Escape) => Message::Hide,
+            Key::Named(Named::ArrowUp) if !captured => Message::MoveSelection(-1),
+            Key::Named(Named::ArrowDown) if !captured => Message::MoveSelection(1),
+            _ => return None,
+        };
+        Some(crate::Message::CommandPalette(palette))
+    })
+}
+
+/// Score how well a search query matches a name, `None` if some characters in
+/// the query don't occur in order in the name. Higher is better.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<u32> {
+    let mut score = 0;
+    let mut name_chars = name.chars().flat_map(char::to_lowercase).peekable();
+    let mut previous: Option<char> = None;
+    let mut consecutive = false;
+    for wanted in query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+    {
+        loop {
+            let c = name_chars.next()?;
+            let word_start = previous.is_none_or(|p| !p.is_alphanumeric());
+            previous = Some(c);
+            if c == wanted {
+                score += 1;
+                if word_start {
+                    score += 3;
+                }
+                if consecutive {
+                    score += 2;
+                }
+                consecutive = true;
+                break;
+            }
+            consecutive = false;
+        }
+    }
+    Some(score)
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Show,
+    Hide,
+    SetQuery(String),
+    MoveSelection(isize),
+    /// Run the selected command.
+    Submit,
+}
+
+/// Id of the search field so that it can be focused when the palette opens.
+const SEARCH_ID: &str = "command-palette-search";
+
+#[derive(Debug, Default)]
+pub struct State {
+    active: bool,
+    query: String,
+    /// Index into the filtered commands.
+    selected: usize,
+}
+impl State {
+    /// Commands that match the search query, the best matches first.
+    pub fn matches(&self) -> Vec<Command> {
+        let mut matches = Command::all()
+            .into_iter()
+            .filter_map(|command| Some((fuzzy_score(&self.query, &command.name())?, command)))
+            .collect::<Vec<_>>();
+        // Stable sort so equal matches keep their order:
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        matches.into_iter().map(|(_, command)| command).collect()
+    }
+
+    /// Returns a command if one should be run.
+    pub fn update(&mut self, message: Message) -> (Task<Message>, Option<Command>) {
+        match message {
+            Message::Show => {
+                self.active = true;
+                self.query.clear();
+                self.selected = 0;
+                return (text_input::focus(SEARCH_ID), None);
+            }
+            Message::Hide => self.active = false,
+            Message::SetQuery(query) => {
+                self.query = query;
+                self.selected = 0;
+            }
+            Message::MoveSelection(offset) if self.active => {
+                let count = self.matches().len();
+                if count > 0 {
+                    self.selected = self.selected.saturating_add_signed(offset).min(count - 1);
+                }
+            }
+            Message::MoveSelection(_) => {}
+            Message::Submit => {
+                let command = self.matches().get(self.selected).copied();
+                if command.is_some() {
+                    self.active = false;
+                }
+                return (Task::none(), command);
+            }
+        }
+        (Task::none(), None)
+    }
+
+    pub fn view(&self) -> Option<Element<'_, crate::Message>> {
+        if !self.active {
+            return None;
+        }
+        let matches = self.matches();
+        let list = column(matches.iter().enumerate().map(|(index, command)| {
+            button(
+                row![text(command.name())]
+                    .push(iced::widget::horizontal_space())
+                    .push_maybe(command.shortcut().map(|s| text(s.to_string()).size(14)))
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+            )
+            .width(Length::Fill)
+            .style(if index == self.selected {
+                button::primary
+            } else {
+                button::text
+            })
+            .on_press(crate::Message::RunCommand(*command))
+            .into()
+        }))
+        .push_maybe(matches.is_empty().then(|| text("No matching commands")))
+        .spacing(2);
+
+        Some(
+            container(
+                column![
+                    text_input("Type a command", &self.query)
+                        .id(SEARCH_ID)
+                        .on_input(|query| crate::Message::CommandPalette(Message::SetQuery(query)))
+                        .on_submit(crate::Message::CommandPalette(Message::Submit)),
+                    scrollable(list).height(Length::Shrink),
+                ]
+                .spacing(10),
+            )
+            .width(500)
+            .max_height(400)
+            .padding(20)
+            .style(container::bordered_box)
+            .into(),
+        )
+    }
+}
@@ -3,14 +3,40 @@
     allow(dead_code, unused_variables, unused_imports)
 )]
 
-use std::{borrow::Cow, env, io::Empty, path::PathBuf, sync::Arc, time::SystemTime};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    env,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    time::SystemTime,
+};
 
-use either::Either;
 #[cfg(feature = "real_data")]
 use firefox_session_data::session_store::FirefoxSessionStore;
 #[cfg(feature = "real_data")]
 pub use firefox_session_data::to_links::ttl_formats::FormatInfo;
 
+mod bookmarks;
+mod data_url;
+mod error;
+#[cfg(not(feature = "real_data"))]
+mod fake;
+mod imports;
+mod links;
+mod mozlz4;
+mod session;
+mod snss;
+mod timeline;
+
+pub use data_url::DataUrl;
+pub use error::{Error, Source};
+pub use mozlz4::Mozlz4Mode;
+pub use session::Container;
+pub use timeline::{
+    archive_folder, archive_session_files, load_timeline, Snapshot, TabLifetime, Timeline,
+};
+
 /// Unconditionally sendable when targeting the web.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct WebSendable<T>(pub T);
@@ -25,7 +51,6 @@ unsafe impl<T> Sync for WebSendable<T> {}
 /// A version of [`tokio::task::spawn_blocking`] that works for the WebAssembly
 /// target where we don't have access to threads, in that case we simply block
 /// the runtime (i.e. the event loop).
-#[cfg(feature = "real_data")]
 pub async fn spawn_blocking<F, R>(f: F) -> R
 where
     F: FnOnce() -> R + Send + 'static,
@@ -41,95 +66,25 @@ where
     }
 }
 
-#[cfg(not(feature = "real_data"))]
-mod fake {
-    use super::*;
-
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    pub enum FormatInfo {
-        PDF,
-        Text,
-        Html,
-    }
-    impl FormatInfo {
-        pub fn all() -> &'static [Self] {
-            &[Self::PDF, Self::Text, Self::Html]
-        }
-        pub fn as_str(&self) -> &'static str {
-            match self {
-                FormatInfo::PDF => "pdf",
-                FormatInfo::Text => "text",
-                FormatInfo::Html => "html",
-            }
-        }
-    }
-    impl std::fmt::Display for FormatInfo {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            match self {
-                FormatInfo::PDF => write!(f, "Convert the links to a PDF that contains links."),
-                FormatInfo::Text => write!(f, "Convert the links to raw text."),
-                FormatInfo::Html => write!(f, "Convert the links to a HTML file with anchor tags."),
+/// Wait for some time without blocking the runtime.
+pub async fn sleep(duration: std::time::Duration) {
+    #[cfg(target_family = "wasm")]
+    {
+        let millis = i32::try_from(duration.as_millis()).unwrap_or(i32::MAX);
+        let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+            if let Some(window) = web_sys::window() {
+                let _ =
+                    window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, millis);
             }
-        }
+        });
+        let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
     }
-
-    pub(super) type FirefoxSessionStore = ();
-
-    impl FileInfo {
-        pub async fn load_data(&mut self) -> Result<(), String> {
-            self.data = Some(FileData::Compressed(Arc::new([])));
-            Ok(())
-        }
-
-        pub async fn decompress_data(&mut self) -> Result<(), String> {
-            self.data = Some(FileData::Uncompressed(Arc::new([])));
-            Ok(())
-        }
-
-        pub async fn parse_session_data(&mut self) -> Result<(), String> {
-            self.data = Some(FileData::Parsed(Arc::new(())));
-            Ok(())
-        }
-
-        pub async fn get_groups_from_session(
-            &self,
-            sort_groups: bool,
-        ) -> Result<AllTabGroups, String> {
-            Ok(AllTabGroups {
-                open: vec![
-                    TabGroup {
-                        index: 0,
-                        name: "Window 1".into(),
-                    },
-                    TabGroup {
-                        index: 1,
-                        name: "Window 2".into(),
-                    },
-                ],
-                closed: vec![TabGroup {
-                    index: 2,
-                    name: "Closed window 1".into(),
-                }],
-            })
-        }
-
-        pub async fn to_text_links(
-            &self,
-            generate_options: GenerateOptions,
-        ) -> Result<String, String> {
-            Ok("http://www.example.com".to_string())
-        }
-
-        pub async fn save_links(
-            &self,
-            save_path: PathBuf,
-            generate_options: GenerateOptions,
-            output_options: OutputOptions,
-        ) -> Result<(), String> {
-            Ok(())
-        }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        tokio::time::sleep(duration).await
     }
 }
+
 #[cfg(not(feature = "real_data"))]
 pub use fake::*;
 
@@ -140,33 +95,96 @@ pub struct FirefoxProfileInfo {
     modified_at: Result<SystemTime, String>,
 }
 impl FirefoxProfileInfo {
+    /// Guess which profile a sessionstore file belongs to based on where it
+    /// is stored. Returns `None` if the file doesn't seem to be inside a
+    /// Firefox profile.
+    pub fn for_session_file(session_file: &Path) -> Option<Self> {
+        let mut folder = session_file.parent()?;
+        if ["sessionstore-backups", "bookmarkbackups"]
+            .contains(&&*folder.file_name()?.to_string_lossy())
+        {
+            folder = folder.parent()?;
+        } else if !["prefs.js", "times.json"]
+            .iter()
+            .any(|file| folder.join(file).is_file())
+        {
+            // Any other folder is only a profile if it has files that Firefox
+            // creates in every profile:
+            return None;
+        }
+        Some(FirefoxProfileInfo {
+            path: folder.to_owned(),
+            modified_at: Err("Not available".to_string()),
+        })
+    }
     /// Name of the Firefox profile folder.
     pub fn name(&self) -> Cow<'_, str> {
         self.path.file_name().unwrap_or_default().to_string_lossy()
     }
-    pub fn find_sessionstore_file(&self) -> PathBuf {
-        let previous = self.path.join("sessionstore-backups/previous.jsonlz4");
-        let recovery_older = self.path.join("sessionstore-backups/recovery.baklz4");
-        let recovery = self.path.join("sessionstore-backups/recovery.jsonlz4");
-        let session_store = self.path.join("sessionstore.jsonlz4");
-
-        if session_store.exists() {
+    /// Session files that exist in this profile, the one with the latest data
+    /// first.
+    pub fn session_files(&self) -> Vec<PathBuf> {
+        [
             // If Firefox is closed then this has the latest data:
-            session_store
-        } else if recovery.exists() {
+            "sessionstore.jsonlz4",
             // Otherwise this keeps the latest data:
-            recovery
-        } else if recovery_older.exists() {
+            "sessionstore-backups/recovery.jsonlz4",
             // When Firefox is overwriting recovery it moves
             // the old one here first:
-            recovery_older
-        } else if previous.exists() {
+            "sessionstore-backups/recovery.baklz4",
             // And this is from the previous startup:
-            previous
-        } else {
+            "sessionstore-backups/previous.jsonlz4",
+        ]
+        .into_iter()
+        .map(|file| self.path.join(file))
+        .filter(|path| path.exists())
+        .collect()
+    }
+    pub fn find_sessionstore_file(&self) -> PathBuf {
+        self.session_files().into_iter().next().unwrap_or_else(|| {
             // This is written most often so keep trying it:
-            recovery
-        }
+            self.path.join("sessionstore-backups/recovery.jsonlz4")
+        })
+    }
+    /// Bookmark backups that Firefox makes daily, the newest first.
+    pub fn bookmark_backups(&self) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(self.path.join("bookmarkbackups")) else {
+            return Vec::new();
+        };
+        let mut backups = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                name.starts_with("bookmarks-") && name.ends_with("lz4")
+            })
+            .collect::<Vec<_>>();
+        // Names start with the date, for example
+        // "bookmarks-2024-05-01_1234_XYZ.jsonlz4":
+        backups.sort();
+        backups.reverse();
+        backups
+    }
+    pub fn find_bookmark_backup(&self) -> PathBuf {
+        self.bookmark_backups()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| self.path.join("bookmarkbackups"))
+    }
+    /// Read the Firefox containers that are defined in this profile. Returns
+    /// an empty list if the profile doesn't have any containers file.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn load_containers(&self) -> Result<Vec<Container>, Error> {
+        let path = self.path.join("containers.json");
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(Error::read(&path, e)),
+        };
+        Container::parse_all(&data).map_err(|e| Error::Containers {
+            path,
+            source: error::source(e),
+        })
     }
     pub fn all_profiles() -> Vec<FirefoxProfileInfo> {
         #[cfg(feature = "real_data")]
@@ -191,9 +209,44 @@ impl FirefoxProfileInfo {
     }
 }
 
+/// Folder where settings are remembered between runs, [`None`] if there is no
+/// such folder (for example on the web).
+pub fn config_folder() -> Option<PathBuf> {
+    const APP_FOLDER: &str = "firefox-session-ui-iced";
+
+    if cfg!(target_family = "wasm") {
+        None
+    } else if let Some(data) = env::var_os("APPDATA") {
+        Some(PathBuf::from(data).join(APP_FOLDER))
+    } else if let Some(config) = env::var_os("XDG_CONFIG_HOME") {
+        Some(PathBuf::from(config).join(APP_FOLDER))
+    } else {
+        let home = PathBuf::from(env::var_os("HOME")?);
+        if cfg!(target_os = "macos") {
+            Some(home.join("Library/Application Support").join(APP_FOLDER))
+        } else {
+            Some(home.join(".config").join(APP_FOLDER))
+        }
+    }
+}
+
+/// Write a file inside [`config_folder`] in the background, creating the
+/// folder if needed. `path` is [`None`] if there is no such folder.
+pub async fn save_config_file(path: Option<PathBuf>, contents: String) -> Result<(), Error> {
+    let path = path.ok_or(Error::NoConfigFolder)?;
+    spawn_blocking(move || {
+        if let Some(folder) = path.parent() {
+            std::fs::create_dir_all(folder).map_err(|e| Error::write(folder, e))?;
+        }
+        std::fs::write(&path, contents).map_err(|e| Error::write(&path, e))
+    })
+    .await
+}
+
 pub async fn prompt_load_file() -> Option<rfd::FileHandle> {
     let mut builder = ::rfd::AsyncFileDialog::new() //.set_parent(&**cx)
         .add_filter("Firefox session file", &["js", "baklz4", "jsonlz4"])
+        .add_filter("Session manager export", &["json", "txt"])
         .add_filter("All files", &["*"])
         .set_title("Open Firefox Sessionstore File");
 
@@ -205,6 +258,15 @@ pub async fn prompt_load_file() -> Option<rfd::FileHandle> {
     builder.pick_file().await
 }
 
+pub async fn prompt_any_file(title: &str) -> Option<rfd::FileHandle> {
+    rfd::AsyncFileDialog::new()
+        .add_filter("mozlz4 files", &["mozlz4", "jsonlz4", "baklz4", "lz4"])
+        .add_filter("All files", &["*"])
+        .set_title(title)
+        .pick_file()
+        .await
+}
+
 pub async fn prompt_save_file() -> Option<rfd::FileHandle> {
     let builder = rfd::AsyncFileDialog::new()
         // .set_parent(&**cx)
@@ -214,6 +276,32 @@ pub async fn prompt_save_file() -> Option<rfd::FileHandle> {
     builder.save_file().await
 }
 
+/// Ask where to save some text and then write it there. When running as a
+/// website the text is downloaded instead.
+///
+/// Returns `false` if the user cancelled the save dialog.
+pub async fn prompt_save_text(file_name: &str, text: String) -> Result<bool, Error> {
+    #[cfg(target_family = "wasm")]
+    {
+        save_file_on_web_target(text.as_bytes(), Some(file_name))?;
+        Ok(true)
+    }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        let Some(handle) = rfd::AsyncFileDialog::new()
+            .set_file_name(file_name)
+            .save_file()
+            .await
+        else {
+            return Ok(false);
+        };
+        let path = handle.path().to_owned();
+        spawn_blocking(move || std::fs::write(&path, text).map_err(|e| Error::write(&path, e)))
+            .await?;
+        Ok(true)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TabGroup {
     /// The index of the tab group. Used to uniquely identify a group in a
@@ -221,6 +309,90 @@ pub struct TabGroup {
     pub index: u32,
     /// Name of a tab group.
     pub name: String,
+    /// RGB color of the group, for example when grouping by container.
+    pub color: Option<[u8; 3]>,
+    /// Native Firefox tab groups inside a window.
+    pub sub_groups: Vec<NativeTabGroup>,
+    /// Encoded image, for example PNG or ICO, of the first favicon that was
+    /// stored as a `data:` URL by a tab in the group.
+    pub favicon: Option<Arc<[u8]>>,
+    /// Nesting level of bookmark folders, `0` for everything else.
+    pub depth: usize,
+    /// JSON pointer to the window or bookmark folder that the group shows,
+    /// [`None`] if the group isn't one of them.
+    pub json_path: Option<String>,
+}
+
+/// What kind of data a [`FileInfo`] was loaded from, detected when the data is
+/// parsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DataSource {
+    #[default]
+    Session,
+    /// A bookmark backup where every folder is shown as a window.
+    Bookmarks,
+    /// A JSON export from the Tab Session Manager extension.
+    TabSessionManager,
+    /// A JSON export from the Session Buddy extension.
+    SessionBuddy,
+    /// A text export from the OneTab extension.
+    OneTab,
+    /// A Chromium session file like `Current Session`.
+    Chromium,
+}
+impl DataSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataSource::Session => "Firefox session",
+            DataSource::Bookmarks => "Firefox bookmark backup",
+            DataSource::TabSessionManager => "Tab Session Manager export",
+            DataSource::SessionBuddy => "Session Buddy export",
+            DataSource::OneTab => "OneTab export",
+            DataSource::Chromium => "Chromium session",
+        }
+    }
+    /// `true` if the data was JSON that can be explored and saved.
+    pub fn is_json(&self) -> bool {
+        !matches!(self, DataSource::OneTab | DataSource::Chromium)
+    }
+}
+
+/// Info about a native Firefox tab group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeTabGroup {
+    pub name: String,
+    pub color: Option<[u8; 3]>,
+    /// `true` if the tab group was collapsed in Firefox's tab bar.
+    pub collapsed: bool,
+    /// Number of tabs in the group.
+    pub tab_count: usize,
+}
+
+/// Everything Firefox stored about a tab, shown by the tab inspector.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TabDetails {
+    /// Name of the window that has the tab.
+    pub window: String,
+    pub url: String,
+    pub title: String,
+    /// URL of the tab's icon, often a `data:` URL.
+    pub favicon: Option<String>,
+    /// Milliseconds since the Unix epoch.
+    pub last_accessed: Option<u64>,
+    pub pinned: bool,
+    pub hidden: bool,
+    pub muted: bool,
+    pub container: String,
+    /// Title of the parent tab in Tree Style Tab or Sidebery, or its id if
+    /// there is no tab with that id.
+    pub parent: Option<String>,
+    /// Scroll position of the current page as `x,y`.
+    pub scroll: Option<String>,
+    pub has_form_data: bool,
+    pub history_length: usize,
+    /// JSON pointer to the tab's data in the sessionstore JSON, for example
+    /// `/windows/0/tabs/3`.
+    pub json_path: String,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -230,6 +402,88 @@ pub struct AllTabGroups {
     /// Tab groups or windows in recently closed windows.
     pub closed: Vec<TabGroup>,
 }
+impl AllTabGroups {
+    /// Saved JSON can only be limited to the selected groups if every group
+    /// is a whole window.
+    pub fn can_select_json_windows(&self) -> bool {
+        [("windows", &self.open), ("_closedWindows", &self.closed)]
+            .into_iter()
+            .all(|(key, groups)| {
+                groups
+                    .iter()
+                    .all(|group| json_window_index(group, key).is_some())
+            })
+    }
+    /// Indexes into the `windows` and `_closedWindows` arrays of the
+    /// sessionstore JSON for the selected groups, [`None`] if a selected group
+    /// isn't a whole window.
+    fn selected_json_windows(&self, options: &GenerateOptions) -> Option<[Vec<usize>; 2]> {
+        let selected = |key: &str, groups: &[TabGroup], indexes: &Option<Vec<u32>>| {
+            groups
+                .iter()
+                .filter(|group| {
+                    indexes
+                        .as_ref()
+                        .is_none_or(|indexes| indexes.contains(&group.index))
+                })
+                .map(|group| json_window_index(group, key))
+                .collect::<Option<Vec<_>>>()
+        };
+        Some([
+            selected("windows", &self.open, &options.open_group_indexes)?,
+            selected(
+                "_closedWindows",
+                &self.closed,
+                &options.closed_group_indexes,
+            )?,
+        ])
+    }
+}
+
+/// Position of the window that a group shows in the `key` array of the
+/// sessionstore JSON, [`None`] if the group isn't a whole window.
+fn json_window_index(group: &TabGroup, key: &str) -> Option<usize> {
+    group
+        .json_path
+        .as_deref()?
+        .strip_prefix('/')?
+        .strip_prefix(key)?
+        .strip_prefix('/')?
+        .parse()
+        .ok()
+}
+
+/// Decides what tabs are placed together in a [`TabGroup`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Windows, or Sidebery/TST panels if there is data from those extensions.
+    #[default]
+    Window,
+    /// The full host name of each tab's URL (`en.wikipedia.org`).
+    Host,
+    /// The registrable domain of each tab's URL (`wikipedia.org`).
+    Domain,
+    /// The Firefox container that each tab is opened in.
+    Container,
+}
+impl GroupBy {
+    pub fn all() -> &'static [Self] {
+        &[Self::Window, Self::Host, Self::Domain, Self::Container]
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupBy::Window => "window",
+            GroupBy::Host => "host",
+            GroupBy::Domain => "domain",
+            GroupBy::Container => "container",
+        }
+    }
+}
+impl std::fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GenerateOptions {
@@ -237,12 +491,28 @@ pub struct GenerateOptions {
     pub closed_group_indexes: Option<Vec<u32>>,
     pub sort_groups: bool,
     pub table_of_content: bool,
+    pub group_by: GroupBy,
+    /// Only include tabs with this Firefox container id (`userContextId`).
+    pub container_filter: Option<u32>,
+    /// Show native Firefox tab groups as sub-sections inside windows.
+    pub native_tab_groups: bool,
 }
 impl GenerateOptions {
     pub fn selected_groups(&self) -> usize {
         self.open_group_indexes.as_ref().map_or(0, Vec::len)
             + self.closed_group_indexes.as_ref().map_or(0, Vec::len)
     }
+    /// `true` if tabs must be grouped by our own code since
+    /// `firefox_session_data` doesn't support these options.
+    pub fn uses_own_grouping(&self) -> bool {
+        self.group_by != GroupBy::Window
+            || self.container_filter.is_some()
+            || self.native_tab_groups
+    }
+    /// Native tab groups are only shown when grouping by window.
+    pub fn shows_native_tab_groups(&self) -> bool {
+        self.native_tab_groups && self.group_by == GroupBy::Window
+    }
 }
 impl Default for GenerateOptions {
     fn default() -> Self {
@@ -251,6 +521,9 @@ impl Default for GenerateOptions {
             closed_group_indexes: Some(Vec::new()),
             sort_groups: true,
             table_of_content: true,
+            group_by: GroupBy::Window,
+            container_filter: None,
+            native_tab_groups: false,
         }
     }
 }
@@ -260,6 +533,14 @@ pub struct OutputOptions {
     pub format: FormatInfo,
     pub overwrite: bool,
     pub create_folder: bool,
+    /// Include favicons that are stored as `data:` URLs in HTML output, see
+    /// [`OutputOptions::can_embed_favicons`]. PDF output never has favicons.
+    pub embed_favicons: bool,
+    /// Save the session's JSON instead of links, `format` is then ignored.
+    pub json: Option<JsonFormat>,
+    /// Only keep the selected windows when saving JSON, see
+    /// [`AllTabGroups::can_select_json_windows`].
+    pub json_selected_windows: bool,
 }
 
 impl Default for OutputOptions {
@@ -268,6 +549,86 @@ impl Default for OutputOptions {
             format: FormatInfo::PDF,
             overwrite: Default::default(),
             create_folder: Default::default(),
+            embed_favicons: Default::default(),
+            json: None,
+            json_selected_windows: Default::default(),
+        }
+    }
+}
+impl OutputOptions {
+    /// Favicons can only be embedded in HTML files that we write ourself. PDF
+    /// files are written by `firefox_session_data`, which has no option for
+    /// favicons, so they aren't supported there.
+    pub fn can_embed_favicons(&self) -> bool {
+        self.json.is_none() && own_link_format(self.format) == Some(links::LinkFormat::Html)
+    }
+    /// Names of the link formats followed by the JSON formats. Only
+    /// `firefox_session_data` can write PDF files, so formats that our own
+    /// code can't write are left out if `own_grouping` is `true`, see
+    /// [`FileInfo::uses_own_grouping`].
+    pub fn format_names(own_grouping: bool) -> Vec<&'static str> {
+        (FormatInfo::all().iter())
+            .filter(|format| !own_grouping || own_link_format(**format).is_some())
+            .map(|format| format.as_str())
+            .chain(JsonFormat::all().iter().map(JsonFormat::as_str))
+            .collect()
+    }
+    /// Name of the selected format, one of [`Self::format_names`].
+    pub fn format_name(&self) -> &'static str {
+        match self.json {
+            Some(json) => json.as_str(),
+            None => self.format.as_str(),
+        }
+    }
+    /// What the selected format does.
+    pub fn format_description(&self) -> String {
+        match self.json {
+            Some(json) => json.to_string(),
+            None => self.format.to_string(),
+        }
+    }
+    /// Select a format by name. Returns `false` if there is no such format.
+    pub fn set_format(&mut self, name: &str) -> bool {
+        if let Some(json) = JsonFormat::all().iter().find(|f| f.as_str() == name) {
+            self.json = Some(*json);
+        } else if let Some(format) = FormatInfo::all().iter().find(|f| f.as_str() == name) {
+            self.format = *format;
+            self.json = None;
+        } else {
+            return false;
+        }
+        true
+    }
+}
+
+/// Save the decompressed sessionstore JSON so that it can be used by other
+/// tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonFormat {
+    /// The data exactly as Firefox wrote it.
+    Raw,
+    /// Indented with one value per line.
+    Pretty,
+}
+impl JsonFormat {
+    pub fn all() -> &'static [Self] {
+        &[Self::Raw, Self::Pretty]
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JsonFormat::Raw => "json",
+            JsonFormat::Pretty => "pretty json",
+        }
+    }
+}
+impl std::fmt::Display for JsonFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonFormat::Raw => write!(f, "Save the decompressed sessionstore JSON."),
+            JsonFormat::Pretty => write!(
+                f,
+                "Save the decompressed sessionstore JSON with indentation and line breaks."
+            ),
         }
     }
 }
@@ -293,6 +654,14 @@ pub struct FileInfo {
     pub file_path: Arc<PathBuf>,
     pub file_handle: Option<WebSendable<rfd::FileHandle>>,
     pub data: Option<FileData>,
+    /// Tabs read from the session, available after the data has been parsed.
+    /// Used when grouping tabs by something other than windows.
+    session: Option<Arc<session::Session>>,
+    /// The decompressed JSON, kept after parsing so that it can be explored.
+    json: Option<Arc<[u8]>>,
+    /// Firefox containers from the profile that the file was loaded from.
+    containers: Arc<[Container]>,
+    source: DataSource,
 }
 impl FileInfo {
     pub fn new(file_path: PathBuf) -> Self {
@@ -300,18 +669,939 @@ impl FileInfo {
             file_path: Arc::new(file_path),
             file_handle: None,
             data: None,
+            session: None,
+            json: None,
+            containers: Arc::new([]),
+            source: DataSource::Session,
+        }
+    }
+    pub fn source(&self) -> DataSource {
+        self.source
+    }
+    /// Firefox containers defined in the profile that the session was loaded
+    /// from. Empty if the profile couldn't be found.
+    pub fn containers(&self) -> &[Container] {
+        &self.containers
+    }
+    /// The decompressed sessionstore JSON.
+    pub fn json_data(&self) -> Option<Arc<[u8]>> {
+        match &self.data {
+            // After parsing the data is only kept if it is JSON:
+            Some(FileData::Uncompressed(data)) if !self.is_parsed() => Some(data.clone()),
+            _ => self.json.clone(),
         }
     }
+    /// `true` once tabs can be listed. Bookmarks, Chromium sessions and
+    /// session manager exports are only parsed by our own code, so their data
+    /// stays [`FileData::Uncompressed`] after parsing.
+    pub fn is_parsed(&self) -> bool {
+        self.session.is_some() || self.data.as_ref().and_then(FileData::as_parsed).is_some()
+    }
+    /// `true` if the parsed session has any native Firefox tab groups.
+    pub fn has_native_tab_groups(&self) -> bool {
+        self.session
+            .as_ref()
+            .is_some_and(|session| session.has_native_groups())
+    }
     pub fn is_compressed_file_format(&self) -> bool {
         self.file_path
             .extension()
-            .and_then(|ext| ext.to_str().map(|v| v.ends_with("lz4")))
-            .unwrap_or(false)
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                // Backups from before upgrades end with a build id, for
+                // example `upgrade.jsonlz4-20240101000000`:
+                let ext = ext.split_once('-').map_or(ext, |(ext, _)| ext);
+                ext.ends_with("lz4")
+            })
+    }
+
+    /// `true` if tabs must be grouped by our own code, either because of
+    /// the options or because `firefox_session_data` can't read the data.
+    pub fn uses_own_grouping(&self, options: &GenerateOptions) -> bool {
+        options.uses_own_grouping() || self.source != DataSource::Session
+    }
+    /// Read tabs, bookmarks or another browser's or session manager's data
+    /// from decompressed data. The JSON is also returned for Firefox sessions
+    /// so that it can be parsed by `firefox_session_data`.
+    fn parse_own_session(
+        &self,
+        data: &[u8],
+    ) -> Result<(DataSource, session::Session, Option<serde_json::Value>), Error> {
+        if self.source == DataSource::Chromium {
+            return snss::session_from_snss(data)
+                .map(|session| (DataSource::Chromium, session, None))
+                .map_err(|e| Error::not_session_data(&self.file_path, e));
+        }
+        let json = match serde_json::from_slice::<serde_json::Value>(data) {
+            Ok(json) => json,
+            Err(e) => {
+                return imports::session_from_onetab(data)
+                    .map(|session| (DataSource::OneTab, session, None))
+                    .ok_or_else(|| Error::not_session_data(&self.file_path, e))
+            }
+        };
+        Ok(if bookmarks::is_backup(&json) {
+            (
+                DataSource::Bookmarks,
+                bookmarks::session_from_backup(&json),
+                None,
+            )
+        } else if let Some((source, session)) = imports::session_from_json(&json, &self.containers)
+        {
+            (source, session, None)
+        } else {
+            (
+                DataSource::Session,
+                session::Session::from_value(&json, &self.containers),
+                Some(json),
+            )
+        })
+    }
+    fn own_session(&self) -> Result<&Arc<session::Session>, Error> {
+        self.session.as_ref().ok_or(Error::NotReady {
+            step: "parse sessionstore data before tabs can be regrouped",
+        })
+    }
+    /// Tab groups for groupings that `firefox_session_data` doesn't support.
+    fn own_tab_groups(&self, options: &GenerateOptions) -> Result<AllTabGroups, Error> {
+        let session = self.own_session()?;
+        let to_tab_groups = |open: bool| {
+            session
+                .groups(open, options)
+                .into_iter()
+                .enumerate()
+                .map(|(ix, group)| TabGroup {
+                    index: ix as _,
+                    name: format!("{} ({})", group.name, group.tabs.len()),
+                    color: group.color,
+                    favicon: group.favicon(),
+                    depth: group.depth,
+                    json_path: group.json_path.clone().filter(|_| self.source.is_json()),
+                    sub_groups: if options.shows_native_tab_groups() {
+                        group
+                            .sections()
+                            .into_iter()
+                            .filter_map(|(native_group, tabs)| {
+                                Some(NativeTabGroup {
+                                    name: native_group?.name.clone(),
+                                    color: native_group?.rgb(),
+                                    collapsed: native_group?.collapsed,
+                                    tab_count: tabs.len(),
+                                })
+                            })
+                            .collect()
+                    } else {
+                        Vec::new()
+                    },
+                })
+                .collect::<Vec<_>>()
+        };
+        Ok(AllTabGroups {
+            open: to_tab_groups(true),
+            closed: to_tab_groups(false),
+        })
+    }
+    /// Convert the selected groups to links without using
+    /// `firefox_session_data`.
+    fn own_links(
+        &self,
+        generate_options: &GenerateOptions,
+        options: links::LinkOptions,
+    ) -> Result<String, Error> {
+        let groups = selected_own_groups(self.own_session()?, generate_options);
+        Ok(links::write_links(&groups, options))
+    }
+    /// Like [`Self::own_links`] with the text format but reuses the text of
+    /// groups that are in the cache.
+    fn own_text_preview(
+        &self,
+        generate_options: &GenerateOptions,
+        cache: &PreviewCache,
+    ) -> Result<String, Error> {
+        let session = self.own_session()?;
+        let options = link_options(generate_options, links::LinkFormat::Text, false);
+        let mut cache = cache.lock(PreviewSource::Own(session.clone()), generate_options);
+        let [open, closed] = cache
+            .groups
+            .get_or_insert_with(|| {
+                [true, false].map(|open| session.groups(open, generate_options).into())
+            })
+            .clone();
+
+        fn selected<'a>(
+            open: bool,
+            groups: &'a [session::Group],
+            indexes: &Option<Vec<u32>>,
+        ) -> Vec<((bool, u32), &'a session::Group)> {
+            (0..)
+                .zip(groups)
+                .filter(|(ix, _)| indexes.as_ref().is_none_or(|indexes| indexes.contains(ix)))
+                .map(|(ix, group)| ((open, ix), group))
+                .collect()
+        }
+        let mut groups = selected(true, &open, &generate_options.open_group_indexes);
+        groups.extend(selected(
+            false,
+            &closed,
+            &generate_options.closed_group_indexes,
+        ));
+
+        let mut out = String::new();
+        if options.table_of_contents {
+            links::write_text_table_of_contents(&mut out, groups.iter().map(|(_, group)| *group))
+                .expect("writing to a String never fails");
+        }
+        for (key, group) in groups {
+            let text = cache.group_text.entry(key).or_insert_with(|| {
+                let mut text = String::new();
+                links::write_text_group(&mut text, group, options)
+                    .expect("writing to a String never fails");
+                text.into()
+            });
+            out.push_str(text);
+        }
+        Ok(out)
+    }
+    /// Details about the tab on a line of the preview. `occurrence` is the
+    /// number of earlier lines in the preview that link to the same URL and
+    /// `groups` are the groups that the preview was generated from, so tabs
+    /// with the same URL can be told apart.
+    pub fn inspect_tab(
+        &self,
+        url: &str,
+        occurrence: usize,
+        groups: &AllTabGroups,
+        options: &GenerateOptions,
+    ) -> Option<TabDetails> {
+        let session = self.own_session().ok()?;
+        let own_groups;
+        // The tabs in the order that the preview lists them:
+        let tabs = if self.uses_own_grouping(options) {
+            own_groups = selected_own_groups(session, options);
+            own_groups
+                .iter()
+                .flat_map(|group| &group.tabs)
+                .collect::<Vec<_>>()
+        } else {
+            let windows = match groups.selected_json_windows(options) {
+                Some([open, closed]) => {
+                    [(&session.windows, open), (&session.closed_windows, closed)]
+                        .into_iter()
+                        .flat_map(|(list, indexes)| {
+                            indexes.into_iter().filter_map(move |ix| list.get(ix))
+                        })
+                        .collect::<Vec<_>>()
+                }
+                // Groups that aren't windows are searched in window order:
+                None => session
+                    .windows
+                    .iter()
+                    .chain(&session.closed_windows)
+                    .collect(),
+            };
+            windows
+                .into_iter()
+                .flat_map(|window| &window.tabs)
+                .collect::<Vec<_>>()
+        };
+        let tab = tabs
+            .into_iter()
+            .filter(|tab| tab.url == url)
+            .nth(occurrence)?;
+
+        let windows = (session.windows.iter().enumerate())
+            .map(|(ix, window)| (format!("Window {}", ix + 1), window))
+            .chain(
+                (session.closed_windows.iter().enumerate())
+                    .map(|(ix, window)| (format!("Closed window {}", ix + 1), window)),
+            );
+        // Our own groups hold clones that share `extra` with the tabs in the
+        // windows:
+        let (window_name, window) = windows.into_iter().find(|(_, window)| {
+            window
+                .tabs
+                .iter()
+                .any(|other| Arc::ptr_eq(&other.extra, &tab.extra))
+        })?;
+        let extra = &tab.extra;
+        Some(TabDetails {
+            window: window.name.clone().unwrap_or(window_name),
+            url: tab.url.clone(),
+            title: tab.title.clone(),
+            favicon: extra.favicon.clone(),
+            last_accessed: extra.last_accessed,
+            pinned: extra.pinned,
+            hidden: extra.hidden,
+            muted: extra.muted,
+            container: tab.container_name(),
+            parent: extra.parent_id.as_ref().map(|parent_id| {
+                window
+                    .tabs
+                    .iter()
+                    .find(|other| other.extra.tree_id.as_ref() == Some(parent_id))
+                    .map_or_else(|| parent_id.clone(), |parent| parent.title.clone())
+            }),
+            scroll: extra.scroll.clone(),
+            has_form_data: extra.has_form_data,
+            history_length: extra.history_length,
+            json_path: format!("{}/{}", window.tabs_pointer, extra.json_key),
+        })
+    }
+    /// The selected groups from our own session model. When grouping by window
+    /// `groups` come from `firefox_session_data`, which can sort the windows
+    /// or list panels instead, so they are found by their JSON path. `action`
+    /// describes what the groups are used for in case they aren't windows.
+    fn selected_groups(
+        &self,
+        groups: &AllTabGroups,
+        options: &GenerateOptions,
+        action: &'static str,
+    ) -> Result<Vec<session::Group>, Error> {
+        let session = self.own_session()?;
+        if self.uses_own_grouping(options) {
+            return Ok(selected_own_groups(session, options));
+        }
+        let [open, closed] = groups
+            .selected_json_windows(options)
+            .ok_or(Error::NotWindows { action })?;
+        let windows = |open: bool, indexes: Vec<usize>| {
+            // Windows are listed in the same order as in the JSON:
+            let windows = session.groups(open, &GenerateOptions::default());
+            indexes
+                .into_iter()
+                .filter_map(|ix| windows.get(ix).cloned())
+                .collect::<Vec<_>>()
+        };
+        let mut selected = windows(true, open);
+        selected.extend(windows(false, closed));
+        Ok(selected)
+    }
+    /// URLs of the tabs in the selected groups, one list per group. `groups`
+    /// are the groups that the selection was made from.
+    pub fn selected_urls(
+        &self,
+        groups: &AllTabGroups,
+        generate_options: &GenerateOptions,
+    ) -> Result<Vec<Vec<String>>, Error> {
+        Ok(self
+            .selected_groups(groups, generate_options, "open")?
+            .into_iter()
+            .map(|group| {
+                group
+                    .tabs
+                    .into_iter()
+                    .map(|tab| tab.url)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|urls| !urls.is_empty())
+            .collect())
+    }
+    /// Save the decompressed sessionstore JSON in the format selected by
+    /// [`OutputOptions::json`].
+    pub async fn save_json(
+        &self,
+        save_path: PathBuf,
+        generate_options: GenerateOptions,
+        output_options: OutputOptions,
+    ) -> Result<(), Error> {
+        let json = self.json_data().ok_or(Error::NotReady {
+            step: "decompress sessionstore data before saving it as JSON",
+        })?;
+        let format = output_options.json.unwrap_or(JsonFormat::Raw);
+        let unsupported = Error::UnsupportedFormat {
+            format: format.as_str(),
+        };
+        if !self.source.is_json() {
+            return Err(unsupported);
+        }
+        let selected_windows = if output_options.json_selected_windows {
+            if self.source != DataSource::Session {
+                return Err(unsupported);
+            }
+            let groups = self.get_groups_from_session(&generate_options).await?;
+            Some(
+                groups
+                    .selected_json_windows(&generate_options)
+                    .ok_or(unsupported)?,
+            )
+        } else {
+            None
+        };
+        let file_path = self.file_path.clone();
+        spawn_blocking(move || {
+            let data = if format == JsonFormat::Raw && selected_windows.is_none() {
+                json.to_vec()
+            } else {
+                let not_session_data = |e: serde_json::Error| Error::NotSessionData {
+                    path: file_path.to_path_buf(),
+                    source: error::source(e),
+                };
+                let mut value =
+                    serde_json::from_slice::<serde_json::Value>(&json).map_err(not_session_data)?;
+                if let Some(selected_windows) = &selected_windows {
+                    retain_selected_windows(&mut value, selected_windows);
+                }
+                match format {
+                    JsonFormat::Raw => serde_json::to_vec(&value),
+                    JsonFormat::Pretty => serde_json::to_vec_pretty(&value),
+                }
+                .map_err(not_session_data)?
+            };
+            write_output_file(save_path, &output_options, &data, "json", "sessionstore")
+        })
+        .await
+    }
+}
+
+/// Remove the windows that aren't selected from sessionstore JSON, see
+/// [`AllTabGroups::selected_json_windows`].
+fn retain_selected_windows(json: &mut serde_json::Value, [open, closed]: &[Vec<usize>; 2]) {
+    let mut retain = |key: &str, indexes: &[usize]| -> Option<Vec<bool>> {
+        let windows = json.get_mut(key)?.as_array_mut()?;
+        let kept = (0..windows.len())
+            .map(|ix| indexes.contains(&ix))
+            .collect::<Vec<_>>();
+        let mut kept_iter = kept.iter();
+        windows.retain(|_| *kept_iter.next().unwrap());
+        Some(kept)
+    };
+    let kept = retain("windows", open);
+    retain("_closedWindows", closed);
+
+    // "selectedWindow" is the 1-based index of the focused open window:
+    let Some(kept) = kept else { return };
+    let Some(selected) = json.get_mut("selectedWindow") else {
+        return;
+    };
+    let new_index = selected
+        .as_u64()
+        .and_then(|ix| ix.checked_sub(1))
+        .filter(|&ix| kept.get(ix as usize) == Some(&true))
+        .map(|ix| kept[..ix as usize].iter().filter(|&&kept| kept).count() + 1);
+    *selected = match new_index {
+        Some(ix) => ix.into(),
+        // The focused window was removed, so focus the first window:
+        None => u64::from(kept.contains(&true)).into(),
+    };
+}
+
+/// Decompress data in Firefox's `mozLz40` format that was read from `path`.
+fn decompress_mozlz4(path: &Path, data: &[u8]) -> Result<Vec<u8>, Error> {
+    mozlz4::decompress(data).map_err(|e| match e {
+        mozlz4::Mozlz4Error::NotMozlz4 => Error::NotMozlz4 {
+            path: path.to_owned(),
+        },
+        e => Error::Decompress {
+            path: path.to_owned(),
+            source: error::source(e),
+        },
+    })
+}
+
+/// Compress or decompress any file in Firefox's `mozLz40` format, not just
+/// session files. Writes to [`Mozlz4Mode::default_output_path`] if `output`
+/// is [`None`] and returns the path that was written.
+pub fn convert_mozlz4_file(
+    mode: Mozlz4Mode,
+    input: &Path,
+    output: Option<&Path>,
+    overwrite: bool,
+) -> Result<PathBuf, Error> {
+    use std::io::Write;
+
+    let output = output.map_or_else(|| mode.default_output_path(input), Path::to_owned);
+    let data = std::fs::read(input).map_err(|e| Error::read(input, e))?;
+    let data = match mode {
+        Mozlz4Mode::Decompress => decompress_mozlz4(input, &data)?,
+        Mozlz4Mode::Compress => mozlz4::compress(&data).map_err(|e| Error::Write {
+            path: output.clone(),
+            source: error::source(e),
+        })?,
+    };
+    std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .create_new(!overwrite)
+        .open(&output)
+        .map_err(|e| Error::write(&output, e))?
+        .write_all(&data)
+        .map_err(|e| Error::write(&output, e))?;
+    Ok(output)
+}
+
+/// Like [`convert_mozlz4_file`] but without blocking the UI.
+pub async fn convert_mozlz4(
+    mode: Mozlz4Mode,
+    input: PathBuf,
+    output: Option<PathBuf>,
+    overwrite: bool,
+) -> Result<PathBuf, Error> {
+    spawn_blocking(move || convert_mozlz4_file(mode, &input, output.as_deref(), overwrite)).await
+}
+
+/// Write a file to `save_path`, adding `file_ext` if it has no extension. On
+/// the web the file is downloaded as `{web_name}.{file_ext}` instead.
+#[cfg_attr(target_family = "wasm", expect(unused_mut, unused_variables))]
+fn write_output_file(
+    mut save_path: PathBuf,
+    output_options: &OutputOptions,
+    data: &[u8],
+    file_ext: &str,
+    web_name: &str,
+) -> Result<(), Error> {
+    #[cfg(target_family = "wasm")]
+    {
+        save_file_on_web_target(data, Some(&format!("{web_name}.{file_ext}")))
+    }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        use std::io::Write;
+
+        if save_path.extension().is_none() {
+            save_path.set_extension(file_ext);
+        }
+        if let Some(folder) = save_path.parent() {
+            if output_options.create_folder {
+                std::fs::create_dir_all(folder).map_err(|e| Error::write(folder, e))?;
+            }
+        }
+        std::fs::OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .create_new(!output_options.overwrite)
+            .open(&save_path)
+            .map_err(|e| Error::write(&save_path, e))?
+            .write_all(data)
+            .map_err(|e| Error::write(&save_path, e))
     }
 }
+
+/// Remembers converted text for [`FileInfo::to_text_preview`] so that changing
+/// which groups are selected only converts groups that weren't shown before.
+/// Clones share the same cache.
+#[derive(Debug, Clone, Default)]
+pub struct PreviewCache(Arc<Mutex<PreviewCacheState>>);
+
+#[derive(Debug, Default)]
+struct PreviewCacheState {
+    /// The session that the cached text was generated from.
+    session: Option<PreviewSource>,
+    /// The options the text was generated with, without the selected groups.
+    options: Option<GenerateOptions>,
+    /// Open and closed groups from our own code, grouped with `options`.
+    groups: Option<[Arc<[session::Group]>; 2]>,
+    /// Text for each group, by whether the group is open and its index.
+    group_text: HashMap<(bool, u32), Arc<str>>,
+}
+
+/// The session that a preview was generated from, either by our own code or
+/// by `firefox_session_data`.
+#[derive(Debug, Clone)]
+enum PreviewSource {
+    Own(Arc<session::Session>),
+    Firefox(Arc<FirefoxSessionStore>),
+}
+impl PreviewSource {
+    fn is_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Own(a), Self::Own(b)) => Arc::ptr_eq(a, b),
+            (Self::Firefox(a), Self::Firefox(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl PreviewCache {
+    /// Lock the cache and clear it if it was used for another session or for
+    /// options that affect the text of each group.
+    fn lock(
+        &self,
+        session: PreviewSource,
+        options: &GenerateOptions,
+    ) -> MutexGuard<'_, PreviewCacheState> {
+        let mut state = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let key = GenerateOptions {
+            open_group_indexes: None,
+            closed_group_indexes: None,
+            ..options.clone()
+        };
+        let same_session = state
+            .session
+            .as_ref()
+            .is_some_and(|cached| cached.is_same(&session));
+        if !same_session || state.options.as_ref() != Some(&key) {
+            *state = PreviewCacheState {
+                session: Some(session),
+                options: Some(key),
+                ..Default::default()
+            };
+        }
+        state
+    }
+
+    /// Number of groups whose text is cached.
+    pub fn cached_groups(&self) -> usize {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .group_text
+            .len()
+    }
+}
+
+/// Options for [`open_urls_in_browser`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenInBrowserOptions {
+    /// Command used to start the browser, uses the local Firefox installation
+    /// if empty. Ignored on the web.
+    pub browser_command: String,
+    /// Open each group of URLs in a new browser window.
+    pub window_per_group: bool,
+}
+impl Default for OpenInBrowserOptions {
+    fn default() -> Self {
+        Self {
+            browser_command: String::new(),
+            window_per_group: true,
+        }
+    }
+}
+
+/// The program and arguments that should be used to start the browser.
+#[cfg(not(target_family = "wasm"))]
+fn browser_command(command: &str) -> (PathBuf, Vec<String>) {
+    let command = command.trim();
+    if command.is_empty() {
+        let candidates: &[&str] = if cfg!(windows) {
+            &[
+                r"C:\Program Files\Mozilla Firefox\firefox.exe",
+                r"C:\Program Files (x86)\Mozilla Firefox\firefox.exe",
+            ]
+        } else if cfg!(target_os = "macos") {
+            &["/Applications/Firefox.app/Contents/MacOS/firefox"]
+        } else {
+            &[]
+        };
+        let program = candidates
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.exists())
+            .unwrap_or_else(|| PathBuf::from("firefox"));
+        (program, Vec::new())
+    } else if Path::new(command).exists() {
+        // A path to an executable that might contain spaces:
+        (PathBuf::from(command), Vec::new())
+    } else {
+        // Split like a shell would so that quoted paths can contain spaces.
+        // Backslashes in Windows paths aren't escapes:
+        let shell_words = if cfg!(windows) {
+            command.replace('\\', "\\\\")
+        } else {
+            command.to_owned()
+        };
+        let mut parts = shlex::split(&shell_words)
+            .unwrap_or_else(|| command.split_whitespace().map(ToOwned::to_owned).collect())
+            .into_iter();
+        let program = PathBuf::from(parts.next().unwrap_or_default());
+        (program, parts.collect())
+    }
+}
+
+/// Open URLs in a browser. On the web this uses `window.open` and fails at the
+/// first tab that the browser's popup blocker stops.
+pub async fn open_urls_in_browser(
+    groups: Vec<Vec<String>>,
+    options: OpenInBrowserOptions,
+) -> Result<(), Error> {
+    #[cfg(target_family = "wasm")]
+    {
+        let window = web_sys::window().ok_or_else(|| Error::Web {
+            action: "find the global window".into(),
+        })?;
+        for url in groups.iter().flatten() {
+            // Blocked popups don't give a window:
+            if !matches!(window.open_with_url_and_target(url, "_blank"), Ok(Some(_))) {
+                return Err(Error::Web {
+                    action: format!("open {url}, allow popups for this page and try again"),
+                });
+            }
+        }
+        Ok(())
+    }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        spawn_blocking(move || {
+            let (program, args) = browser_command(&options.browser_command);
+            let groups = if options.window_per_group {
+                groups
+            } else {
+                vec![groups.concat()]
+            };
+            for (ix, urls) in groups.iter().enumerate() {
+                let Some((first, rest)) = urls.split_first() else {
+                    continue;
+                };
+                if ix > 0 {
+                    // Give the browser time to open the previous window,
+                    // otherwise tabs might end up in the wrong window:
+                    std::thread::sleep(std::time::Duration::from_millis(500));
+                }
+
+                let mut command = std::process::Command::new(&program);
+                command.args(&args);
+                if options.window_per_group {
+                    command.arg("--new-window");
+                }
+                command.arg(first);
+                command.args(rest);
+                let mut child = command.spawn().map_err(|e| Error::Browser {
+                    program: program.clone(),
+                    source: error::source(e),
+                })?;
+                // Don't wait here since the process might be the browser
+                // itself if it wasn't running already, but wait on another
+                // thread so that the exited process is cleaned up:
+                std::thread::spawn(move || child.wait());
+            }
+            Ok(())
+        })
+        .await
+    }
+}
+
+/// The groups from our own session model that are selected by the generate
+/// options.
+fn selected_own_groups(
+    session: &session::Session,
+    generate_options: &GenerateOptions,
+) -> Vec<session::Group> {
+    let filter = |open: bool, indexes: &Option<Vec<u32>>| {
+        session
+            .groups(open, generate_options)
+            .into_iter()
+            .enumerate()
+            .filter(|(ix, _)| {
+                indexes
+                    .as_ref()
+                    .is_none_or(|indexes| indexes.contains(&(*ix as u32)))
+            })
+            .map(|(_, g)| g)
+            .collect::<Vec<_>>()
+    };
+    let mut groups = filter(true, &generate_options.open_group_indexes);
+    groups.extend(filter(false, &generate_options.closed_group_indexes));
+    groups
+}
+
+fn link_options(
+    generate_options: &GenerateOptions,
+    format: links::LinkFormat,
+    page_breaks_after_group: bool,
+) -> links::LinkOptions {
+    links::LinkOptions {
+        format,
+        table_of_contents: generate_options.table_of_content,
+        page_breaks_after_group,
+        native_groups: generate_options.shows_native_tab_groups(),
+        favicons: false,
+    }
+}
+
+/// The windows that were selected in a loaded session, to be exported together
+/// with windows from other sessions by [`save_merged_links`].
+#[derive(Debug, Clone)]
+pub struct MergeSource {
+    /// Heading for the source's groups in the merged document.
+    pub name: String,
+    pub file_path: Arc<PathBuf>,
+    groups: Arc<[session::Group]>,
+}
+impl MergeSource {
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+    pub fn tab_count(&self) -> usize {
+        self.groups.iter().map(|group| group.tabs.len()).sum()
+    }
+}
+
+impl FileInfo {
+    /// Remember the groups that `generate_options` selects from `groups` so
+    /// that they can be merged with groups from other files. The source is
+    /// named after the file and the folder it is in, since most session files
+    /// have the same name.
+    pub fn merge_source(
+        &self,
+        groups: &AllTabGroups,
+        generate_options: &GenerateOptions,
+    ) -> Result<MergeSource, Error> {
+        let selected = self.selected_groups(groups, generate_options, "merge")?;
+        let mut components = self
+            .file_path
+            .components()
+            .rev()
+            .take(2)
+            .collect::<Vec<_>>();
+        components.reverse();
+        let name = components
+            .iter()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        Ok(MergeSource {
+            name,
+            file_path: self.file_path.clone(),
+            groups: selected.into(),
+        })
+    }
+}
+
+/// Groups of each source, without tabs whose URL was in an earlier group if
+/// `deduplicate` is `true`. Groups that end up empty are left out.
+fn merged_groups(sources: &[MergeSource], deduplicate: bool) -> Vec<Vec<session::Group>> {
+    let mut seen = HashSet::new();
+    sources
+        .iter()
+        .map(|source| {
+            if !deduplicate {
+                return source.groups.to_vec();
+            }
+            source
+                .groups
+                .iter()
+                .filter_map(|group| {
+                    let tabs = group
+                        .tabs
+                        .iter()
+                        .filter(|tab| seen.insert(tab.url.clone()))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    (!tabs.is_empty()).then(|| session::Group {
+                        tabs,
+                        ..group.clone()
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Write the groups from several sessions into one document, with a heading
+/// for each source. Uses the table of contents and native tab group options
+/// from `generate_options`.
+pub async fn save_merged_links(
+    sources: Vec<MergeSource>,
+    deduplicate: bool,
+    save_path: PathBuf,
+    generate_options: GenerateOptions,
+    output_options: OutputOptions,
+) -> Result<(), Error> {
+    spawn_blocking(move || {
+        let groups = merged_groups(&sources, deduplicate);
+        let sources = sources
+            .iter()
+            .zip(&groups)
+            .map(|(source, groups)| links::Source {
+                name: Some(&source.name),
+                groups,
+            })
+            .collect::<Vec<_>>();
+        let (data, file_ext) = write_own_links(&sources, &generate_options, &output_options)?;
+        write_output_file(save_path, &output_options, &data, file_ext, "firefox-links")
+    })
+    .await
+}
+
+/// Write links with our own code in the selected output format. Returns the
+/// data and its file extension.
+fn write_own_links(
+    sources: &[links::Source<'_>],
+    generate_options: &GenerateOptions,
+    output_options: &OutputOptions,
+) -> Result<(Vec<u8>, &'static str), Error> {
+    // Only `firefox_session_data` can write PDF documents with links:
+    let format = own_link_format(output_options.format).ok_or(Error::UnsupportedFormat {
+        format: output_options.format.as_str(),
+    })?;
+    let page_breaks = !matches!(format, links::LinkFormat::Text);
+    let output = links::write_sources(
+        sources,
+        links::LinkOptions {
+            favicons: output_options.embed_favicons,
+            ..link_options(generate_options, format, page_breaks)
+        },
+    );
+    Ok((output.into_bytes(), format.file_extension()))
+}
+
+/// The format to use when writing links ourself, [`None`] if the format
+/// isn't supported (i.e. PDF output).
+#[cfg(feature = "real_data")]
+fn own_link_format(format: FormatInfo) -> Option<links::LinkFormat> {
+    use firefox_session_data::session_store::to_links::LinkFormat;
+
+    let (format, as_pdf) = format.as_format().to_link_format();
+    if as_pdf.is_some() {
+        return None;
+    }
+    Some(match format {
+        LinkFormat::TXT => links::LinkFormat::Text,
+        LinkFormat::RTF { .. } => links::LinkFormat::Rtf,
+        LinkFormat::HTML => links::LinkFormat::Html,
+        LinkFormat::Markdown => links::LinkFormat::Markdown,
+        LinkFormat::Typst => links::LinkFormat::Typst,
+    })
+}
+/// Convert groups from `firefox_session_data` to the text format. A macro since
+/// the type of the groups can't be named.
+#[cfg(feature = "real_data")]
+macro_rules! firefox_text_links {
+    ($groups:expr, $table_of_contents:expr) => {{
+        use firefox_session_data::{
+            pdf_converter::html_to_pdf::WriteBuilderSimple,
+            session_store::{
+                session_info::TreeDataSource, to_links::LinkFormat, to_links::ToLinksOptions,
+            },
+            to_links::TabsToLinksOutput,
+        };
+
+        let mut output: Vec<u8> = Vec::new();
+        firefox_session_data::tabs_to_links(
+            $groups,
+            TabsToLinksOutput {
+                format: LinkFormat::TXT,
+                as_pdf: None,
+                conversion_options: ToLinksOptions {
+                    format: LinkFormat::TXT,
+                    page_breaks_after_group: false, // We don't have any page break character in raw text
+                    skip_page_break_after_last_group: true,
+                    table_of_contents: $table_of_contents,
+                    indent_all_links: true,
+                    custom_page_break: "".into(),
+                    // If there is any data from Sidebery then TST data
+                    // won't be used and so on:
+                    tree_sources: (&[
+                        TreeDataSource::Sidebery,
+                        TreeDataSource::TstWebExtension,
+                        TreeDataSource::TstLegacy,
+                    ] as &[_])
+                        .into(),
+                },
+            },
+            WriteBuilderSimple(&mut output),
+        )
+        .map_err(|e| Error::Convert {
+            source: error::source(e.to_string()),
+        })
+        .map(|()| String::from_utf8_lossy(&output).into_owned())
+    }};
+}
+
 #[cfg(feature = "real_data")]
 impl FileInfo {
-    pub async fn load_data(&mut self) -> Result<(), String> {
+    pub async fn load_data(&mut self) -> Result<(), Error> {
         if self.data.is_some() {
             return Ok(());
         }
@@ -320,7 +1610,10 @@ impl FileInfo {
         let data = self
             .file_handle
             .as_ref()
-            .ok_or("no file handle for the specified path")?
+            // Can only read files that the user picked:
+            .ok_or_else(|| Error::NotFound {
+                path: self.file_path.to_path_buf(),
+            })?
             .0
             .read()
             .await;
@@ -333,122 +1626,204 @@ impl FileInfo {
             };
 
             let path = self.file_path.clone();
-            spawn_blocking(move || -> Result<_, String> {
-                let file = File::open(&*path)
-                    .map_err(|e| format!("failed to open file at {}: {e}", path.display()))?;
+            spawn_blocking(move || -> Result<_, Error> {
+                let file = File::open(&*path).map_err(|e| Error::read(&path, e))?;
 
                 let mut buffer = BufReader::new(file);
                 let mut data = Vec::new();
 
-                buffer.read_to_end(&mut data).map_err(|e| {
-                    format!("failed to read file data from {}: {e}", path.display())
-                })?;
+                buffer
+                    .read_to_end(&mut data)
+                    .map_err(|e| Error::read(&path, e))?;
 
                 Ok(data)
             })
             .await?
         };
 
-        let data = Arc::from(data);
-        self.data = Some(if self.is_compressed_file_format() {
-            FileData::Compressed(data)
+        #[cfg(not(target_family = "wasm"))]
+        {
+            let path = self.file_path.clone();
+            self.containers = spawn_blocking(move || {
+                FirefoxProfileInfo::for_session_file(&path)
+                    .map(|profile| profile.load_containers())
+                    .unwrap_or(Ok(Vec::new()))
+            })
+            .await
+            .unwrap_or_else(|e| {
+                // Containers are optional so just continue without them:
+                log::warn!("Failed to load Firefox containers: {e}");
+                Vec::new()
+            })
+            .into();
+        }
+
+        // Chromium session files are detected by their content since they
+        // don't have a file extension:
+        self.source = if snss::is_snss(&data) {
+            DataSource::Chromium
         } else {
-            FileData::Uncompressed(data)
-        });
+            DataSource::Session
+        };
+        let data = Arc::from(data);
+        self.data = Some(
+            if self.is_compressed_file_format() && self.source == DataSource::Session {
+                FileData::Compressed(data)
+            } else {
+                FileData::Uncompressed(data)
+            },
+        );
 
         Ok(())
     }
-    pub async fn decompress_data(&mut self) -> Result<(), String> {
-        let data = match self
-            .data
-            .as_ref()
-            .ok_or("Tried to decompress data before reading it")?
-        {
+    pub async fn decompress_data(&mut self) -> Result<(), Error> {
+        let data = match self.data.as_ref().ok_or(Error::NotReady {
+            step: "read the file before decompressing its data",
+        })? {
             FileData::Compressed(data) => data.clone(),
             FileData::Uncompressed(_) | FileData::Parsed(_) => return Ok(()),
         };
-        let decompressed = spawn_blocking(move || {
-            firefox_session_data::io_utils::decompress_lz4_data(Either::<_, Empty>::Left(
-                Vec::<u8>::from(&*data).into(),
-            ))
-            .map(|reader| -> Vec<u8> { reader.into() })
-            .map_err(|e| format!("failed to decompress data: {e}"))
-        })
-        .await?;
+        let path = self.file_path.clone();
+        let decompressed = spawn_blocking(move || decompress_mozlz4(&path, &data)).await?;
 
         self.data = Some(FileData::Uncompressed(Arc::from(decompressed)));
         Ok(())
     }
-    pub async fn parse_session_data(&mut self) -> Result<(), String> {
-        let data = match self
-            .data
-            .as_ref()
-            .ok_or("Tried to parse data before reading it")?
-        {
-            FileData::Compressed(_) => return Err("can't parse compressed data".to_string()),
+    pub async fn parse_session_data(&mut self) -> Result<(), Error> {
+        let data = match self.data.as_ref().ok_or(Error::NotReady {
+            step: "read the file before parsing its data",
+        })? {
+            FileData::Compressed(_) => {
+                return Err(Error::NotReady {
+                    step: "decompress data before parsing it",
+                })
+            }
+            // Bookmarks and other exports are only parsed by our own code:
+            FileData::Uncompressed(_) if self.session.is_some() => return Ok(()),
             FileData::Uncompressed(data) => data.clone(),
             FileData::Parsed(_) => return Ok(()),
         };
-        let session = spawn_blocking(move || {
-            serde_json::from_slice::<FirefoxSessionStore>(&data)
-                .map_err(|e| format!("failed to parse sessionstore JSON data: {e}"))
+        let json = data.clone();
+        let this = self.clone();
+        let path = self.file_path.clone();
+        let (source, session, tabs) = spawn_blocking(move || {
+            let not_session_data = |e| Error::NotSessionData {
+                path: path.to_path_buf(),
+                source: error::source(e),
+            };
+            let (source, tabs, value) = this.parse_own_session(&data)?;
+            let session = match value {
+                Some(value) => Some(
+                    serde_json::from_value::<FirefoxSessionStore>(value)
+                        .map_err(not_session_data)?,
+                ),
+                None => None,
+            };
+            Ok::<_, Error>((source, session, tabs))
         })
         .await?;
 
-        self.data = Some(FileData::Parsed(Arc::new(session)));
+        if let Some(session) = session {
+            self.data = Some(FileData::Parsed(Arc::new(session)));
+        }
+        self.json = source.is_json().then_some(json);
+        self.source = source;
+        self.session = Some(Arc::new(tabs));
         Ok(())
     }
-    pub async fn get_groups_from_session(&self, sort_groups: bool) -> Result<AllTabGroups, String> {
+    pub async fn get_groups_from_session(
+        &self,
+        options: &GenerateOptions,
+    ) -> Result<AllTabGroups, Error> {
         use firefox_session_data::session_store::session_info::get_groups_from_session;
 
+        if self.uses_own_grouping(options) {
+            let this = self.clone();
+            let options = options.clone();
+            return spawn_blocking(move || this.own_tab_groups(&options)).await;
+        }
+        let sort_groups = options.sort_groups;
+
         let session = self
             .data
             .as_ref()
             .and_then(FileData::as_parsed)
             .cloned()
-            .ok_or("must deserialize JSON sessionstore data before tab groups can be inspected")?;
-
-        Ok(spawn_blocking(move || AllTabGroups {
-            open: get_groups_from_session(&session, true, false, sort_groups)
-                .enumerate()
-                .map(|(ix, group)| TabGroup {
-                    index: ix as _,
-                    name: group.name().to_owned(),
-                })
-                .collect::<Vec<_>>(),
-            closed: get_groups_from_session(&session, false, true, sort_groups)
-                .enumerate()
-                .map(|(ix, group)| TabGroup {
-                    index: ix as _,
-                    name: group.name().to_owned(),
-                })
-                .collect::<Vec<_>>(),
+            .ok_or(Error::NotReady {
+                step: "parse sessionstore data before tab groups can be inspected",
+            })?;
+
+        // Favicons and JSON paths come from our own grouping by window. Its
+        // groups are matched by name, which contains the window's position,
+        // or else by position if the groups weren't sorted. Tree Style Tab
+        // and Sidebery data make `firefox_session_data` list panels or trees
+        // that aren't windows, so nothing is matched then:
+        let own_session = self.session.clone().filter(|tabs| !tabs.has_tree_data());
+        Ok(spawn_blocking(move || {
+            let own_groups = |open: bool| {
+                own_session
+                    .as_ref()
+                    .map(|tabs| tabs.groups(open, &GenerateOptions::default()))
+                    .unwrap_or_default()
+            };
+            let to_tab_groups = |open: bool| {
+                let own_groups = own_groups(open);
+                let by_name = own_groups
+                    .iter()
+                    .map(|group| (group.name.as_str(), group))
+                    .collect::<HashMap<_, _>>();
+                get_groups_from_session(&session, open, !open, sort_groups)
+                    .enumerate()
+                    .map(|(ix, group)| {
+                        let window = by_name
+                            .get(group.name())
+                            .copied()
+                            .or_else(|| own_groups.get(ix).filter(|_| !sort_groups));
+                        TabGroup {
+                            index: ix as _,
+                            name: group.name().to_owned(),
+                            color: None,
+                            sub_groups: Vec::new(),
+                            favicon: window.and_then(|window| window.favicon()),
+                            depth: 0,
+                            json_path: window.and_then(|window| window.json_path.clone()),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            };
+            AllTabGroups {
+                open: to_tab_groups(true),
+                closed: to_tab_groups(false),
+            }
         })
         .await)
     }
 
     /// Generate a text only representation of the sessionstore data.
-    pub async fn to_text_links(&self, generate_options: GenerateOptions) -> Result<String, String> {
-        use firefox_session_data::{
-            pdf_converter::html_to_pdf::WriteBuilderSimple,
-            session_store::{
-                session_info::{get_groups_from_session, TreeDataSource},
-                to_links::LinkFormat,
-                to_links::ToLinksOptions,
-            },
-            to_links::TabsToLinksOutput,
-        };
+    pub async fn to_text_links(&self, generate_options: GenerateOptions) -> Result<String, Error> {
+        use firefox_session_data::session_store::session_info::get_groups_from_session;
+
+        if self.uses_own_grouping(&generate_options) {
+            let this = self.clone();
+            return spawn_blocking(move || {
+                this.own_links(
+                    &generate_options,
+                    link_options(&generate_options, links::LinkFormat::Text, false),
+                )
+            })
+            .await;
+        }
 
         let session = self
             .data
             .as_ref()
             .and_then(FileData::as_parsed)
             .cloned()
-            .ok_or("must deserialize JSON sessionstore data before converting tabs to links")?;
+            .ok_or(Error::NotReady {
+                step: "parse sessionstore data before converting tabs to links",
+            })?;
 
         spawn_blocking(move || {
-            let mut output: Vec<u8> = Vec::new();
-
             let open_groups =
                 get_groups_from_session(&session, true, false, generate_options.sort_groups)
                     .enumerate()
@@ -473,43 +1848,92 @@ impl FileInfo {
                     })
                     .map(|(_, g)| g);
 
-            firefox_session_data::tabs_to_links(
+            firefox_text_links!(
                 &open_groups.chain(closed_groups).collect::<Vec<_>>(),
-                TabsToLinksOutput {
-                    format: LinkFormat::TXT,
-                    as_pdf: None,
-                    conversion_options: ToLinksOptions {
-                        format: LinkFormat::TXT,
-                        page_breaks_after_group: false, // We don't have any page break character in raw text
-                        skip_page_break_after_last_group: true,
-                        table_of_contents: generate_options.table_of_content,
-                        indent_all_links: true,
-                        custom_page_break: "".into(),
-                        // If there is any data from Sidebery then TST data
-                        // won't be used and so on:
-                        tree_sources: (&[
-                            TreeDataSource::Sidebery,
-                            TreeDataSource::TstWebExtension,
-                            TreeDataSource::TstLegacy,
-                        ] as &[_])
-                            .into(),
-                    },
-                },
-                WriteBuilderSimple(&mut output),
+                generate_options.table_of_content
             )
-            .map_err(|e| e.to_string())?;
+        })
+        .await
+    }
+    /// The text shown in the preview. Has the same links as
+    /// [`Self::to_text_links`] but each group is converted on its own so that
+    /// its text can be reused when other groups are selected.
+    pub async fn to_text_preview(
+        &self,
+        generate_options: GenerateOptions,
+        cache: PreviewCache,
+    ) -> Result<String, Error> {
+        use firefox_session_data::session_store::session_info::get_groups_from_session;
+
+        if self.uses_own_grouping(&generate_options) {
+            let this = self.clone();
+            return spawn_blocking(move || this.own_text_preview(&generate_options, &cache)).await;
+        }
+
+        let session = self
+            .data
+            .as_ref()
+            .and_then(FileData::as_parsed)
+            .cloned()
+            .ok_or(Error::NotReady {
+                step: "parse sessionstore data before converting tabs to links",
+            })?;
+        spawn_blocking(move || {
+            let mut cache = cache.lock(PreviewSource::Firefox(session.clone()), &generate_options);
+            let mut names = Vec::new();
+            let mut texts = Vec::new();
+            for (open, indexes) in [
+                (true, &generate_options.open_group_indexes),
+                (false, &generate_options.closed_group_indexes),
+            ] {
+                let groups =
+                    get_groups_from_session(&session, open, !open, generate_options.sort_groups);
+                for (ix, group) in (0..).zip(groups) {
+                    if indexes
+                        .as_ref()
+                        .is_some_and(|indexes| !indexes.contains(&ix))
+                    {
+                        continue;
+                    }
+                    names.push(group.name().to_owned());
+                    let text = match cache.group_text.get(&(open, ix)) {
+                        Some(text) => text.clone(),
+                        None => {
+                            let text = Arc::<str>::from(firefox_text_links!(
+                                std::slice::from_ref(&group),
+                                false
+                            )?);
+                            cache.group_text.insert((open, ix), text.clone());
+                            text
+                        }
+                    };
+                    texts.push(text);
+                }
+            }
 
-            Ok(String::from_utf8_lossy(&output).into_owned())
+            // Each group was converted on its own so the table of contents
+            // from `firefox_session_data` can't be used, list the names here:
+            let mut out = String::new();
+            if generate_options.table_of_content {
+                links::write_text_table_of_contents_names(
+                    &mut out,
+                    names.iter().map(String::as_str),
+                )
+                .expect("writing to a String never fails");
+            }
+            for text in texts {
+                out.push_str(&text);
+            }
+            Ok(out)
         })
         .await
     }
-    #[cfg_attr(target_family = "wasm", expect(unused_mut, unused_variables))]
     pub async fn save_links(
         &self,
-        mut save_path: PathBuf,
+        save_path: PathBuf,
         generate_options: GenerateOptions,
         output_options: OutputOptions,
-    ) -> Result<(), String> {
+    ) -> Result<(), Error> {
         use firefox_session_data::{
             pdf_converter::html_to_pdf::WriteBuilderSimple,
             session_store::{
@@ -520,12 +1944,41 @@ impl FileInfo {
             to_links::TabsToLinksOutput,
         };
 
+        // Groupings that firefox_session_data doesn't know about, and
+        // favicons, are written by our own code:
+        let own_grouping = self.uses_own_grouping(&generate_options);
+        if own_grouping || (output_options.embed_favicons && output_options.can_embed_favicons()) {
+            // The selected windows are found through the sidebar's groups:
+            let groups = if own_grouping {
+                AllTabGroups::default()
+            } else {
+                self.get_groups_from_session(&generate_options).await?
+            };
+            let this = self.clone();
+            return spawn_blocking(move || {
+                let groups =
+                    this.selected_groups(&groups, &generate_options, "embed favicons for")?;
+                let (data, file_ext) = write_own_links(
+                    &[links::Source {
+                        name: None,
+                        groups: &groups,
+                    }],
+                    &generate_options,
+                    &output_options,
+                )?;
+                write_output_file(save_path, &output_options, &data, file_ext, "firefox-links")
+            })
+            .await;
+        }
+
         let session = self
             .data
             .as_ref()
             .and_then(FileData::as_parsed)
             .cloned()
-            .ok_or("must deserialize JSON sessionstore data before converting tabs to links")?;
+            .ok_or(Error::NotReady {
+                step: "parse sessionstore data before converting tabs to links",
+            })?;
 
         spawn_blocking(move || {
             let (format, as_pdf) = output_options.format.as_format().to_link_format();
@@ -542,39 +1995,7 @@ impl FileInfo {
                 }
             };
 
-            let mut file = {
-                #[cfg(target_family = "wasm")]
-                {
-                    Vec::new()
-                }
-                #[cfg(not(target_family = "wasm"))]
-                {
-                    if save_path.extension().is_none() {
-                        save_path.set_extension(file_ext);
-                    }
-
-                    if let Some(folder) = save_path.parent() {
-                        if output_options.create_folder {
-                            std::fs::create_dir_all(folder).map_err(|e| {
-                                format!("failed to create folder at \"{}\": {e}", folder.display())
-                            })?;
-                        }
-                    }
-
-                    std::fs::OpenOptions::new()
-                        .write(true)
-                        .truncate(true)
-                        .create(true)
-                        .create_new(!output_options.overwrite)
-                        .open(&save_path)
-                        .map_err(|e| {
-                            format!(
-                                "failed to create new file at \"{}\": {e}",
-                                save_path.display()
-                            )
-                        })?
-                }
-            };
+            let page_breaks = !matches!(output_options.format, FormatInfo::TEXT);
 
             let open_groups =
                 get_groups_from_session(&session, true, false, generate_options.sort_groups)
@@ -600,7 +2021,7 @@ impl FileInfo {
                     })
                     .map(|(_, g)| g);
 
-            let page_breaks = !matches!(output_options.format, FormatInfo::TEXT);
+            let mut file = Vec::new();
             firefox_session_data::tabs_to_links(
                 &open_groups.chain(closed_groups).collect::<Vec<_>>(),
                 TabsToLinksOutput {
@@ -626,12 +2047,11 @@ impl FileInfo {
                 },
                 WriteBuilderSimple(&mut file),
             )
-            .map_err(|e| e.to_string())?;
-
-            #[cfg(target_family = "wasm")]
-            save_file_on_web_target(file.as_slice(), Some(&format!("firefox-links.{file_ext}")))?;
+            .map_err(|e| Error::Convert {
+                source: error::source(e.to_string()),
+            })?;
 
-            Ok(())
+            write_output_file(save_path, &output_options, &file, file_ext, "firefox-links")
         })
         .await
     }
@@ -644,26 +2064,29 @@ impl FileInfo {
 /// <https://stackoverflow.com/questions/54626186/how-to-download-file-with-javascript>
 /// <https://stackoverflow.com/questions/44147912/arraybuffer-to-blob-conversion>
 #[cfg(target_family = "wasm")]
-fn save_file_on_web_target(data: &[u8], file_name: Option<&str>) -> Result<(), String> {
+fn save_file_on_web_target(data: &[u8], file_name: Option<&str>) -> Result<(), Error> {
     use wasm_bindgen::JsCast;
 
+    let web_error = |action: &str| Error::Web {
+        action: action.to_owned(),
+    };
+
     let byte_array = js_sys::Uint8Array::new_with_length(
         data.len()
             .try_into()
-            .map_err(|e| format!("Output file size was larger than a 32 bit number {e}"))?,
+            .map_err(|_| web_error("save file since its size was larger than a 32 bit number"))?,
     );
     byte_array.copy_from(data);
     let array = js_sys::Array::of1(&byte_array);
-    let blob = web_sys::Blob::new_with_u8_array_sequence(&array)
-        .ok()
-        .ok_or("Blob creation failed")?;
+    let blob =
+        web_sys::Blob::new_with_u8_array_sequence(&array).map_err(|_| web_error("create Blob"))?;
 
     let a_tag: web_sys::HtmlAnchorElement = web_sys::window()
-        .ok_or("no global window")?
+        .ok_or_else(|| web_error("find the global window"))?
         .document()
-        .ok_or("no \"window.document\"")?
+        .ok_or_else(|| web_error("find \"window.document\""))?
         .create_element("a")
-        .map_err(|_| "failed to create \"a\" tag")?
+        .map_err(|_| web_error("create \"a\" tag"))?
         .unchecked_into();
 
     if let Some(file_name) = file_name {
@@ -671,16 +2094,13 @@ fn save_file_on_web_target(data: &[u8], file_name: Option<&str>) -> Result<(), S
     }
 
     let url = web_sys::Url::create_object_url_with_blob(&blob)
-        .ok()
-        .ok_or("url creation failed")?;
+        .map_err(|_| web_error("create object URL"))?;
 
     a_tag.set_href(&url);
 
     a_tag.click();
 
-    web_sys::Url::revoke_object_url(&url)
-        .ok()
-        .ok_or("url revoke failed")?;
+    web_sys::Url::revoke_object_url(&url).map_err(|_| web_error("revoke object URL"))?;
 
     Ok(())
 }
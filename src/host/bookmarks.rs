@@ -0,0 +1,118 @@
+//! Read Firefox bookmark backups (`bookmarkbackups/bookmarks-*.jsonlz4` in a
+//! profile) into the same model as sessions, so that bookmarks can be
+//! browsed, grouped and exported like tabs.
+//!
+//! Every bookmark folder becomes a [`Window`] whose tabs are the bookmarks
+//! directly inside it. Folders are listed depth first, so the windows form the
+//! folder tree when indented by [`Window::depth`].
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use super::session::{Session, Tab, TabExtra, Window};
+
+const FOLDER_TYPE: &str = "text/x-moz-place-container";
+const BOOKMARK_TYPE: &str = "text/x-moz-place";
+
+/// `true` if the JSON is a bookmark backup rather than sessionstore data.
+pub fn is_backup(json: &Value) -> bool {
+    json.get("root").and_then(Value::as_str) == Some("placesRoot")
+        || json.get("guid").and_then(Value::as_str) == Some("root________")
+}
+
+/// Read all bookmark folders from a backup.
+pub fn session_from_backup(json: &Value) -> Session {
+    let mut windows = Vec::new();
+    // The root folder only contains the special folders like the toolbar:
+    for (ix, child) in children(json) {
+        add_folder(&mut windows, child, format!("/children/{ix}"), 0);
+    }
+    Session {
+        windows,
+        ..Session::default()
+    }
+}
+
+fn children(json: &Value) -> impl Iterator<Item = (usize, &Value)> {
+    json.get("children")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+}
+
+fn add_folder(windows: &mut Vec<Window>, json: &Value, json_pointer: String, depth: usize) {
+    if json.get("type").and_then(Value::as_str) != Some(FOLDER_TYPE) {
+        return;
+    }
+    let tabs = children(json)
+        .filter_map(|(ix, child)| bookmark(child, ix))
+        .collect();
+    windows.push(Window {
+        tabs,
+        native_groups: Vec::new(),
+        name: Some(folder_title(json)),
+        depth,
+        json_pointer: json_pointer.clone(),
+        tabs_pointer: format!("{json_pointer}/children"),
+    });
+    for (ix, child) in children(json) {
+        add_folder(
+            windows,
+            child,
+            format!("{json_pointer}/children/{ix}"),
+            depth + 1,
+        );
+    }
+}
+
+/// The special folders have short internal titles like `menu`.
+fn folder_title(json: &Value) -> String {
+    let title = json.get("title").and_then(Value::as_str).unwrap_or("");
+    let special = match json.get("root").and_then(Value::as_str) {
+        Some("bookmarksMenuFolder") => "Bookmarks Menu",
+        Some("toolbarFolder") => "Bookmarks Toolbar",
+        Some("unfiledBookmarksFolder") => "Other Bookmarks",
+        Some("mobileFolder") => "Mobile Bookmarks",
+        _ if title.is_empty() => "Unnamed folder",
+        _ => title,
+    };
+    special.to_owned()
+}
+
+fn bookmark(json: &Value, json_index: usize) -> Option<Tab> {
+    if json.get("type").and_then(Value::as_str) != Some(BOOKMARK_TYPE) {
+        return None;
+    }
+    let url = json.get("uri")?.as_str()?;
+    // Saved searches like "place:sort=8" can't be opened as links:
+    if url.starts_with("place:") {
+        return None;
+    }
+    let title = json
+        .get("title")
+        .and_then(Value::as_str)
+        .filter(|title| !title.is_empty())
+        .unwrap_or(url);
+    Some(Tab {
+        url: url.to_owned(),
+        title: title.to_owned(),
+        user_context_id: 0,
+        container: None,
+        native_group: None,
+        extra: Arc::new(TabExtra {
+            favicon: json
+                .get("iconUri")
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned),
+            // Bookmarks store microseconds:
+            last_accessed: json
+                .get("lastModified")
+                .and_then(Value::as_u64)
+                .map(|time| time / 1000),
+            json_key: json_index.to_string(),
+            ..TabExtra::default()
+        }),
+    })
+}
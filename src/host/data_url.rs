@@ -0,0 +1,80 @@
+//! Decode `data:` URLs, which Firefox uses to store favicons in the session.
+
+/// The media type and content of a `data:` URL such as
+/// `data:image/png;base64,iVBORw0...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUrl {
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+impl DataUrl {
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url
+            .get(..5)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("data:"))
+            .map(|_| &url[5..])?;
+        let (header, content) = rest.split_once(',')?;
+        let (media_type, is_base64) = match header.strip_suffix(";base64") {
+            Some(media_type) => (media_type, true),
+            None => (header, false),
+        };
+        let content = percent_decode(content);
+        Some(Self {
+            media_type: media_type.trim().to_ascii_lowercase(),
+            data: if is_base64 {
+                base64_decode(&content)?
+            } else {
+                content
+            },
+        })
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.media_type.starts_with("image/")
+    }
+}
+
+fn percent_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut ix = 0;
+    while ix < bytes.len() {
+        let hex = || std::str::from_utf8(bytes.get(ix + 1..ix + 3)?).ok();
+        match hex().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) if bytes[ix] == b'%' => {
+                out.push(byte);
+                ix += 3;
+            }
+            _ => {
+                out.push(bytes[ix]);
+                ix += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Decode standard or URL-safe base64, ignoring whitespace and padding.
+fn base64_decode(text: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let mut buffer = 0_u32;
+    let mut bits = 0;
+    for &c in text {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' | b' ' | b'\t' | b'\r' | b'\n' => continue,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
@@ -0,0 +1,203 @@
+//! Errors returned by the [`host`](super) module.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The underlying cause of an [`Error`]. Stored in an [`Arc`] so that errors
+/// can be cloned and sent as part of UI messages.
+pub type Source = Arc<dyn std::error::Error + Send + Sync>;
+
+/// Convert a foreign error or a message into a [`Source`].
+pub(super) fn source(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Source {
+    Arc::from(error.into())
+}
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// The file that should be read doesn't exist.
+    NotFound { path: PathBuf },
+    /// The file exists but reading it failed, for example because of missing
+    /// permissions.
+    Read { path: PathBuf, source: Source },
+    /// The output file already exists and we weren't allowed to overwrite it.
+    AlreadyExists { path: PathBuf },
+    /// Failed to create or write to the output file or its folder.
+    Write { path: PathBuf, source: Source },
+    /// The file's data couldn't be decompressed, it is probably corrupt.
+    Decompress { path: PathBuf, source: Source },
+    /// The file should be compressed but doesn't start with the `mozLz40`
+    /// header.
+    NotMozlz4 { path: PathBuf },
+    /// The file's data isn't sessionstore JSON data.
+    NotSessionData { path: PathBuf, source: Source },
+    /// The `containers.json` file in a Firefox profile couldn't be parsed.
+    Containers { path: PathBuf, source: Source },
+    /// A step was called before the data it needs was prepared, for example
+    /// converting tabs to links before the data was parsed.
+    NotReady { step: &'static str },
+    /// The output format can't be used with the selected options.
+    UnsupportedFormat { format: &'static str },
+    /// The selected groups aren't whole windows, for example Sidebery or Tree
+    /// Style Tab panels, so we can't find their tabs.
+    NotWindows { action: &'static str },
+    /// `firefox_session_data` failed to convert tabs to links.
+    Convert { source: Source },
+    /// Failed to start the browser that tabs should be opened in.
+    Browser { program: PathBuf, source: Source },
+    /// A browser API failed when running as a website.
+    Web { action: String },
+    /// There is no folder to remember settings in, see
+    /// [`config_folder`](super::config_folder).
+    NoConfigFolder,
+}
+impl Error {
+    /// Create an error for a failed read, detecting missing files.
+    pub(super) fn read(path: &Path, error: std::io::Error) -> Self {
+        if error.kind() == std::io::ErrorKind::NotFound {
+            Self::NotFound {
+                path: path.to_owned(),
+            }
+        } else {
+            Self::Read {
+                path: path.to_owned(),
+                source: source(error),
+            }
+        }
+    }
+    /// Create an error for a failed write, detecting existing files.
+    pub(super) fn write(path: &Path, error: std::io::Error) -> Self {
+        if error.kind() == std::io::ErrorKind::AlreadyExists {
+            Self::AlreadyExists {
+                path: path.to_owned(),
+            }
+        } else {
+            Self::Write {
+                path: path.to_owned(),
+                source: source(error),
+            }
+        }
+    }
+    pub(super) fn not_session_data(
+        path: &Path,
+        error: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self::NotSessionData {
+            path: path.to_owned(),
+            source: source(error),
+        }
+    }
+
+    /// The file that the error is about, if any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::NotFound { path }
+            | Error::Read { path, .. }
+            | Error::AlreadyExists { path }
+            | Error::Write { path, .. }
+            | Error::Decompress { path, .. }
+            | Error::NotMozlz4 { path }
+            | Error::NotSessionData { path, .. }
+            | Error::Containers { path, .. } => Some(path),
+            Error::Browser { program, .. } => Some(program),
+            Error::NotReady { .. }
+            | Error::UnsupportedFormat { .. }
+            | Error::NotWindows { .. }
+            | Error::Convert { .. }
+            | Error::Web { .. }
+            | Error::NoConfigFolder => None,
+        }
+    }
+
+    /// `true` if the input file couldn't be used as a session, so that
+    /// choosing another file might help.
+    pub fn is_bad_input_file(&self) -> bool {
+        matches!(
+            self,
+            Error::NotFound { .. }
+                | Error::Read { .. }
+                | Error::Decompress { .. }
+                | Error::NotMozlz4 { .. }
+                | Error::NotSessionData { .. }
+        )
+    }
+
+    /// `true` if the error was caused by a missing folder when writing a file.
+    pub fn is_missing_folder(&self) -> bool {
+        matches!(self, Error::Write { source, .. }
+            if source
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound))
+    }
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound { path } => write!(f, "there is no file at \"{}\"", path.display()),
+            Error::Read { path, .. } => write!(f, "failed to read \"{}\"", path.display()),
+            Error::AlreadyExists { path } => {
+                write!(f, "a file already exists at \"{}\"", path.display())
+            }
+            Error::Write { path, .. } => write!(f, "failed to write to \"{}\"", path.display()),
+            Error::Decompress { path, .. } => {
+                write!(f, "failed to decompress data from \"{}\"", path.display())
+            }
+            Error::NotMozlz4 { path } => write!(
+                f,
+                "the file at \"{}\" isn't mozlz4 compressed since it doesn't start with \"mozLz40\"",
+                path.display()
+            ),
+            Error::NotSessionData { path, .. } => write!(
+                f,
+                "the file at \"{}\" doesn't contain sessionstore data",
+                path.display()
+            ),
+            Error::Containers { path, .. } => {
+                write!(f, "failed to read Firefox containers from \"{}\"", path.display())
+            }
+            Error::NotReady { step } => write!(f, "must {step}"),
+            Error::UnsupportedFormat { format } => write!(
+                f,
+                "the \"{format}\" format can't be used with this data or with groups that aren't whole windows"
+            ),
+            Error::NotWindows { action } => write!(
+                f,
+                "can't {action} groups that aren't whole windows, like Sidebery or Tree Style Tab panels"
+            ),
+            Error::Convert { .. } => write!(f, "failed to convert tabs to links"),
+            Error::Browser { program, .. } => {
+                write!(f, "failed to start browser at \"{}\"", program.display())
+            }
+            Error::Web { action } => write!(f, "failed to {action}"),
+            Error::NoConfigFolder => write!(f, "there is no folder to store settings in"),
+        }?;
+        // Include the cause since the UI only shows a single line of text:
+        if let Some(source) = std::error::Error::source(self) {
+            write!(f, ": {source}")?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Read { source, .. }
+            | Error::Write { source, .. }
+            | Error::Decompress { source, .. }
+            | Error::NotSessionData { source, .. }
+            | Error::Containers { source, .. }
+            | Error::Convert { source }
+            | Error::Browser { source, .. } => Some(&**source),
+            Error::NotFound { .. }
+            | Error::AlreadyExists { .. }
+            | Error::NotMozlz4 { .. }
+            | Error::NotReady { .. }
+            | Error::UnsupportedFormat { .. }
+            | Error::NotWindows { .. }
+            | Error::Web { .. }
+            | Error::NoConfigFolder => None,
+        }
+    }
+}
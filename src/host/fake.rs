@@ -0,0 +1,895 @@
+//! A fake backend that is used when the `real_data` feature is disabled so
+//! that the UI can be worked on without compiling `firefox_session_data`.
+//!
+//! Instead of reading the selected file a random session is generated. The
+//! random generator is seeded with the file path so the same path always gives
+//! the same session, set the `FAKE_SESSION_SEED` environment variable to an
+//! integer to pick a seed explicitly. The generated session is serialized to
+//! sessionstore JSON and then parsed with our own [`session`] model, so
+//! groupings, previews and exports all behave like they would for real data.
+//!
+//! Paths whose file name contains `bookmarks` generate a bookmark backup
+//! instead of a session, and `tab_session_manager`, `session_buddy` or
+//! `onetab` generate an export from that extension, like the fixtures in
+//! `tests/fixtures`. `Current Session`, `Session_` and `chromium_session`
+//! generate a Chromium session file.
+//!
+//! Errors can be tested by loading a path whose file name contains `missing`,
+//! `corrupt` or `invalid`, which fails when reading, decompressing or parsing
+//! the data.
+
+use serde_json::{json, Value};
+
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatInfo {
+    PDF,
+    Text,
+    Markdown,
+    Html,
+}
+impl FormatInfo {
+    pub fn all() -> &'static [Self] {
+        &[Self::PDF, Self::Text, Self::Markdown, Self::Html]
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FormatInfo::PDF => "pdf",
+            FormatInfo::Text => "text",
+            FormatInfo::Markdown => "markdown",
+            FormatInfo::Html => "html",
+        }
+    }
+}
+impl std::fmt::Display for FormatInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormatInfo::PDF => write!(f, "Convert the links to a PDF that contains links."),
+            FormatInfo::Text => write!(f, "Convert the links to raw text."),
+            FormatInfo::Markdown => write!(f, "Convert the links to a Markdown list."),
+            FormatInfo::Html => write!(f, "Convert the links to a HTML file with anchor tags."),
+        }
+    }
+}
+
+pub(super) type FirefoxSessionStore = ();
+
+pub(super) fn own_link_format(format: FormatInfo) -> Option<links::LinkFormat> {
+    match format {
+        FormatInfo::PDF => None,
+        FormatInfo::Text => Some(links::LinkFormat::Text),
+        FormatInfo::Markdown => Some(links::LinkFormat::Markdown),
+        FormatInfo::Html => Some(links::LinkFormat::Html),
+    }
+}
+
+/// Microseconds since the Unix epoch, which bookmarks use for timestamps.
+const BOOKMARK_TIME: u64 = 1_700_000_000_000_000;
+
+/// A small deterministic random number generator (SplitMix64).
+struct Rng(u64);
+impl Rng {
+    /// Seed from `FAKE_SESSION_SEED` or else from the file path.
+    fn for_file(path: &Path) -> Self {
+        let seed = env::var("FAKE_SESSION_SEED")
+            .ok()
+            .and_then(|seed| seed.trim().parse().ok())
+            .unwrap_or_else(|| {
+                // FNV-1a, stable between builds unlike the std hasher:
+                path.to_string_lossy()
+                    .bytes()
+                    .fold(0xcbf29ce484222325_u64, |hash, byte| {
+                        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+                    })
+            });
+        Self(seed)
+    }
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+    /// A number in `range`.
+    fn range(&mut self, range: std::ops::RangeInclusive<usize>) -> usize {
+        let len = (range.end() - range.start()) as u64 + 1;
+        range.start() + (self.next() % len) as usize
+    }
+    /// `true` with a probability of `percent`%.
+    fn chance(&mut self, percent: u64) -> bool {
+        self.next() % 100 < percent
+    }
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0..=items.len() - 1)]
+    }
+}
+
+const WORDS: &[&str] = &[
+    "async",
+    "borrow",
+    "checker",
+    "release",
+    "notes",
+    "guide",
+    "firefox",
+    "session",
+    "tabs",
+    "weather",
+    "recipe",
+    "pasta",
+    "history",
+    "roman",
+    "empire",
+    "quantum",
+    "computing",
+    "garden",
+    "design",
+    "patterns",
+    "review",
+    "budget",
+    "travel",
+    "tokyo",
+    "lisbon",
+    "hiking",
+    "trail",
+    "keyboard",
+    "layout",
+    "compiler",
+    "error",
+    "handling",
+    "benchmark",
+    "results",
+    "meeting",
+    "agenda",
+    "open",
+    "source",
+    "license",
+    "music",
+    "playlist",
+    "chess",
+    "opening",
+    "theory",
+];
+/// Titles that aren't plain ASCII.
+const UNICODE_TITLES: &[&str] = &[
+    "日本語の記事 - ウィキペディア",
+    "Ünïcödé Straße und Größe",
+    "Ржавчина: язык программирования",
+    "🦀 Rust Nation 🦀",
+    "مرحبا بالعالم",
+    "Ελληνικά γράμματα",
+];
+/// Hosts with the suffix that is added to page titles on that site.
+const SITES: &[(&str, &str)] = &[
+    ("en.wikipedia.org", " - Wikipedia"),
+    ("de.wikipedia.org", " – Wikipedia"),
+    ("github.com", " · GitHub"),
+    ("docs.rs", " - Docs.rs"),
+    ("doc.rust-lang.org", " - The Rust Programming Language"),
+    ("developer.mozilla.org", " | MDN"),
+    ("stackoverflow.com", " - Stack Overflow"),
+    ("news.ycombinator.com", " | Hacker News"),
+    ("www.youtube.com", " - YouTube"),
+    ("www.reddit.com", " : r/rust"),
+    ("old.reddit.com", " : r/firefox"),
+    ("www.bbc.co.uk", " - BBC News"),
+    ("crates.io", " - crates.io: Rust Package Registry"),
+    ("addons.mozilla.org", " – Add-ons for Firefox"),
+    ("maps.google.com", " - Google Maps"),
+];
+const GROUP_NAMES: &[&str] = &["Research", "Shopping", "Work", "Reading list", "Trip", ""];
+const COLORS: &[&str] = &[
+    "blue",
+    "turquoise",
+    "green",
+    "yellow",
+    "orange",
+    "red",
+    "pink",
+    "purple",
+    "gray",
+];
+
+fn fake_containers() -> Vec<Container> {
+    [
+        (1, "Personal", "blue"),
+        (2, "Work", "orange"),
+        (3, "Banking", "green"),
+        (4, "Shopping", "pink"),
+    ]
+    .into_iter()
+    .map(|(id, name, color)| Container {
+        id,
+        name: name.into(),
+        color: color.into(),
+    })
+    .collect()
+}
+
+/// A sessionstore history entry for a random web page.
+fn fake_entry(rng: &mut Rng) -> Value {
+    if rng.chance(3) {
+        return json!({ "url": "about:newtab", "title": "New Tab" });
+    }
+    let (host, suffix) = *rng.pick(SITES);
+    let words = (0..rng.range(1..=6))
+        .map(|_| *rng.pick(WORDS))
+        .collect::<Vec<_>>();
+    let mut title = if rng.chance(5) {
+        rng.pick(UNICODE_TITLES).to_string()
+    } else {
+        let mut title = words.join(" ");
+        title[..1].make_ascii_uppercase();
+        title
+    };
+    if rng.chance(4) {
+        // Some sites put whole articles in their titles:
+        while title.len() < 200 {
+            title.push(' ');
+            title.push_str(rng.pick::<&str>(WORDS));
+        }
+    }
+    title.push_str(suffix);
+    json!({
+        "url": format!("https://{host}/{}", words.join("-")),
+        "title": title,
+    })
+}
+
+/// A `data:` URL with a 16x16 BMP image in a color picked from the host name.
+fn fake_favicon(host: &str) -> String {
+    let hash = host
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        })
+        .to_le_bytes();
+    let (size, header_size) = (16_u32, 14 + 40);
+    let image_size = size * size * 4;
+    let mut bmp = Vec::with_capacity((header_size + image_size) as usize);
+    // BITMAPFILEHEADER:
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&(header_size + image_size).to_le_bytes());
+    bmp.extend_from_slice(&[0; 4]);
+    bmp.extend_from_slice(&header_size.to_le_bytes());
+    // BITMAPINFOHEADER with 32 bits per pixel and no compression:
+    bmp.extend_from_slice(&40_u32.to_le_bytes());
+    bmp.extend_from_slice(&size.to_le_bytes());
+    bmp.extend_from_slice(&size.to_le_bytes());
+    bmp.extend_from_slice(&1_u16.to_le_bytes());
+    bmp.extend_from_slice(&32_u16.to_le_bytes());
+    bmp.extend_from_slice(&0_u32.to_le_bytes());
+    bmp.extend_from_slice(&image_size.to_le_bytes());
+    bmp.extend_from_slice(&[0; 16]);
+    // Pixels as BGRA, a colored square with a lighter border:
+    for y in 0..size {
+        for x in 0..size {
+            let border = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+            let [b, g, r] = [hash[0], hash[1], hash[2]].map(|c| if border { c | 0x80 } else { c });
+            bmp.extend_from_slice(&[b, g, r, 0xff]);
+        }
+    }
+    format!("data:image/bmp;base64,{}", base64_encode(&bmp))
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let value = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for ix in 0..4 {
+            if ix <= chunk.len() {
+                out.push(ALPHABET[(value >> (18 - ix * 6) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// A tab with some history, the last entry is the current page.
+fn fake_tab(rng: &mut Rng) -> Value {
+    let entries = (0..rng.range(1..=4))
+        .map(|_| fake_entry(rng))
+        .collect::<Vec<_>>();
+    let mut tab = json!({
+        "index": entries.len(),
+        "entries": entries,
+        "userContextId": if rng.chance(20) { rng.range(1..=4) } else { 0 },
+        "lastAccessed": 1_700_000_000_000_u64 + rng.next() % 50_000_000_000,
+        "pinned": rng.chance(5),
+        "hidden": rng.chance(3),
+        "muted": rng.chance(5),
+    });
+    if let Some(url) = entries.last().and_then(|entry| entry["url"].as_str()) {
+        if let Some(host) = url
+            .strip_prefix("https://")
+            .and_then(|url| url.split('/').next())
+        {
+            // Firefox stores most favicons as data URLs:
+            tab["image"] = json!(if host.ends_with(".org") {
+                format!("https://{host}/favicon.ico")
+            } else {
+                fake_favicon(host)
+            });
+        }
+    }
+    if rng.chance(30) {
+        tab["scroll"] = json!({ "scroll": format!("0,{}", rng.range(1..=20_000)) });
+    }
+    if rng.chance(5) {
+        tab["formdata"] = json!({ "id": { "search": "typed text" } });
+    }
+    tab
+}
+
+fn fake_window(rng: &mut Rng, window_ix: usize) -> Value {
+    // Most windows are small but some have lots of tabs:
+    let tab_count = if rng.chance(20) {
+        rng.range(50..=200)
+    } else {
+        rng.range(1..=25)
+    };
+    let mut tabs = (0..tab_count).map(|_| fake_tab(rng)).collect::<Vec<_>>();
+
+    // Tree Style Tab data, tabs are placed after their parent:
+    let mut depths = Vec::<usize>::with_capacity(tabs.len());
+    for (ix, tab) in tabs.iter_mut().enumerate() {
+        let id = format!("tab-{window_ix}-{ix}");
+        let parent = depths
+            .last()
+            .filter(|&&depth| depth < 4 && rng.chance(35))
+            .map(|_| {
+                // Child of the previous tab or of one of its ancestors:
+                let depth = rng.range(0..=depths[ix - 1]);
+                (0..ix)
+                    .rev()
+                    .find(|&i| depths[i] == depth)
+                    .expect("ancestor exists")
+            });
+        depths.push(parent.map_or(0, |parent| depths[parent] + 1));
+        tab["extData"] = json!({ "treestyletab-id": id });
+        if let Some(parent) = parent {
+            tab["extData"]["treestyletab-parent"] = json!(format!("tab-{window_ix}-{parent}"));
+        }
+    }
+
+    // Native tab groups are contiguous runs of tabs:
+    let mut groups = Vec::new();
+    if tabs.len() > 3 && rng.chance(40) {
+        for group_ix in 0..rng.range(1..=2) {
+            let id = format!("{}-{group_ix}", 1_741_513_525_143_u64 + window_ix as u64);
+            let start = rng.range(0..=tabs.len() - 2);
+            let end = rng.range(start + 1..=tabs.len().min(start + 8));
+            for tab in &mut tabs[start..end] {
+                tab["groupId"] = json!(id);
+            }
+            groups.push(json!({
+                "id": id,
+                "name": rng.pick(GROUP_NAMES),
+                "color": rng.pick(COLORS),
+                "collapsed": rng.chance(30),
+            }));
+        }
+    }
+
+    let closed_tabs = (0..rng.range(0..=5))
+        .map(|_| {
+            let state = fake_tab(rng);
+            json!({
+                "title": state["entries"].as_array().and_then(|e| e.last()).map(|e| e["title"].clone()),
+                "closedAt": 1_700_000_000_000_u64 + rng.next() % 50_000_000_000,
+                "state": state,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "selected": rng.range(1..=tabs.len()),
+        "tabs": tabs,
+        "groups": groups,
+        "_closedTabs": closed_tabs,
+    })
+}
+
+/// Generate sessionstore JSON data.
+fn fake_session_json(rng: &mut Rng) -> Vec<u8> {
+    let windows = (0..rng.range(1..=8))
+        .map(|ix| fake_window(rng, ix))
+        .collect::<Vec<_>>();
+    let closed_windows = (0..rng.range(0..=4))
+        .map(|ix| fake_window(rng, 100 + ix))
+        .collect::<Vec<_>>();
+    // Spread over a year so that backups of a profile are in some order:
+    let last_update = 1_750_000_000_000_u64 + rng.next() % 31_536_000_000;
+    json!({
+        "version": ["sessionrestore", 1],
+        "windows": windows,
+        "_closedWindows": closed_windows,
+        "selectedWindow": 1,
+        "session": { "lastUpdate": last_update },
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// A bookmark folder with random bookmarks and sub folders. `root` is set for
+/// Firefox's special folders.
+fn fake_bookmark_folder(rng: &mut Rng, title: &str, root: Option<&str>, depth: usize) -> Value {
+    let mut children = (0..rng.range(0..=12))
+        .map(|_| {
+            let entry = fake_entry(rng);
+            json!({
+                "type": "text/x-moz-place",
+                "typeCode": 1,
+                "title": entry["title"],
+                "uri": entry["url"],
+                "dateAdded": BOOKMARK_TIME + rng.next() % 50_000_000_000_000,
+                "lastModified": BOOKMARK_TIME + rng.next() % 50_000_000_000_000,
+            })
+        })
+        .collect::<Vec<_>>();
+    if rng.chance(20) {
+        children.push(json!({ "type": "text/x-moz-place-separator", "typeCode": 3 }));
+    }
+    if depth < 3 {
+        for _ in 0..rng.range(0..=3 - depth) {
+            let title = rng.pick(WORDS).to_string();
+            children.push(fake_bookmark_folder(rng, &title, None, depth + 1));
+        }
+    }
+    let mut folder = json!({
+        "type": "text/x-moz-place-container",
+        "typeCode": 2,
+        "title": title,
+        "dateAdded": BOOKMARK_TIME,
+        "lastModified": BOOKMARK_TIME,
+        "children": children,
+    });
+    if let Some(root) = root {
+        folder["root"] = json!(root);
+    }
+    folder
+}
+
+/// Generate the JSON of a bookmark backup.
+fn fake_bookmarks_json(rng: &mut Rng) -> Vec<u8> {
+    let folders = [
+        ("menu", "bookmarksMenuFolder"),
+        ("toolbar", "toolbarFolder"),
+        ("unfiled", "unfiledBookmarksFolder"),
+        ("mobile", "mobileFolder"),
+    ]
+    .map(|(title, root)| fake_bookmark_folder(rng, title, Some(root), 0));
+    json!({
+        "guid": "root________",
+        "title": "",
+        "type": "text/x-moz-place-container",
+        "typeCode": 2,
+        "root": "placesRoot",
+        "children": folders,
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// The fields that extensions like Tab Session Manager save for a tab.
+fn fake_extension_tab(rng: &mut Rng) -> Value {
+    let tab = fake_tab(rng);
+    let entry = &tab["entries"][tab["entries"].as_array().map_or(0, Vec::len) - 1];
+    json!({
+        "url": entry["url"],
+        "title": entry["title"],
+        "favIconUrl": tab["image"],
+        "lastAccessed": tab["lastAccessed"],
+        "pinned": tab["pinned"],
+        "hidden": tab["hidden"],
+        "mutedInfo": { "muted": tab["muted"] },
+        "cookieStoreId": match tab["userContextId"].as_u64() {
+            Some(id @ 1..) => format!("firefox-container-{id}"),
+            _ => "firefox-default".to_owned(),
+        },
+    })
+}
+
+fn fake_session_name(rng: &mut Rng) -> String {
+    let mut name = (0..rng.range(1..=3))
+        .map(|_| *rng.pick(WORDS))
+        .collect::<Vec<_>>()
+        .join(" ");
+    name[..1].make_ascii_uppercase();
+    name
+}
+
+/// Generate a Tab Session Manager export, windows and tabs are keyed by id.
+fn fake_tab_session_manager_json(rng: &mut Rng) -> Vec<u8> {
+    let sessions = (0..rng.range(1..=4))
+        .map(|session_ix| {
+            let mut windows = serde_json::Map::new();
+            let mut tabs_number = 0;
+            for window_ix in 0..rng.range(1..=3) {
+                // Ids that sort differently as text and as numbers:
+                let window_id = 8 + session_ix * 5 + window_ix;
+                let mut tabs = serde_json::Map::new();
+                for index in 0..rng.range(1..=15) {
+                    let id = window_id * 100 + index;
+                    let mut tab = fake_extension_tab(rng);
+                    tab["id"] = json!(id);
+                    tab["index"] = json!(index);
+                    tab["windowId"] = json!(window_id);
+                    tabs.insert(id.to_string(), tab);
+                }
+                tabs_number += tabs.len();
+                windows.insert(window_id.to_string(), Value::Object(tabs));
+            }
+            json!({
+                "name": fake_session_name(rng),
+                "date": 1_700_000_000_000_u64 + rng.next() % 50_000_000_000,
+                "tag": ["_user"],
+                "windowsNumber": windows.len(),
+                "tabsNumber": tabs_number,
+                "windows": windows,
+                "id": format!("fake-session-{session_ix}"),
+            })
+        })
+        .collect::<Vec<_>>();
+    Value::Array(sessions).to_string().into_bytes()
+}
+
+/// Generate a Session Buddy export with collections of folders of links.
+fn fake_session_buddy_json(rng: &mut Rng) -> Vec<u8> {
+    let collections = (0..rng.range(1..=4))
+        .map(|_| {
+            let folders = (0..rng.range(1..=3))
+                .map(|_| {
+                    let links = (0..rng.range(1..=15))
+                        .map(|_| fake_extension_tab(rng))
+                        .collect::<Vec<_>>();
+                    json!({ "links": links })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "title": fake_session_name(rng),
+                "created": 1_700_000_000_000_u64 + rng.next() % 50_000_000_000,
+                "folders": folders,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({ "format": "nxs.json.v1", "collections": collections })
+        .to_string()
+        .into_bytes()
+}
+
+/// Generate a OneTab export, a `url | title` line per tab with an empty line
+/// between tab groups.
+fn fake_onetab_text(rng: &mut Rng) -> Vec<u8> {
+    (0..rng.range(1..=5))
+        .map(|_| {
+            (0..rng.range(1..=15))
+                .map(|_| {
+                    let entry = fake_entry(rng);
+                    format!(
+                        "{} | {}\n",
+                        entry["url"].as_str().unwrap_or_default(),
+                        entry["title"].as_str().unwrap_or_default()
+                    )
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .into_bytes()
+}
+
+/// Generate a Chromium session file by writing the commands that Chromium
+/// would write while tabs are opened, navigated and closed.
+fn fake_chromium_session(rng: &mut Rng) -> Vec<u8> {
+    fn command(out: &mut Vec<u8>, id: u8, payload: &[u8]) {
+        out.extend_from_slice(&(payload.len() as u16 + 1).to_le_bytes());
+        out.push(id);
+        out.extend_from_slice(payload);
+    }
+    fn ints(values: &[i32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+    /// Add a field to a pickle, padded to 4 bytes.
+    fn field(pickle: &mut Vec<u8>, len: usize, bytes: &[u8]) {
+        pickle.extend_from_slice(&(len as i32).to_le_bytes());
+        pickle.extend_from_slice(bytes);
+        pickle.resize(pickle.len().next_multiple_of(4), 0);
+    }
+    fn pickle(fields: &[u8]) -> Vec<u8> {
+        let mut pickle = (fields.len() as u32).to_le_bytes().to_vec();
+        pickle.extend_from_slice(fields);
+        pickle
+    }
+
+    let mut out = snss::MAGIC.to_vec();
+    out.extend_from_slice(&ints(&[3]));
+    let mut tab_id = 100;
+    for window_id in 1..=rng.range(1..=4) as i32 {
+        if rng.chance(30) {
+            let mut fields = ints(&[window_id]);
+            let title = fake_session_name(rng);
+            field(&mut fields, title.len(), title.as_bytes());
+            command(&mut out, snss::SET_WINDOW_USER_TITLE, &pickle(&fields));
+        }
+        for index in 0..rng.range(1..=20) as i32 {
+            tab_id += 1;
+            command(&mut out, snss::SET_TAB_WINDOW, &ints(&[window_id, tab_id]));
+            command(
+                &mut out,
+                snss::SET_TAB_INDEX_IN_WINDOW,
+                &ints(&[tab_id, index]),
+            );
+            let navigations = rng.range(1..=3) as i32;
+            for navigation in 0..navigations {
+                let entry = fake_entry(rng);
+                let url = entry["url"].as_str().unwrap_or_default();
+                let title = entry["title"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .encode_utf16()
+                    .flat_map(u16::to_le_bytes)
+                    .collect::<Vec<_>>();
+                let mut fields = ints(&[tab_id, navigation]);
+                field(&mut fields, url.len(), url.as_bytes());
+                field(&mut fields, title.len() / 2, &title);
+                // Page state and transition type:
+                field(&mut fields, 0, &[]);
+                fields.extend_from_slice(&ints(&[0]));
+                command(&mut out, snss::UPDATE_TAB_NAVIGATION, &pickle(&fields));
+            }
+            command(
+                &mut out,
+                snss::SET_SELECTED_NAVIGATION_INDEX,
+                &ints(&[tab_id, rng.range(0..=navigations as usize - 1) as i32]),
+            );
+            if rng.chance(10) {
+                command(&mut out, snss::SET_PINNED_STATE, &ints(&[tab_id, 1]));
+            }
+            if rng.chance(10) {
+                // The tab id, padding and the time it was closed:
+                command(&mut out, snss::TAB_CLOSED, &ints(&[tab_id, 0, 0, 0]));
+            }
+        }
+        if window_id > 1 && rng.chance(25) {
+            command(&mut out, snss::WINDOW_CLOSED, &ints(&[window_id, 0, 0, 0]));
+        }
+    }
+    out
+}
+
+/// A minimal PDF with one line of text for each line in `text`. Characters
+/// that the standard Helvetica font doesn't have are replaced with `?`.
+fn text_to_pdf(text: &str) -> Vec<u8> {
+    const LINES_PER_PAGE: usize = 60;
+
+    let lines = text.lines().collect::<Vec<_>>();
+    let pages = lines.chunks(LINES_PER_PAGE).collect::<Vec<_>>();
+    // Objects: catalog, page tree, font, then a page and content per page.
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_owned(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..pages.len())
+                .map(|ix| format!("{} 0 R", 4 + ix * 2))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_owned(),
+    ];
+    for (ix, page) in pages.iter().enumerate() {
+        let mut content = String::from("BT /F1 10 Tf 12 TL 40 800 Td\n");
+        for line in *page {
+            let line = line
+                .chars()
+                .map(|c| match c {
+                    '(' | ')' | '\\' => format!("\\{c}"),
+                    c if c.is_ascii() && !c.is_ascii_control() => c.to_string(),
+                    // WinAnsiEncoding matches Latin-1 for these:
+                    '\u{a0}'..='\u{ff}' => format!("\\{:03o}", c as u32),
+                    _ => "?".to_owned(),
+                })
+                .collect::<String>();
+            content.push_str(&format!("({line}) '\n"));
+        }
+        content.push_str("ET");
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            5 + ix * 2
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{content}\nendstream",
+            content.len()
+        ));
+    }
+
+    let mut out = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (ix, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n{object}\nendobj\n", ix + 1).as_bytes());
+    }
+    let xref = out.len();
+    out.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+    );
+    for offset in offsets {
+        out.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+    }
+    out.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .as_bytes(),
+    );
+    out
+}
+
+impl FileInfo {
+    /// `true` if the file name contains `problem`, used to simulate errors.
+    fn simulates(&self, problem: &str) -> bool {
+        self.file_path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().contains(problem))
+    }
+
+    pub async fn load_data(&mut self) -> Result<(), Error> {
+        if self.data.is_some() {
+            return Ok(());
+        }
+        if self.simulates("missing") {
+            return Err(Error::NotFound {
+                path: self.file_path.to_path_buf(),
+            });
+        }
+        let path = self.file_path.clone();
+        let compressed = self.is_compressed_file_format();
+        let corrupt = self.simulates("corrupt");
+        let invalid = self.simulates("invalid");
+        let generate = if self.simulates("bookmarks") {
+            fake_bookmarks_json
+        } else if self.simulates("tab_session_manager") {
+            fake_tab_session_manager_json
+        } else if self.simulates("session_buddy") {
+            fake_session_buddy_json
+        } else if self.simulates("onetab") {
+            fake_onetab_text
+        } else if self.simulates("Current Session")
+            || self.simulates("Session_")
+            || self.simulates("chromium_session")
+        {
+            fake_chromium_session
+        } else {
+            fake_session_json
+        };
+        let data = spawn_blocking(move || {
+            let mut rng = Rng::for_file(&path);
+            let mut json = generate(&mut rng);
+            if invalid {
+                json.truncate(json.len() / 2);
+            }
+            if compressed {
+                let mut data = mozlz4::compress(&json).expect("fake sessions are small");
+                if corrupt {
+                    data.truncate(data.len() / 2);
+                }
+                FileData::Compressed(data.into())
+            } else {
+                FileData::Uncompressed(json.into())
+            }
+        })
+        .await;
+        self.source = match &data {
+            FileData::Uncompressed(data) if snss::is_snss(data) => DataSource::Chromium,
+            _ => DataSource::Session,
+        };
+        self.data = Some(data);
+        self.containers = fake_containers().into();
+        Ok(())
+    }
+
+    pub async fn decompress_data(&mut self) -> Result<(), Error> {
+        let Some(FileData::Compressed(data)) = &self.data else {
+            return Ok(());
+        };
+        let data = data.clone();
+        let path = self.file_path.clone();
+        let data = spawn_blocking(move || decompress_mozlz4(&path, &data)).await?;
+        self.data = Some(FileData::Uncompressed(data.into()));
+        Ok(())
+    }
+
+    pub async fn parse_session_data(&mut self) -> Result<(), Error> {
+        if self.is_parsed() {
+            return Ok(());
+        }
+        let Some(FileData::Uncompressed(data)) = &self.data else {
+            return Err(Error::NotReady {
+                step: "decompress data before parsing it",
+            });
+        };
+        let data = data.clone();
+        let json = data.clone();
+        let this = self.clone();
+        let (source, session, _) = spawn_blocking(move || this.parse_own_session(&data)).await?;
+        self.json = source.is_json().then_some(json);
+        // Like `firefox_session_data`, only Firefox sessions are parsed:
+        if source == DataSource::Session {
+            self.data = Some(FileData::Parsed(Arc::new(())));
+        }
+        self.source = source;
+        self.session = Some(Arc::new(session));
+        Ok(())
+    }
+
+    pub async fn get_groups_from_session(
+        &self,
+        options: &GenerateOptions,
+    ) -> Result<AllTabGroups, Error> {
+        self.own_tab_groups(options)
+    }
+
+    pub async fn to_text_links(&self, generate_options: GenerateOptions) -> Result<String, Error> {
+        self.own_links(
+            &generate_options,
+            link_options(&generate_options, links::LinkFormat::Text, false),
+        )
+    }
+
+    pub async fn to_text_preview(
+        &self,
+        generate_options: GenerateOptions,
+        cache: PreviewCache,
+    ) -> Result<String, Error> {
+        let this = self.clone();
+        spawn_blocking(move || this.own_text_preview(&generate_options, &cache)).await
+    }
+
+    pub async fn save_links(
+        &self,
+        save_path: PathBuf,
+        generate_options: GenerateOptions,
+        output_options: OutputOptions,
+    ) -> Result<(), Error> {
+        let session = self.own_session()?.clone();
+        let own_grouping = self.uses_own_grouping(&generate_options);
+        spawn_blocking(move || {
+            let groups = selected_own_groups(&session, &generate_options);
+            let sources = [links::Source {
+                name: None,
+                groups: &groups,
+            }];
+            // Like `firefox_session_data`, windows can be saved as PDF. Write
+            // the text format to a simple PDF:
+            let (data, file_ext) =
+                if own_link_format(output_options.format).is_none() && !own_grouping {
+                    let text = links::write_sources(
+                        &sources,
+                        link_options(&generate_options, links::LinkFormat::Text, false),
+                    );
+                    (text_to_pdf(&text), "pdf")
+                } else {
+                    write_own_links(&sources, &generate_options, &output_options)?
+                };
+
+            write_output_file(save_path, &output_options, &data, file_ext, "firefox-links")
+        })
+        .await
+    }
+}
@@ -0,0 +1,266 @@
+//! Read exports from other session managers into the same model as sessions,
+//! so that their tabs can be previewed and exported like Firefox's own.
+//!
+//! - Tab Session Manager exports a JSON array of sessions where windows and
+//!   tabs are objects keyed by their ids.
+//! - Session Buddy exports JSON with either `collections` of `folders` of
+//!   `links` (newer versions) or `sessions` of `windows` of `tabs`.
+//! - OneTab exports text with a `url | title` line per tab and an empty line
+//!   between tab groups.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use super::session::{Container, Session, Tab, TabExtra, Window};
+use super::DataSource;
+
+/// Read a JSON export from Tab Session Manager or Session Buddy. Returns
+/// [`None`] for other JSON data.
+pub fn session_from_json(json: &Value, containers: &[Container]) -> Option<(DataSource, Session)> {
+    if is_tab_session_manager(json) {
+        Some((
+            DataSource::TabSessionManager,
+            tab_session_manager(json, containers),
+        ))
+    } else if is_session_buddy(json) {
+        Some((DataSource::SessionBuddy, session_buddy(json)))
+    } else {
+        None
+    }
+}
+
+fn str_field<'a>(json: &'a Value, key: &str) -> Option<&'a str> {
+    json.get(key)
+        .and_then(Value::as_str)
+        .filter(|value| !value.is_empty())
+}
+
+/// A tab from the common WebExtension fields `url`, `title` and `favIconUrl`.
+fn link(json: &Value, json_key: String) -> Option<Tab> {
+    let url = str_field(json, "url")?;
+    let title = str_field(json, "title").unwrap_or(url);
+    Some(Tab {
+        url: url.to_owned(),
+        title: title.to_owned(),
+        user_context_id: 0,
+        container: None,
+        native_group: None,
+        extra: Arc::new(TabExtra {
+            favicon: str_field(json, "favIconUrl").map(ToOwned::to_owned),
+            last_accessed: json.get("lastAccessed").and_then(Value::as_u64),
+            pinned: json.get("pinned").and_then(Value::as_bool) == Some(true),
+            json_key,
+            ..TabExtra::default()
+        }),
+    })
+}
+
+/// Entries of a JSON object ordered by their numeric ids.
+fn by_id(json: Option<&Value>) -> Vec<(&String, &Value)> {
+    let mut entries = json
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|(key, _)| (key.parse::<u64>().ok(), key.as_str()));
+    entries
+}
+
+/// Window name that includes the session name if there is more than one
+/// window in the session.
+fn window_name(session_name: &str, window_ix: usize, window_count: usize) -> String {
+    if window_count == 1 {
+        session_name.to_owned()
+    } else {
+        format!("{session_name} - Window {}", window_ix + 1)
+    }
+}
+
+fn is_tab_session_manager(json: &Value) -> bool {
+    json.as_array().is_some_and(|sessions| {
+        !sessions.is_empty()
+            && sessions.iter().all(|session| {
+                session.get("windows").is_some_and(Value::is_object)
+                    && session.get("tabsNumber").is_some()
+            })
+    })
+}
+
+fn tab_session_manager(json: &Value, containers: &[Container]) -> Session {
+    let containers = containers.iter().cloned().map(Arc::new).collect::<Vec<_>>();
+    let mut windows = Vec::new();
+    for (session_ix, session) in json.as_array().into_iter().flatten().enumerate() {
+        let session_name = str_field(session, "name")
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| format!("Session {}", session_ix + 1));
+        let session_windows = by_id(session.get("windows"));
+        for (window_ix, (window_id, window)) in session_windows.iter().enumerate() {
+            let json_pointer = format!("/{session_ix}/windows/{window_id}");
+            let mut tabs = by_id(Some(window))
+                .into_iter()
+                .filter_map(|(tab_id, tab)| {
+                    let index = tab.get("index").and_then(Value::as_u64);
+                    Some((
+                        index,
+                        with_container(link(tab, tab_id.clone())?, tab, &containers),
+                    ))
+                })
+                .collect::<Vec<_>>();
+            tabs.sort_by_key(|(index, _)| *index);
+            windows.push(Window {
+                tabs: tabs.into_iter().map(|(_, tab)| tab).collect(),
+                name: Some(window_name(&session_name, window_ix, session_windows.len())),
+                tabs_pointer: json_pointer.clone(),
+                json_pointer,
+                ..Window::default()
+            });
+        }
+    }
+    Session {
+        windows,
+        ..Session::default()
+    }
+}
+
+/// Tab Session Manager keeps each tab's `cookieStoreId`, which is
+/// `firefox-container-{userContextId}` for tabs in containers.
+fn with_container(mut tab: Tab, json: &Value, containers: &[Arc<Container>]) -> Tab {
+    let id = str_field(json, "cookieStoreId")
+        .and_then(|store| store.strip_prefix("firefox-container-"))
+        .and_then(|id| id.parse::<u32>().ok());
+    if let Some(id) = id {
+        tab.user_context_id = id;
+        tab.container = containers.iter().find(|c| c.id == id).cloned();
+    }
+    let extra = Arc::make_mut(&mut tab.extra);
+    extra.hidden = json.get("hidden").and_then(Value::as_bool) == Some(true);
+    extra.muted = json
+        .get("mutedInfo")
+        .and_then(|info| info.get("muted"))
+        .and_then(Value::as_bool)
+        == Some(true);
+    tab
+}
+
+fn is_session_buddy(json: &Value) -> bool {
+    let all_have = |key: &str, child: &str| {
+        json.get(key)
+            .and_then(Value::as_array)
+            .is_some_and(|items| items.iter().all(|item| item.get(child).is_some()))
+    };
+    all_have("collections", "folders") || all_have("sessions", "windows")
+}
+
+fn session_buddy(json: &Value) -> Session {
+    let mut session = Session::default();
+    // Collections in newer versions and sessions in older versions:
+    for (key, windows_key, tabs_key) in [
+        ("collections", "folders", "links"),
+        ("sessions", "windows", "tabs"),
+    ] {
+        let items = json
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        for (ix, item) in items.enumerate() {
+            let name = str_field(item, "title")
+                .or_else(|| str_field(item, "name"))
+                .map(ToOwned::to_owned)
+                .unwrap_or_else(|| format!("Session {}", ix + 1));
+            let item_windows = item
+                .get(windows_key)
+                .and_then(Value::as_array)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            // The windows that were open before the browser was restarted:
+            let target = if str_field(item, "type") == Some("previous") {
+                &mut session.closed_windows
+            } else {
+                &mut session.windows
+            };
+            for (window_ix, window) in item_windows.iter().enumerate() {
+                let json_pointer = format!("/{key}/{ix}/{windows_key}/{window_ix}");
+                let tabs = window
+                    .get(tabs_key)
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .enumerate()
+                    .filter_map(|(tab_ix, tab)| link(tab, tab_ix.to_string()))
+                    .collect();
+                target.push(Window {
+                    tabs,
+                    name: Some(match str_field(window, "title") {
+                        Some(title) => format!("{name} - {title}"),
+                        None => window_name(&name, window_ix, item_windows.len()),
+                    }),
+                    tabs_pointer: format!("{json_pointer}/{tabs_key}"),
+                    json_pointer,
+                    ..Window::default()
+                });
+            }
+        }
+    }
+    session
+}
+
+/// Read a OneTab export. Returns [`None`] if any line isn't a link.
+pub fn session_from_onetab(data: &[u8]) -> Option<Session> {
+    let text = std::str::from_utf8(data).ok()?;
+    let mut windows = Vec::<Window>::new();
+    let mut in_group = false;
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            in_group = false;
+            continue;
+        }
+        let (url, title) = match line.split_once(" | ") {
+            Some((url, title)) => (url.trim(), title.trim()),
+            None => (line, ""),
+        };
+        if !is_url(url) {
+            return None;
+        }
+        if !in_group {
+            windows.push(Window {
+                name: Some(format!("Group {}", windows.len() + 1)),
+                ..Window::default()
+            });
+            in_group = true;
+        }
+        let window = windows.last_mut()?;
+        window.tabs.push(Tab {
+            url: url.to_owned(),
+            title: if title.is_empty() { url } else { title }.to_owned(),
+            user_context_id: 0,
+            container: None,
+            native_group: None,
+            extra: Arc::new(TabExtra {
+                json_key: window.tabs.len().to_string(),
+                ..TabExtra::default()
+            }),
+        });
+    }
+    if windows.is_empty() {
+        return None;
+    }
+    Some(Session {
+        windows,
+        ..Session::default()
+    })
+}
+
+/// `true` for text like `https://example.com` that starts with a URL scheme
+/// and has no spaces.
+fn is_url(text: &str) -> bool {
+    let Some((scheme, rest)) = text.split_once(':') else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+        && !rest.is_empty()
+        && !text.contains(char::is_whitespace)
+}
@@ -0,0 +1,486 @@
+//! Write groups of tabs as links. Used for groupings that `firefox_session_data`
+//! doesn't know about, the output tries to look similar to what that crate
+//! generates.
+
+use std::{borrow::Cow, fmt::Write, sync::Arc};
+
+use super::session::{Group, NativeGroup, Tab};
+use super::DataUrl;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkFormat {
+    Text,
+    Markdown,
+    Html,
+    Typst,
+    Rtf,
+}
+impl LinkFormat {
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            LinkFormat::Text => "txt",
+            LinkFormat::Markdown => "md",
+            LinkFormat::Html => "html",
+            LinkFormat::Typst => "typ",
+            LinkFormat::Rtf => "rtf",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkOptions {
+    pub format: LinkFormat,
+    pub table_of_contents: bool,
+    pub page_breaks_after_group: bool,
+    /// Write native Firefox tab groups as sub-sections.
+    pub native_groups: bool,
+    /// Show favicons that are stored as `data:` URLs next to links. Only
+    /// supported by HTML.
+    pub favicons: bool,
+}
+
+/// Runs of tabs that should be written together, with a sub-heading if they
+/// are in a native tab group.
+fn sections(group: &Group, options: LinkOptions) -> Vec<(Option<&Arc<NativeGroup>>, &[Tab])> {
+    if options.native_groups {
+        group.sections()
+    } else {
+        vec![(None, group.tabs.as_slice())]
+    }
+}
+
+/// The tab's title followed by the name of its container, if it has one.
+fn tab_title(tab: &Tab) -> Cow<'_, str> {
+    if tab.user_context_id == 0 {
+        Cow::Borrowed(&tab.title)
+    } else {
+        Cow::Owned(format!("{} [{}]", tab.title, tab.container_name()))
+    }
+}
+
+/// Groups that are written under a heading of their own, used when several
+/// sessions are merged into one document.
+#[derive(Debug, Clone, Copy)]
+pub struct Source<'a> {
+    /// [`None`] writes the groups without a heading.
+    pub name: Option<&'a str>,
+    pub groups: &'a [Group],
+}
+
+/// Tracks where page breaks go so that a group isn't separated from the
+/// heading of its source.
+struct PageBreaks {
+    enabled: bool,
+    /// `true` once anything was written that a page break can follow.
+    after_content: bool,
+}
+impl PageBreaks {
+    fn new(options: LinkOptions) -> Self {
+        Self {
+            enabled: options.page_breaks_after_group,
+            after_content: options.table_of_contents,
+        }
+    }
+    /// Whether a page break should come before the next heading. A source
+    /// heading is followed by its first group without a break.
+    fn before_heading(&mut self, is_source: bool) -> bool {
+        let page_break = self.enabled && self.after_content;
+        self.after_content = !is_source;
+        page_break
+    }
+}
+
+pub fn write_links(groups: &[Group], options: LinkOptions) -> String {
+    write_sources(&[Source { name: None, groups }], options)
+}
+
+/// Like [`write_links`] but the groups of each named source are written under
+/// a heading with the source's name.
+pub fn write_sources(sources: &[Source<'_>], options: LinkOptions) -> String {
+    let mut out = String::new();
+    match options.format {
+        LinkFormat::Text => write_text(&mut out, sources, options),
+        LinkFormat::Markdown => write_markdown(&mut out, sources, options),
+        LinkFormat::Html => write_html(&mut out, sources, options),
+        LinkFormat::Typst => write_typst(&mut out, sources, options),
+        LinkFormat::Rtf => write_rtf(&mut out, sources, options),
+    }
+    .expect("writing to a String never fails");
+    out
+}
+
+/// Heading level of groups, one level below the source headings if there are
+/// any.
+fn group_level(source: &Source<'_>) -> usize {
+    if source.name.is_some() {
+        2
+    } else {
+        1
+    }
+}
+
+fn write_text(out: &mut String, sources: &[Source<'_>], options: LinkOptions) -> std::fmt::Result {
+    if options.table_of_contents {
+        writeln!(out, "Table of contents:")?;
+        let mut number = 0;
+        for source in sources {
+            let indent = match source.name {
+                Some(name) => {
+                    writeln!(out, "  {name}:")?;
+                    "    "
+                }
+                None => "  ",
+            };
+            number = write_text_contents(out, indent, number, source.groups)?;
+        }
+        writeln!(out)?;
+    }
+    for source in sources {
+        if let Some(name) = source.name {
+            writeln!(out, "=== {name} ===\n")?;
+        }
+        for group in source.groups {
+            write_text_group(out, group, options)?;
+        }
+    }
+    Ok(())
+}
+
+/// The table of contents that [`LinkFormat::Text`] starts with.
+pub fn write_text_table_of_contents<'a>(
+    out: &mut String,
+    groups: impl IntoIterator<Item = &'a Group>,
+) -> std::fmt::Result {
+    writeln!(out, "Table of contents:")?;
+    write_text_contents(out, "  ", 0, groups)?;
+    writeln!(out)
+}
+
+/// A table of contents like [`write_text_table_of_contents`] for groups that
+/// were converted by `firefox_session_data`, so only their names are known.
+pub fn write_text_table_of_contents_names<'a>(
+    out: &mut String,
+    names: impl IntoIterator<Item = &'a str>,
+) -> std::fmt::Result {
+    writeln!(out, "Table of contents:")?;
+    for (number, name) in (1..).zip(names) {
+        writeln!(out, "  {number}. {name}")?;
+    }
+    writeln!(out)
+}
+
+/// Numbered entries in a table of contents, returns the last number.
+fn write_text_contents<'a>(
+    out: &mut String,
+    indent: &str,
+    mut number: usize,
+    groups: impl IntoIterator<Item = &'a Group>,
+) -> Result<usize, std::fmt::Error> {
+    for group in groups {
+        number += 1;
+        writeln!(
+            out,
+            "{indent}{number}. {} ({} tabs)",
+            group.name,
+            group.tabs.len()
+        )?;
+    }
+    Ok(number)
+}
+
+/// A single group in the [`LinkFormat::Text`] format. Doesn't depend on the
+/// other groups so it can be cached.
+pub fn write_text_group(out: &mut String, group: &Group, options: LinkOptions) -> std::fmt::Result {
+    writeln!(out, "{}:", group.name)?;
+    for (native_group, tabs) in sections(group, options) {
+        let indent = if let Some(native_group) = native_group {
+            writeln!(out, "    {}:", native_group.name)?;
+            "        "
+        } else {
+            "    "
+        };
+        for tab in tabs {
+            writeln!(out, "{indent}{}", tab_title(tab))?;
+            writeln!(out, "{indent}{}", tab.url)?;
+            writeln!(out)?;
+        }
+    }
+    writeln!(out)
+}
+
+fn write_markdown(
+    out: &mut String,
+    sources: &[Source<'_>],
+    options: LinkOptions,
+) -> std::fmt::Result {
+    let escape = |s: &str| s.replace('[', "\\[").replace(']', "\\]");
+    if options.table_of_contents {
+        writeln!(out, "# Table of contents\n")?;
+        let mut number = 0;
+        for source in sources {
+            if let Some(name) = source.name {
+                writeln!(out, "**{}**\n", escape(name))?;
+            }
+            for group in source.groups {
+                number += 1;
+                writeln!(
+                    out,
+                    "{number}. {} ({} tabs)",
+                    escape(&group.name),
+                    group.tabs.len()
+                )?;
+            }
+            writeln!(out)?;
+        }
+    }
+    for source in sources {
+        if let Some(name) = source.name {
+            writeln!(out, "# {}\n", escape(name))?;
+        }
+        let heading = "#".repeat(group_level(source));
+        for group in source.groups {
+            writeln!(out, "{heading} {}\n", escape(&group.name))?;
+            for (native_group, tabs) in sections(group, options) {
+                if let Some(native_group) = native_group {
+                    writeln!(out, "\n{heading}# {}\n", escape(&native_group.name))?;
+                }
+                for tab in tabs {
+                    writeln!(out, "- [{}](<{}>)", escape(&tab_title(tab)), tab.url)?;
+                }
+            }
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn write_html(out: &mut String, sources: &[Source<'_>], options: LinkOptions) -> std::fmt::Result {
+    let page_break_style = |page_break: bool| {
+        if page_break {
+            " style=\"break-before: page\""
+        } else {
+            ""
+        }
+    };
+    writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Firefox Tabs</title>\n</head>\n<body>"
+    )?;
+    if options.table_of_contents {
+        writeln!(out, "<h1>Table of contents</h1>")?;
+        let mut ix = 0;
+        for source in sources {
+            if let Some(name) = source.name {
+                writeln!(out, "<h2>{}</h2>", html_escape(name))?;
+            }
+            writeln!(out, "<ol start=\"{}\">", ix + 1)?;
+            for group in source.groups {
+                writeln!(
+                    out,
+                    "<li><a href=\"#group-{ix}\">{}</a> ({} tabs)</li>",
+                    html_escape(&group.name),
+                    group.tabs.len()
+                )?;
+                ix += 1;
+            }
+            writeln!(out, "</ol>")?;
+        }
+    }
+    let mut page_breaks = PageBreaks::new(options);
+    let mut ix = 0;
+    for source in sources {
+        if let Some(name) = source.name {
+            writeln!(
+                out,
+                "<h1{}>{}</h1>",
+                page_break_style(page_breaks.before_heading(true)),
+                html_escape(name)
+            )?;
+        }
+        let level = group_level(source);
+        for group in source.groups {
+            writeln!(
+                out,
+                "<h{level} id=\"group-{ix}\"{}>{}</h{level}>\n<ul>",
+                page_break_style(page_breaks.before_heading(false)),
+                html_escape(&group.name)
+            )?;
+            ix += 1;
+            for (native_group, tabs) in sections(group, options) {
+                if let Some(native_group) = native_group {
+                    let [r, g, b] = native_group.rgb().unwrap_or_default();
+                    writeln!(
+                        out,
+                        "<li><h{0} style=\"border-left: 6px solid #{r:02x}{g:02x}{b:02x}; padding-left: 6px\">{1}</h{0}>\n<ul>",
+                        level + 1,
+                        html_escape(&native_group.name)
+                    )?;
+                }
+                for tab in tabs {
+                    write_html_tab(out, tab, options)?;
+                }
+                if native_group.is_some() {
+                    writeln!(out, "</ul></li>")?;
+                }
+            }
+            writeln!(out, "</ul>")?;
+        }
+    }
+    writeln!(out, "</body>\n</html>")
+}
+
+fn write_html_tab(out: &mut String, tab: &Tab, options: LinkOptions) -> std::fmt::Result {
+    write!(out, "<li>")?;
+    if let Some(favicon) = tab.extra.favicon.as_deref().filter(|_| options.favicons) {
+        // Other URLs would load the icon from the internet when the file is
+        // opened:
+        if DataUrl::parse(favicon).is_some_and(|url| url.is_image()) {
+            write!(
+                out,
+                "<img src=\"{}\" width=\"16\" height=\"16\" alt=\"\" style=\"vertical-align: middle; margin-right: 4px\">",
+                html_escape(favicon)
+            )?;
+        }
+    }
+    write!(
+        out,
+        "<a href=\"{}\">{}</a>",
+        html_escape(&tab.url),
+        html_escape(&tab.title)
+    )?;
+    if tab.user_context_id != 0 {
+        let [r, g, b] = tab
+            .container
+            .as_ref()
+            .and_then(|c| c.rgb())
+            .unwrap_or_default();
+        write!(
+            out,
+            " <span style=\"color: #{r:02x}{g:02x}{b:02x}\">[{}]</span>",
+            html_escape(&tab.container_name())
+        )?;
+    }
+    writeln!(out, "</li>")
+}
+
+fn write_typst(out: &mut String, sources: &[Source<'_>], options: LinkOptions) -> std::fmt::Result {
+    let escape = |s: &str| {
+        s.chars()
+            .fold(String::with_capacity(s.len()), |mut acc, c| {
+                if "\\#*_`$<>@[]=-+/~\"'".contains(c) {
+                    acc.push('\\');
+                }
+                acc.push(c);
+                acc
+            })
+    };
+    if options.table_of_contents {
+        writeln!(out, "#outline()\n")?;
+    }
+    let mut page_breaks = PageBreaks::new(options);
+    for source in sources {
+        if let Some(name) = source.name {
+            if page_breaks.before_heading(true) {
+                writeln!(out, "#pagebreak()")?;
+            }
+            writeln!(out, "= {}\n", escape(name))?;
+        }
+        let heading = "=".repeat(group_level(source));
+        for group in source.groups {
+            if page_breaks.before_heading(false) {
+                writeln!(out, "#pagebreak()")?;
+            }
+            writeln!(out, "{heading} {}\n", escape(&group.name))?;
+            for (native_group, tabs) in sections(group, options) {
+                if let Some(native_group) = native_group {
+                    writeln!(out, "\n{heading}= {}\n", escape(&native_group.name))?;
+                }
+                for tab in tabs {
+                    writeln!(out, "- #link({:?})[{}]", tab.url, escape(&tab_title(tab)))?;
+                }
+            }
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_rtf(out: &mut String, sources: &[Source<'_>], options: LinkOptions) -> std::fmt::Result {
+    let escape = |s: &str| {
+        s.chars()
+            .fold(String::with_capacity(s.len()), |mut acc, c| {
+                match c {
+                    '\\' | '{' | '}' => {
+                        acc.push('\\');
+                        acc.push(c);
+                    }
+                    c if c.is_ascii() => acc.push(c),
+                    c => {
+                        let mut buf = [0; 2];
+                        for unit in c.encode_utf16(&mut buf) {
+                            // RTF uses signed 16 bit numbers for unicode escapes:
+                            let _ = write!(acc, "\\u{}?", *unit as i16);
+                        }
+                    }
+                }
+                acc
+            })
+    };
+    writeln!(out, "{{\\rtf1\\ansi\\deff0")?;
+    if options.table_of_contents {
+        writeln!(out, "{{\\b Table of contents}}\\par")?;
+        let mut number = 0;
+        for source in sources {
+            if let Some(name) = source.name {
+                writeln!(out, "{{\\i {}}}\\par", escape(name))?;
+            }
+            for group in source.groups {
+                number += 1;
+                writeln!(
+                    out,
+                    "{number}. {} ({} tabs)\\par",
+                    escape(&group.name),
+                    group.tabs.len()
+                )?;
+            }
+        }
+        writeln!(out, "\\par")?;
+    }
+    let mut page_breaks = PageBreaks::new(options);
+    for source in sources {
+        if let Some(name) = source.name {
+            if page_breaks.before_heading(true) {
+                writeln!(out, "\\page")?;
+            }
+            writeln!(out, "{{\\b\\fs32 {}}}\\par\\par", escape(name))?;
+        }
+        for group in source.groups {
+            if page_breaks.before_heading(false) {
+                writeln!(out, "\\page")?;
+            }
+            writeln!(out, "{{\\b {}}}\\par", escape(&group.name))?;
+            for (native_group, tabs) in sections(group, options) {
+                if let Some(native_group) = native_group {
+                    writeln!(out, "{{\\i {}}}\\par", escape(&native_group.name))?;
+                }
+                for tab in tabs {
+                    writeln!(
+                        out,
+                        "{{\\field{{\\*\\fldinst HYPERLINK \"{}\"}}{{\\fldrslt {}}}}}\\par",
+                        escape(&tab.url),
+                        escape(&tab_title(tab))
+                    )?;
+                }
+            }
+            writeln!(out, "\\par")?;
+        }
+    }
+    writeln!(out, "}}")
+}
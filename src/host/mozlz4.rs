@@ -0,0 +1,120 @@
+//! Firefox's `mozLz40` container format, used for `sessionstore.jsonlz4` as
+//! well as `search.json.mozlz4`, `addonStartup.json.lz4` and bookmark
+//! backups.
+//!
+//! A file starts with [`MAGIC`], followed by the size of the decompressed
+//! data as a little endian `u32` and then a single LZ4 block.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Magic bytes at the start of every `mozLz40` file.
+pub const MAGIC: &[u8] = b"mozLz40\0";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mozlz4Error {
+    /// The data doesn't start with [`MAGIC`].
+    NotMozlz4,
+    /// The data ended before the decompressed size.
+    MissingSize,
+    /// The LZ4 block is corrupt.
+    Lz4(String),
+    /// The LZ4 block didn't decompress to the size in the header.
+    WrongSize { expected: usize, actual: usize },
+    /// The data is too large for the `u32` size in the header.
+    TooLarge,
+}
+impl fmt::Display for Mozlz4Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mozlz4Error::NotMozlz4 => write!(f, "data doesn't start with \"mozLz40\""),
+            Mozlz4Error::MissingSize => write!(f, "data ended before the decompressed size"),
+            Mozlz4Error::Lz4(e) => write!(f, "corrupt LZ4 data: {e}"),
+            Mozlz4Error::WrongSize { expected, actual } => write!(
+                f,
+                "header says the data is {expected} bytes but it decompressed to {actual} bytes"
+            ),
+            Mozlz4Error::TooLarge => write!(f, "data is larger than 4 GiB"),
+        }
+    }
+}
+impl std::error::Error for Mozlz4Error {}
+
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, Mozlz4Error> {
+    let data = data.strip_prefix(MAGIC).ok_or(Mozlz4Error::NotMozlz4)?;
+    let (size, block) = data
+        .split_first_chunk::<4>()
+        .ok_or(Mozlz4Error::MissingSize)?;
+    let expected = u32::from_le_bytes(*size) as usize;
+    let out = lz4_flex::block::decompress(block, expected)
+        .map_err(|e| Mozlz4Error::Lz4(e.to_string()))?;
+    if out.len() != expected {
+        return Err(Mozlz4Error::WrongSize {
+            expected,
+            actual: out.len(),
+        });
+    }
+    Ok(out)
+}
+
+pub fn compress(data: &[u8]) -> Result<Vec<u8>, Mozlz4Error> {
+    let size = u32::try_from(data.len()).map_err(|_| Mozlz4Error::TooLarge)?;
+    let mut out = MAGIC.to_vec();
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&lz4_flex::block::compress(data));
+    Ok(out)
+}
+
+/// Compress or decompress a whole file, see [`convert_mozlz4_file`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mozlz4Mode {
+    Compress,
+    Decompress,
+}
+impl Mozlz4Mode {
+    pub fn all() -> &'static [Self] {
+        &[Self::Decompress, Self::Compress]
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Mozlz4Mode::Compress => "compress",
+            Mozlz4Mode::Decompress => "decompress",
+        }
+    }
+
+    /// Where to write the result if no output path is given:
+    /// `search.json.mozlz4` and `sessionstore.jsonlz4` decompress to
+    /// `search.json` and `sessionstore.json`, compressing adds `.mozlz4`.
+    pub fn default_output_path(&self, input: &Path) -> PathBuf {
+        let name = input
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let name = match self {
+            Mozlz4Mode::Compress => format!("{name}.mozlz4"),
+            Mozlz4Mode::Decompress => {
+                if let Some(stem) = [".mozlz4", ".lz4"]
+                    .iter()
+                    .find_map(|ext| name.strip_suffix(ext))
+                {
+                    stem.to_owned()
+                } else if let Some(stem) = [".jsonlz4", ".baklz4"]
+                    .iter()
+                    .find_map(|ext| name.strip_suffix(ext))
+                {
+                    format!("{stem}.json")
+                } else {
+                    format!("{name}.json")
+                }
+            }
+        };
+        input.with_file_name(name)
+    }
+}
+impl fmt::Display for Mozlz4Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
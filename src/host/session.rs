@@ -0,0 +1,527 @@
+//! A simplified view of the tabs stored in Firefox's sessionstore data.
+//!
+//! The types from `firefox_session_data` are used for the normal export
+//! pipeline, but when we want to regroup tabs in other ways than by window we
+//! need access to the individual tabs, so we read the few fields we care about
+//! straight from the JSON data.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use serde_json::Value;
+
+use super::{DataUrl, GenerateOptions, GroupBy};
+
+/// A Firefox container (also called a contextual identity), read from the
+/// `containers.json` file in a Firefox profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Container {
+    /// The `userContextId` that tabs in this container have.
+    pub id: u32,
+    pub name: String,
+    /// One of Firefox's named container colors, for example `"blue"`.
+    pub color: String,
+}
+impl Container {
+    /// Parse the data inside a `containers.json` file.
+    pub fn parse_all(data: &[u8]) -> Result<Vec<Self>, String> {
+        let json = serde_json::from_slice::<Value>(data)
+            .map_err(|e| format!("failed to parse containers JSON data: {e}"))?;
+        let identities = json
+            .get("identities")
+            .and_then(Value::as_array)
+            .ok_or("containers data has no \"identities\" list")?;
+
+        Ok(identities
+            .iter()
+            // Firefox has some private containers for internal use:
+            .filter(|identity| identity.get("public").and_then(Value::as_bool) != Some(false))
+            .filter_map(|identity| {
+                let id = identity.get("userContextId")?.as_u64()?.try_into().ok()?;
+                let name = match identity.get("name").and_then(Value::as_str) {
+                    Some(name) => name.to_owned(),
+                    // Default containers are only given a translation id like
+                    // "userContextPersonal.label":
+                    None => identity
+                        .get("l10nID")
+                        .or_else(|| identity.get("l10nId"))
+                        .and_then(Value::as_str)?
+                        .strip_prefix("userContext")?
+                        .trim_end_matches(".label")
+                        .to_owned(),
+                };
+                let color = identity
+                    .get("color")
+                    .and_then(Value::as_str)
+                    .unwrap_or("toolbar")
+                    .to_owned();
+                Some(Self { id, name, color })
+            })
+            .collect())
+    }
+
+    /// The RGB color that Firefox uses for this container's color name.
+    pub fn rgb(&self) -> Option<[u8; 3]> {
+        color_rgb(&self.color)
+    }
+}
+
+/// The RGB color for one of the named colors that Firefox uses for containers
+/// and tab groups.
+pub fn color_rgb(name: &str) -> Option<[u8; 3]> {
+    Some(match name {
+        "blue" => [0x37, 0xad, 0xff],
+        "turquoise" | "cyan" => [0x00, 0xc7, 0x9a],
+        "green" => [0x51, 0xcd, 0x00],
+        "yellow" => [0xff, 0xcb, 0x00],
+        "orange" => [0xff, 0x9f, 0x00],
+        "red" => [0xff, 0x61, 0x3d],
+        "pink" => [0xff, 0x4b, 0xda],
+        "purple" => [0xaf, 0x51, 0xf5],
+        "toolbar" | "gray" | "grey" => [0x7c, 0x7c, 0x7d],
+        _ => return None,
+    })
+}
+
+/// A native Firefox tab group inside a window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeGroup {
+    pub id: String,
+    pub name: String,
+    /// One of Firefox's named tab group colors, for example `"blue"`.
+    pub color: String,
+    pub collapsed: bool,
+}
+impl NativeGroup {
+    fn from_value(json: &Value) -> Option<Self> {
+        Some(Self {
+            id: json.get("id")?.as_str()?.to_owned(),
+            name: json
+                .get("name")
+                .and_then(Value::as_str)
+                .filter(|name| !name.is_empty())
+                .unwrap_or("Unnamed group")
+                .to_owned(),
+            color: json
+                .get("color")
+                .and_then(Value::as_str)
+                .unwrap_or("gray")
+                .to_owned(),
+            collapsed: json
+                .get("collapsed")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        })
+    }
+    pub fn rgb(&self) -> Option<[u8; 3]> {
+        color_rgb(&self.color)
+    }
+}
+
+/// A tab with the info that is needed to convert it into a link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tab {
+    pub url: String,
+    pub title: String,
+    /// Firefox container id, `0` if the tab isn't in a container.
+    pub user_context_id: u32,
+    /// The container with the tab's `user_context_id` if we know about it.
+    pub container: Option<Arc<Container>>,
+    /// The native Firefox tab group that the tab is part of.
+    pub native_group: Option<Arc<NativeGroup>>,
+    /// Everything else that is only shown when inspecting the tab.
+    pub extra: Arc<TabExtra>,
+}
+
+/// Info about a tab that isn't needed to write links.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TabExtra {
+    /// URL of the tab's icon, often a `data:` URL.
+    pub favicon: Option<String>,
+    /// Milliseconds since the Unix epoch.
+    pub last_accessed: Option<u64>,
+    pub pinned: bool,
+    pub hidden: bool,
+    pub muted: bool,
+    /// Id that Tree Style Tab or Sidebery uses for this tab.
+    pub tree_id: Option<String>,
+    /// Tree Style Tab or Sidebery id of the tab's parent.
+    pub parent_id: Option<String>,
+    /// Scroll position of the current page as `x,y`.
+    pub scroll: Option<String>,
+    /// `true` if Firefox remembered text that was typed into forms.
+    pub has_form_data: bool,
+    /// Number of pages in the tab's back/forward history.
+    pub history_length: usize,
+    /// Key of the tab in [`Window::tabs_pointer`]. For sessions this is the
+    /// position in the window's `tabs` array, tabs without history are skipped
+    /// so this can differ from the position in [`Window::tabs`].
+    pub json_key: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Window {
+    pub tabs: Vec<Tab>,
+    /// Native Firefox tab groups in this window.
+    pub native_groups: Vec<Arc<NativeGroup>>,
+    /// Name to use instead of the window's position, for example the title
+    /// of a bookmark folder.
+    pub name: Option<String>,
+    /// Nesting level of bookmark folders, always `0` for real windows.
+    pub depth: usize,
+    /// JSON pointer to the window in the data it was read from.
+    pub json_pointer: String,
+    /// JSON pointer to the array or object that holds the window's tabs.
+    pub tabs_pointer: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Session {
+    pub windows: Vec<Window>,
+    pub closed_windows: Vec<Window>,
+    /// When Firefox last wrote the session (`session.lastUpdate`), in
+    /// milliseconds since the Unix epoch.
+    pub last_update: Option<u64>,
+}
+impl Session {
+    /// Read tab info from sessionstore JSON data.
+    pub fn from_value(json: &Value, containers: &[Container]) -> Self {
+        let containers = containers.iter().cloned().map(Arc::new).collect::<Vec<_>>();
+        let windows = |key: &str| {
+            json.get(key)
+                .and_then(Value::as_array)
+                .map(|windows| {
+                    windows
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, window)| Window {
+                            json_pointer: format!("/{key}/{ix}"),
+                            tabs_pointer: format!("/{key}/{ix}/tabs"),
+                            ..Window::from_value(window, &containers)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Self {
+            windows: windows("windows"),
+            closed_windows: windows("_closedWindows"),
+            last_update: json
+                .get("session")
+                .and_then(|session| session.get("lastUpdate"))
+                .and_then(Value::as_u64),
+        }
+    }
+
+    /// `true` if any window has native Firefox tab groups.
+    pub fn has_native_groups(&self) -> bool {
+        self.windows
+            .iter()
+            .chain(&self.closed_windows)
+            .any(|window| !window.native_groups.is_empty())
+    }
+
+    /// `true` if any tab has Tree Style Tab or Sidebery data, which
+    /// `firefox_session_data` uses to group tabs into panels or trees
+    /// instead of windows.
+    pub fn has_tree_data(&self) -> bool {
+        self.windows
+            .iter()
+            .chain(&self.closed_windows)
+            .flat_map(|window| &window.tabs)
+            .any(|tab| tab.extra.tree_id.is_some())
+    }
+
+    /// Group all tabs in either the open or the closed windows.
+    pub fn groups(&self, open: bool, options: &GenerateOptions) -> Vec<Group> {
+        let windows = if open {
+            &self.windows
+        } else {
+            &self.closed_windows
+        };
+        let included = |tab: &&Tab| {
+            options
+                .container_filter
+                .is_none_or(|id| tab.user_context_id == id)
+        };
+        match options.group_by {
+            GroupBy::Window => windows
+                .iter()
+                .enumerate()
+                .map(|(ix, window)| Group {
+                    name: window.name.clone().unwrap_or_else(|| {
+                        if open {
+                            format!("Window {}", ix + 1)
+                        } else {
+                            format!("Closed window {}", ix + 1)
+                        }
+                    }),
+                    color: None,
+                    tabs: window.tabs.iter().filter(included).cloned().collect(),
+                    depth: window.depth,
+                    // Filtered windows aren't the same as the JSON data:
+                    json_path: options
+                        .container_filter
+                        .is_none()
+                        .then(|| window.json_pointer.clone()),
+                })
+                // Hide windows that only had tabs from other containers:
+                .filter(|group| options.container_filter.is_none() || !group.tabs.is_empty())
+                .collect(),
+            GroupBy::Host | GroupBy::Domain | GroupBy::Container => {
+                let mut groups = Vec::<Group>::new();
+                // Position of each group in `groups` by name:
+                let mut group_indexes = BTreeMap::<String, usize>::new();
+                for tab in windows
+                    .iter()
+                    .flat_map(|window| &window.tabs)
+                    .filter(included)
+                {
+                    let name = match options.group_by {
+                        GroupBy::Host => url_host(&tab.url).unwrap_or("Other").to_owned(),
+                        GroupBy::Container => tab.container_name(),
+                        _ => url_host(&tab.url)
+                            .map(registrable_domain)
+                            .unwrap_or("Other")
+                            .to_owned(),
+                    };
+
+                    if let Some(&ix) = group_indexes.get(&name) {
+                        groups[ix].tabs.push(tab.clone());
+                    } else {
+                        group_indexes.insert(name.clone(), groups.len());
+                        groups.push(Group {
+                            name,
+                            color: match options.group_by {
+                                GroupBy::Container => tab.container.as_ref().and_then(|c| c.rgb()),
+                                _ => None,
+                            },
+                            tabs: vec![tab.clone()],
+                            depth: 0,
+                            json_path: None,
+                        });
+                    }
+                }
+                if options.sort_groups {
+                    groups.sort_by(|a, b| a.name.cmp(&b.name));
+                }
+                groups
+            }
+        }
+    }
+}
+impl Window {
+    fn from_value(json: &Value, containers: &[Arc<Container>]) -> Self {
+        let native_groups = json
+            .get("groups")
+            .and_then(Value::as_array)
+            .map(|groups| {
+                groups
+                    .iter()
+                    .filter_map(NativeGroup::from_value)
+                    .map(Arc::new)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        Self {
+            tabs: json
+                .get("tabs")
+                .and_then(Value::as_array)
+                .map(|tabs| {
+                    tabs.iter()
+                        .enumerate()
+                        .filter_map(|(ix, tab)| {
+                            Tab::from_value(tab, ix, containers, &native_groups)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            native_groups,
+            ..Default::default()
+        }
+    }
+}
+impl Tab {
+    /// Name of the tab's container, also for tabs that aren't in a container.
+    pub fn container_name(&self) -> String {
+        match (&self.container, self.user_context_id) {
+            (Some(container), _) => container.name.clone(),
+            (None, 0) => "No container".to_owned(),
+            (None, id) => format!("Container {id}"),
+        }
+    }
+
+    fn from_value(
+        json: &Value,
+        json_index: usize,
+        containers: &[Arc<Container>],
+        native_groups: &[Arc<NativeGroup>],
+    ) -> Option<Self> {
+        let entries = json.get("entries")?.as_array()?;
+        // "index" is 1-based and points at the current history entry:
+        let index = json
+            .get("index")
+            .and_then(Value::as_u64)
+            .map_or(entries.len(), |ix| ix as usize);
+        let entry = index
+            .checked_sub(1)
+            .and_then(|ix| entries.get(ix))
+            .or(entries.last())?;
+
+        let url = entry.get("url")?.as_str()?.to_owned();
+        let title = entry
+            .get("title")
+            .and_then(Value::as_str)
+            .filter(|title| !title.is_empty())
+            .unwrap_or(&url)
+            .to_owned();
+        let user_context_id = json
+            .get("userContextId")
+            .and_then(Value::as_u64)
+            .and_then(|id| u32::try_from(id).ok())
+            .unwrap_or(0);
+        let container = containers.iter().find(|c| c.id == user_context_id).cloned();
+        let native_group = json
+            .get("groupId")
+            .and_then(Value::as_str)
+            .and_then(|id| native_groups.iter().find(|g| g.id == id))
+            .cloned();
+        Some(Self {
+            url,
+            title,
+            user_context_id,
+            container,
+            native_group,
+            extra: Arc::new(TabExtra {
+                json_key: json_index.to_string(),
+                ..TabExtra::from_value(json, entry, entries.len())
+            }),
+        })
+    }
+}
+impl TabExtra {
+    fn from_value(json: &Value, entry: &Value, history_length: usize) -> Self {
+        let flag = |key: &str| json.get(key).and_then(Value::as_bool).unwrap_or(false);
+        let ext_data = json.get("extData");
+        // Sidebery stores its data as a JSON string:
+        let sidebery = ext_data
+            .and_then(|data| data.get("sidebery-data"))
+            .and_then(|data| match data {
+                Value::String(text) => serde_json::from_str::<Value>(text).ok(),
+                other => Some(other.clone()),
+            });
+        let id = |value: Option<&Value>| match value? {
+            Value::String(id) => Some(id.clone()),
+            Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        };
+        let tree_style_tab = |key: &str| id(ext_data.and_then(|data| data.get(key)));
+        let sidebery_id = |key: &str| id(sidebery.as_ref().and_then(|data| data.get(key)));
+        Self {
+            favicon: json
+                .get("image")
+                .and_then(Value::as_str)
+                .filter(|image| !image.is_empty())
+                .map(ToOwned::to_owned),
+            last_accessed: json.get("lastAccessed").and_then(Value::as_u64),
+            pinned: flag("pinned"),
+            hidden: flag("hidden"),
+            muted: flag("muted"),
+            tree_id: tree_style_tab("treestyletab-id").or_else(|| sidebery_id("id")),
+            parent_id: tree_style_tab("treestyletab-parent")
+                .or_else(|| sidebery_id("parentId"))
+                // Sidebery uses -1 for tabs without a parent:
+                .filter(|id| id != "-1"),
+            // Older Firefox versions stored the scroll position per history
+            // entry:
+            scroll: [json, entry]
+                .into_iter()
+                .find_map(|value| value.get("scroll")?.get("scroll")?.as_str())
+                .map(ToOwned::to_owned),
+            has_form_data: json.get("formdata").is_some() || entry.get("formdata").is_some(),
+            history_length,
+            json_key: String::new(),
+        }
+    }
+}
+
+/// Some tabs that should be written as a section in the generated output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub name: String,
+    /// Color associated with the group, for example a container's color.
+    pub color: Option<[u8; 3]>,
+    pub tabs: Vec<Tab>,
+    /// Nesting level of bookmark folders.
+    pub depth: usize,
+    /// JSON pointer to the window or bookmark folder that the group is made
+    /// from, if it is one.
+    pub json_path: Option<String>,
+}
+impl Group {
+    /// The first favicon that a tab stored as a `data:` URL with image data.
+    pub fn favicon(&self) -> Option<Arc<[u8]>> {
+        self.tabs.iter().find_map(|tab| {
+            let url = DataUrl::parse(tab.extra.favicon.as_deref()?)?;
+            url.is_image().then(|| url.data.into())
+        })
+    }
+    /// Split the tabs into runs of tabs that are in the same native tab group
+    /// ([`None`] for tabs that aren't in a tab group).
+    pub fn sections(&self) -> Vec<(Option<&Arc<NativeGroup>>, &[Tab])> {
+        let mut sections = Vec::new();
+        let mut start = 0;
+        for (ix, tab) in self.tabs.iter().enumerate().skip(1) {
+            if tab.native_group != self.tabs[ix - 1].native_group {
+                sections.push((
+                    self.tabs[start].native_group.as_ref(),
+                    &self.tabs[start..ix],
+                ));
+                start = ix;
+            }
+        }
+        if let Some(first) = self.tabs.get(start) {
+            sections.push((first.native_group.as_ref(), &self.tabs[start..]));
+        }
+        sections
+    }
+}
+
+/// Get the host part of a URL.
+pub fn url_host(url: &str) -> Option<&str> {
+    let (_scheme, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = if host.starts_with('[') {
+        // IPv6 address:
+        &host[..=host.find(']')?]
+    } else {
+        host.split(':').next()?
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+/// Guess the registrable domain of a host (`en.wikipedia.org` ->
+/// `wikipedia.org`).
+///
+/// We don't ship the full public suffix list so this only knows about the
+/// most common second level suffixes like `co.uk`.
+pub fn registrable_domain(host: &str) -> &str {
+    const SECOND_LEVEL: &[&str] = &["co", "com", "org", "net", "ac", "gov", "edu", "ne", "or"];
+
+    if host.starts_with('[') || host.parse::<std::net::Ipv4Addr>().is_ok() {
+        return host;
+    }
+    let labels = host.split('.').collect::<Vec<_>>();
+    let keep = match labels.as_slice() {
+        [.., second, tld] if tld.len() == 2 && SECOND_LEVEL.contains(second) => 3,
+        _ => 2,
+    };
+    if labels.len() <= keep {
+        return host;
+    }
+    let skip = labels[..labels.len() - keep]
+        .iter()
+        .map(|label| label.len() + 1)
+        .sum::<usize>();
+    &host[skip..]
+}
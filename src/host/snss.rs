@@ -0,0 +1,294 @@
+//! Read Chromium's session files (`Current Session`, `Last Session` and
+//! `Sessions/Session_*`) into the same model as Firefox sessions.
+//!
+//! A file starts with [`MAGIC`] and a version, followed by commands that are
+//! replayed to build the session: a little endian `u16` size, a command id and
+//! then `size - 1` bytes of payload. Fixed size payloads are C structs while
+//! the rest are `base::Pickle`s where every field is aligned to 4 bytes.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::Arc,
+};
+
+use super::session::{Session, Tab, TabExtra, Window};
+
+/// Magic bytes at the start of every session file.
+pub const MAGIC: &[u8] = b"SNSS";
+
+// Command ids from Chromium's `session_service_commands.cc`:
+pub const SET_TAB_WINDOW: u8 = 0;
+pub const SET_TAB_INDEX_IN_WINDOW: u8 = 2;
+pub const TAB_NAVIGATION_PATH_PRUNED_FROM_BACK: u8 = 5;
+pub const UPDATE_TAB_NAVIGATION: u8 = 6;
+pub const SET_SELECTED_NAVIGATION_INDEX: u8 = 7;
+pub const TAB_NAVIGATION_PATH_PRUNED_FROM_FRONT: u8 = 11;
+pub const SET_PINNED_STATE: u8 = 12;
+pub const TAB_CLOSED: u8 = 16;
+pub const WINDOW_CLOSED: u8 = 17;
+pub const TAB_NAVIGATION_PATH_PRUNED: u8 = 24;
+pub const SET_WINDOW_USER_TITLE: u8 = 31;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnssError {
+    /// The data doesn't start with [`MAGIC`].
+    NotSnss,
+    /// Only versions 1 and 3 of the format are known.
+    UnsupportedVersion(i32),
+}
+impl fmt::Display for SnssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnssError::NotSnss => write!(f, "data doesn't start with \"SNSS\""),
+            SnssError::UnsupportedVersion(version) => {
+                write!(f, "unsupported Chromium session file version {version}")
+            }
+        }
+    }
+}
+impl std::error::Error for SnssError {}
+
+pub fn is_snss(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Reads the fields of a command's payload.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+impl<'a> Reader<'a> {
+    /// Skip the `u32` size header of a pickle.
+    fn pickle(data: &'a [u8]) -> Option<Self> {
+        let mut reader = Self { data };
+        let size = reader.u32()? as usize;
+        reader.data = reader.data.get(..size)?;
+        Some(reader)
+    }
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(..len)?;
+        self.data = &self.data[len..];
+        Some(bytes)
+    }
+    /// Read bytes and the padding after them in a pickle.
+    fn aligned(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.bytes(len)?;
+        self.bytes(len.next_multiple_of(4) - len)?;
+        Some(bytes)
+    }
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+    fn i32(&mut self) -> Option<i32> {
+        Some(self.u32()? as i32)
+    }
+    fn string(&mut self) -> Option<String> {
+        let len = usize::try_from(self.i32()?).ok()?;
+        Some(String::from_utf8_lossy(self.aligned(len)?).into_owned())
+    }
+    fn string16(&mut self) -> Option<String> {
+        let len = usize::try_from(self.i32()?).ok()?;
+        let units = self
+            .aligned(len.checked_mul(2)?)?
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]));
+        Some(
+            char::decode_utf16(units)
+                .map(|c| c.unwrap_or('\u{FFFD}'))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct SnssTab {
+    window_id: Option<i32>,
+    index: i32,
+    /// Url and title of each history entry.
+    navigations: BTreeMap<i32, (String, String)>,
+    selected_navigation: Option<i32>,
+    pinned: bool,
+}
+
+#[derive(Debug, Default)]
+struct SnssWindow {
+    title: Option<String>,
+    closed: bool,
+}
+
+/// The tabs and windows after replaying some commands.
+#[derive(Debug, Default)]
+struct Replay {
+    tabs: HashMap<i32, SnssTab>,
+    windows: HashMap<i32, SnssWindow>,
+}
+impl Replay {
+    fn tab(&mut self, tab_id: i32) -> &mut SnssTab {
+        self.tabs.entry(tab_id).or_default()
+    }
+    fn window(&mut self, window_id: i32) -> &mut SnssWindow {
+        self.windows.entry(window_id).or_default()
+    }
+
+    /// Apply a command, returns [`None`] if its payload is too short.
+    fn apply(&mut self, id: u8, mut payload: Reader<'_>) -> Option<()> {
+        match id {
+            SET_TAB_WINDOW => {
+                let window_id = payload.i32()?;
+                let tab_id = payload.i32()?;
+                self.window(window_id);
+                self.tab(tab_id).window_id = Some(window_id);
+            }
+            SET_TAB_INDEX_IN_WINDOW => {
+                let tab_id = payload.i32()?;
+                self.tab(tab_id).index = payload.i32()?;
+            }
+            UPDATE_TAB_NAVIGATION => {
+                let mut pickle = Reader::pickle(payload.data)?;
+                let tab_id = pickle.i32()?;
+                let index = pickle.i32()?;
+                let url = pickle.string()?;
+                let title = pickle.string16()?;
+                self.tab(tab_id).navigations.insert(index, (url, title));
+            }
+            SET_SELECTED_NAVIGATION_INDEX => {
+                let tab_id = payload.i32()?;
+                self.tab(tab_id).selected_navigation = Some(payload.i32()?);
+            }
+            TAB_NAVIGATION_PATH_PRUNED_FROM_BACK => {
+                // Only the first `count` entries are kept:
+                let tab_id = payload.i32()?;
+                let count = payload.i32()?;
+                self.tab(tab_id)
+                    .navigations
+                    .retain(|&index, _| index < count);
+            }
+            TAB_NAVIGATION_PATH_PRUNED_FROM_FRONT => {
+                let tab_id = payload.i32()?;
+                let count = payload.i32()?;
+                let tab = self.tab(tab_id);
+                tab.navigations = std::mem::take(&mut tab.navigations)
+                    .into_iter()
+                    .filter(|&(index, _)| index >= count)
+                    .map(|(index, navigation)| (index - count, navigation))
+                    .collect();
+                tab.selected_navigation = tab.selected_navigation.map(|ix| ix - count);
+            }
+            TAB_NAVIGATION_PATH_PRUNED => {
+                let tab_id = payload.i32()?;
+                let start = payload.i32()?;
+                let count = payload.i32()?;
+                let tab = self.tab(tab_id);
+                tab.navigations = std::mem::take(&mut tab.navigations)
+                    .into_iter()
+                    .filter(|&(index, _)| index < start || index >= start + count)
+                    .map(|(index, navigation)| {
+                        (
+                            if index < start { index } else { index - count },
+                            navigation,
+                        )
+                    })
+                    .collect();
+                tab.selected_navigation =
+                    tab.selected_navigation
+                        .map(|ix| if ix < start + count { ix } else { ix - count });
+            }
+            SET_PINNED_STATE => {
+                let tab_id = payload.i32()?;
+                self.tab(tab_id).pinned = *payload.bytes(1)?.first()? != 0;
+            }
+            TAB_CLOSED => {
+                self.tabs.remove(&payload.i32()?);
+            }
+            WINDOW_CLOSED => {
+                let window_id = payload.i32()?;
+                self.window(window_id).closed = true;
+            }
+            SET_WINDOW_USER_TITLE => {
+                let mut pickle = Reader::pickle(payload.data)?;
+                let window_id = pickle.i32()?;
+                let title = pickle.string()?;
+                self.window(window_id).title = Some(title).filter(|title| !title.is_empty());
+            }
+            // Selected tabs, window bounds and everything else isn't shown:
+            _ => {}
+        }
+        Some(())
+    }
+}
+
+/// Replay the commands in a session file.
+pub fn session_from_snss(data: &[u8]) -> Result<Session, SnssError> {
+    let data = data.strip_prefix(MAGIC).ok_or(SnssError::NotSnss)?;
+    let mut reader = Reader { data };
+    let version = reader.i32().ok_or(SnssError::NotSnss)?;
+    if !matches!(version, 1 | 3) {
+        return Err(SnssError::UnsupportedVersion(version));
+    }
+
+    let mut replay = Replay::default();
+    // Chromium stops at a command that was only partly written, for example
+    // if the browser crashed:
+    while let Some(size) = reader.bytes(2) {
+        let size = u16::from_le_bytes([size[0], size[1]]) as usize;
+        let Some((&id, payload)) = reader.bytes(size).and_then(<[u8]>::split_first) else {
+            break;
+        };
+        // Commands that don't have the expected fields are skipped:
+        replay.apply(id, Reader { data: payload });
+    }
+    let Replay { tabs, windows } = replay;
+
+    let mut window_tabs = HashMap::<i32, Vec<(i32, i32, Tab)>>::new();
+    for (tab_id, tab) in tabs {
+        let Some(window_id) = tab.window_id else {
+            continue;
+        };
+        let navigation = tab
+            .selected_navigation
+            .and_then(|ix| tab.navigations.get(&ix))
+            .or_else(|| tab.navigations.values().next_back());
+        let Some((url, title)) = navigation else {
+            continue;
+        };
+        window_tabs.entry(window_id).or_default().push((
+            tab.index,
+            tab_id,
+            Tab {
+                url: url.clone(),
+                title: if title.is_empty() { url } else { title }.clone(),
+                user_context_id: 0,
+                container: None,
+                native_group: None,
+                extra: Arc::new(TabExtra {
+                    pinned: tab.pinned,
+                    history_length: tab.navigations.len(),
+                    json_key: tab_id.to_string(),
+                    ..TabExtra::default()
+                }),
+            },
+        ));
+    }
+
+    let mut window_ids = windows.keys().copied().collect::<Vec<_>>();
+    window_ids.sort();
+    let mut session = Session::default();
+    for window_id in window_ids {
+        let window = &windows[&window_id];
+        let Some(mut tabs) = window_tabs.remove(&window_id) else {
+            continue;
+        };
+        tabs.sort_by_key(|(index, tab_id, _)| (*index, *tab_id));
+        let target = if window.closed {
+            &mut session.closed_windows
+        } else {
+            &mut session.windows
+        };
+        target.push(Window {
+            tabs: tabs.into_iter().map(|(_, _, tab)| tab).collect(),
+            name: window.title.clone(),
+            ..Window::default()
+        });
+    }
+    Ok(session)
+}
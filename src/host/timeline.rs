@@ -0,0 +1,212 @@
+//! Read every session file that a Firefox profile keeps so that the number of
+//! windows and tabs can be followed over time.
+//!
+//! Firefox keeps the latest session, the one it wrote before that, the session
+//! from the previous startup and a copy from before each upgrade
+//! (`upgrade.jsonlz4-{build id}`). Since Firefox replaces these files the
+//! sessions can also be archived to a folder of our own.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{spawn_blocking, Error, FileInfo, FirefoxProfileInfo};
+
+/// The session that was read from one file.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub file_path: PathBuf,
+    /// When Firefox wrote the session, or when the file was modified if the
+    /// session doesn't say.
+    pub time: SystemTime,
+    pub windows: usize,
+    pub closed_windows: usize,
+    /// Tabs in open windows.
+    pub tab_count: usize,
+    /// Titles of the tabs in open windows by their URL.
+    tabs: Arc<BTreeMap<String, String>>,
+}
+impl Snapshot {
+    async fn load(file_path: PathBuf) -> Result<Self, Error> {
+        let mut info = FileInfo::new(file_path);
+        info.load_data().await?;
+        info.decompress_data().await?;
+        info.parse_session_data().await?;
+        let session = info.own_session()?;
+        let time = match session.last_update {
+            Some(millis) => UNIX_EPOCH + Duration::from_millis(millis),
+            None => std::fs::metadata(&*info.file_path)
+                .and_then(|metadata| metadata.modified())
+                .map_err(|e| Error::read(&info.file_path, e))?,
+        };
+        let open_tabs = session.windows.iter().flat_map(|window| &window.tabs);
+        Ok(Self {
+            file_path: info.file_path.to_path_buf(),
+            time,
+            windows: session.windows.len(),
+            closed_windows: session.closed_windows.len(),
+            tab_count: open_tabs.clone().count(),
+            tabs: Arc::new(
+                open_tabs
+                    .map(|tab| (tab.url.clone(), tab.title.clone()))
+                    .collect(),
+            ),
+        })
+    }
+
+    /// `true` if a tab with this URL is open.
+    pub fn contains(&self, url: &str) -> bool {
+        self.tabs.contains_key(url)
+    }
+    /// URLs and titles of tabs that are open in this snapshot but not in
+    /// `other`.
+    pub fn tabs_not_in<'a>(&'a self, other: &Snapshot) -> Vec<(&'a str, &'a str)> {
+        self.tabs
+            .iter()
+            .filter(|(url, _)| !other.contains(url))
+            .map(|(url, title)| (url.as_str(), title.as_str()))
+            .collect()
+    }
+}
+
+/// The first and last snapshot that a tab was open in. The tab might have
+/// been closed in between.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabLifetime {
+    pub url: String,
+    pub title: String,
+    /// Index into [`Timeline::snapshots`].
+    pub first_seen: usize,
+    /// Index into [`Timeline::snapshots`].
+    pub last_seen: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    /// The oldest session first.
+    pub snapshots: Vec<Snapshot>,
+    /// Files that couldn't be read.
+    pub failed: Vec<(PathBuf, Error)>,
+}
+impl Timeline {
+    /// Tabs whose URL or title contains `query`, ignoring case, in the order
+    /// that they were first seen.
+    pub fn find_tabs(&self, query: &str) -> Vec<TabLifetime> {
+        let query = query.to_lowercase();
+        let mut found = BTreeMap::<&str, TabLifetime>::new();
+        for (ix, snapshot) in self.snapshots.iter().enumerate() {
+            let matching = snapshot.tabs.iter().filter(|(url, title)| {
+                url.to_lowercase().contains(&query) || title.to_lowercase().contains(&query)
+            });
+            for (url, title) in matching {
+                found
+                    .entry(url)
+                    .and_modify(|tab| {
+                        tab.last_seen = ix;
+                        tab.title.clone_from(title);
+                    })
+                    .or_insert_with(|| TabLifetime {
+                        url: url.clone(),
+                        title: title.clone(),
+                        first_seen: ix,
+                        last_seen: ix,
+                    });
+            }
+        }
+        let mut found = found.into_values().collect::<Vec<_>>();
+        found.sort_by_key(|tab| tab.first_seen);
+        found
+    }
+}
+
+impl FirefoxProfileInfo {
+    /// Where the profile's session files are copied to inside the `archive`
+    /// folder.
+    fn archive_folder(&self, archive: &Path) -> PathBuf {
+        archive.join(&*self.name())
+    }
+    /// Every session file in the profile, including the backups from before
+    /// upgrades, and the copies that [`archive_session_files`] made.
+    pub fn history_files(&self, archive: Option<&Path>) -> Vec<PathBuf> {
+        let list = |folder: &Path, keep: fn(&str) -> bool| {
+            let mut files = std::fs::read_dir(folder)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| keep(&path.file_name().unwrap_or_default().to_string_lossy()))
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+        let mut files = self.session_files();
+        files.extend(list(&self.path.join("sessionstore-backups"), |name| {
+            name.starts_with("upgrade.jsonlz4-")
+        }));
+        if let Some(archive) = archive {
+            files.extend(list(&self.archive_folder(archive), |name| {
+                name.ends_with("lz4")
+            }));
+        }
+        files
+    }
+}
+
+/// Default folder for [`archive_session_files`], [`None`] if there is no
+/// config folder.
+pub fn archive_folder() -> Option<PathBuf> {
+    Some(super::config_folder()?.join("archive"))
+}
+
+/// Read every file from [`FirefoxProfileInfo::history_files`].
+pub async fn load_timeline(profile: FirefoxProfileInfo, archive: Option<PathBuf>) -> Timeline {
+    let mut timeline = Timeline::default();
+    for path in profile.history_files(archive.as_deref()) {
+        match Snapshot::load(path.clone()).await {
+            Ok(snapshot) => timeline.snapshots.push(snapshot),
+            Err(e) => timeline.failed.push((path, e)),
+        }
+    }
+    timeline.snapshots.sort_by(|a, b| {
+        a.time
+            .cmp(&b.time)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    timeline
+}
+
+/// Copy the profile's session files to a folder inside `archive` so that
+/// they are kept after Firefox replaces them. Copies are named after when
+/// the file was modified so archiving again only copies files that changed.
+/// Returns how many files were copied.
+pub async fn archive_session_files(
+    profile: FirefoxProfileInfo,
+    archive: PathBuf,
+) -> Result<usize, Error> {
+    spawn_blocking(move || {
+        let folder = profile.archive_folder(&archive);
+        std::fs::create_dir_all(&folder).map_err(|e| Error::write(&folder, e))?;
+        let mut copied = 0;
+        for file in profile.session_files() {
+            let modified = std::fs::metadata(&file)
+                .and_then(|metadata| metadata.modified())
+                .map_err(|e| Error::read(&file, e))?;
+            let secs = modified
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let name = file.file_name().unwrap_or_default().to_string_lossy();
+            let target = folder.join(format!("{secs}-{name}"));
+            if target.exists() {
+                continue;
+            }
+            std::fs::copy(&file, &target).map_err(|e| Error::write(&target, e))?;
+            copied += 1;
+        }
+        Ok(copied)
+    })
+    .await
+}
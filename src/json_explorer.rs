@@ -0,0 +1,370 @@
+//! Tree view of the raw sessionstore JSON, for debugging sessions that aren't
+//! converted the way one would expect.
+//!
+//! Nodes are identified by JSON pointers (RFC 6901) like `/windows/0/tabs/3`,
+//! the same format that [`Value::pointer`] accepts.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use iced::widget::{
+    button, column, container, horizontal_space, mouse_area, row, scrollable, text, text_input,
+    vertical_space,
+};
+use iced::{Alignment, Element, Length, Task};
+use serde_json::Value;
+
+use crate::link_list::{monospace, CHAR_WIDTH, LINE_HEIGHT};
+
+/// Id of the [`scrollable`] that shows the tree.
+const SCROLL_ID: &str = "json-explorer";
+/// Extra rows that are laid out above and below the visible area.
+const OVERSCAN: usize = 20;
+/// Number of rows shown before the size of the viewport is known.
+const INITIAL_VISIBLE_ROWS: usize = 100;
+/// Long strings, for example favicon data URLs, are cut off after this many
+/// characters. The whole value can still be copied.
+const MAX_VALUE_CHARS: usize = 120;
+/// Characters of indentation per level of nesting.
+const INDENT: usize = 2;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Loaded(Result<Arc<Value>, String>),
+    /// Expand or collapse an object or array.
+    Toggle(String),
+    Select(String),
+    SetQuery(String),
+    /// Select the next node whose key or value contains the query.
+    FindNext,
+    CopyPath,
+    CopyValue,
+    Scrolled {
+        offset_y: f32,
+        height: f32,
+    },
+}
+
+/// A visible node in the tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Row {
+    path: String,
+    depth: usize,
+    /// Key or array index followed by the value or a summary of its children.
+    label: String,
+    /// `None` for values without children.
+    expanded: Option<bool>,
+}
+
+#[derive(Debug, Default)]
+pub struct State {
+    root: Option<Arc<Value>>,
+    /// Why the JSON couldn't be parsed.
+    error: Option<String>,
+    /// Paths of expanded objects and arrays.
+    expanded: HashSet<String>,
+    rows: Vec<Row>,
+    /// Number of characters in the longest row, including indentation.
+    longest_row: usize,
+    selected: Option<String>,
+    query: String,
+    /// `true` if the last search didn't find anything.
+    no_match: bool,
+    offset_y: f32,
+    viewport_height: Option<f32>,
+}
+impl State {
+    pub fn is_loaded(&self) -> bool {
+        self.root.is_some() || self.error.is_some()
+    }
+
+    /// Expand every parent of a node, select it and scroll to it.
+    pub fn reveal(&mut self, path: &str) -> Task<Message> {
+        let Some(root) = &self.root else {
+            return Task::none();
+        };
+        if root.pointer(path).is_none() {
+            log::warn!("No JSON at {path}");
+            return Task::none();
+        }
+        let mut parent = path;
+        while let Some((prefix, _)) = parent.rsplit_once('/') {
+            self.expanded.insert(prefix.to_owned());
+            parent = prefix;
+        }
+        self.selected = Some(path.to_owned());
+        self.rebuild_rows();
+
+        let Some(ix) = self.rows.iter().position(|row| row.path == path) else {
+            return Task::none();
+        };
+        // Show a few rows above the selected row:
+        self.offset_y = ix.saturating_sub(3) as f32 * LINE_HEIGHT;
+        scrollable::scroll_to(
+            scrollable::Id::new(SCROLL_ID),
+            scrollable::AbsoluteOffset {
+                x: 0.0,
+                y: self.offset_y,
+            },
+        )
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Loaded(Ok(root)) => {
+                self.root = Some(root);
+                self.error = None;
+                self.expanded = HashSet::from([String::new()]);
+                self.rebuild_rows();
+            }
+            Message::Loaded(Err(e)) => {
+                self.root = None;
+                self.error = Some(e);
+                self.rows.clear();
+            }
+            Message::Toggle(path) => {
+                if !self.expanded.remove(&path) {
+                    self.expanded.insert(path);
+                }
+                self.rebuild_rows();
+            }
+            Message::Select(path) => {
+                self.selected = Some(path);
+            }
+            Message::SetQuery(query) => {
+                self.query = query;
+                self.no_match = false;
+            }
+            Message::FindNext => {
+                let found = self.root.as_ref().and_then(|root| {
+                    find_next(root, self.selected.as_deref().unwrap_or(""), &self.query)
+                });
+                self.no_match = found.is_none();
+                if let Some(path) = found {
+                    return self.reveal(&path);
+                }
+            }
+            Message::CopyPath => {
+                if let Some(path) = &self.selected {
+                    return iced::clipboard::write(path.clone());
+                }
+            }
+            Message::CopyValue => {
+                let value = self
+                    .root
+                    .as_ref()
+                    .zip(self.selected.as_ref())
+                    .and_then(|(root, path)| root.pointer(path));
+                if let Some(value) = value {
+                    return iced::clipboard::write(
+                        serde_json::to_string_pretty(value).unwrap_or_default(),
+                    );
+                }
+            }
+            Message::Scrolled { offset_y, height } => {
+                self.offset_y = offset_y.max(0.0);
+                self.viewport_height = Some(height);
+            }
+        }
+        Task::none()
+    }
+
+    fn rebuild_rows(&mut self) {
+        self.rows.clear();
+        if let Some(root) = self.root.clone() {
+            self.add_rows(&root, String::new(), "(root)", 0);
+        }
+        self.longest_row = self
+            .rows
+            .iter()
+            .map(|row| row.depth * INDENT + 2 + row.label.chars().count())
+            .max()
+            .unwrap_or(0);
+    }
+    fn add_rows(&mut self, value: &Value, path: String, key: &str, depth: usize) {
+        let children = match value {
+            Value::Object(map) => Some(map.len()),
+            Value::Array(list) => Some(list.len()),
+            _ => None,
+        };
+        let expanded = children.map(|_| self.expanded.contains(&path));
+        self.rows.push(Row {
+            path: path.clone(),
+            depth,
+            label: format!("{key}: {}", summary(value)),
+            expanded,
+        });
+        if expanded != Some(true) {
+            return;
+        }
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map {
+                    self.add_rows(child, child_path(&path, key), key, depth + 1);
+                }
+            }
+            Value::Array(list) => {
+                for (ix, child) in list.iter().enumerate() {
+                    let key = ix.to_string();
+                    self.add_rows(child, child_path(&path, &key), &key, depth + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        if let Some(error) = &self.error {
+            return text(format!("Failed to parse JSON: {error}")).into();
+        }
+        if self.root.is_none() {
+            return text("Parsing JSON...").into();
+        }
+
+        let toolbar = row![
+            text_input("Search keys and values", &self.query)
+                .on_input(Message::SetQuery)
+                .on_submit(Message::FindNext)
+                .width(250),
+            button("Find next")
+                .on_press_maybe((!self.query.trim().is_empty()).then_some(Message::FindNext)),
+        ]
+        .push_maybe(self.no_match.then(|| text("No matches")))
+        .push(horizontal_space())
+        .push_maybe(self.selected.as_ref().map(|path| {
+            row![
+                text(if path.is_empty() { "/" } else { path }),
+                button("Copy path").on_press(Message::CopyPath),
+                button("Copy value").on_press(Message::CopyValue),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center)
+        }))
+        .spacing(5)
+        .align_y(Alignment::Center);
+
+        let visible = self.viewport_height.map_or(INITIAL_VISIBLE_ROWS, |height| {
+            (height / LINE_HEIGHT).ceil() as usize + 1
+        });
+        let first_visible = ((self.offset_y / LINE_HEIGHT) as usize).min(self.rows.len());
+        let first = first_visible.saturating_sub(OVERSCAN);
+        let last = (first_visible + visible + OVERSCAN).min(self.rows.len());
+
+        let rows = column(self.rows[first..last].iter().map(|row| {
+            let toggle = match row.expanded {
+                Some(expanded) => mouse_area(monospace(if expanded { "▾ " } else { "▸ " }))
+                    .on_press(Message::Toggle(row.path.clone()))
+                    .interaction(iced::mouse::Interaction::Pointer)
+                    .into(),
+                None => Element::from(monospace("  ")),
+            };
+            let selected = self.selected.as_ref() == Some(&row.path);
+            container(row![
+                monospace(" ".repeat(row.depth * INDENT)),
+                toggle,
+                mouse_area(monospace(row.label.as_str()))
+                    .on_press(Message::Select(row.path.clone())),
+            ])
+            .height(LINE_HEIGHT)
+            .width(Length::Fill)
+            .style(move |theme: &iced::Theme| {
+                if selected {
+                    container::Style::default()
+                        .background(theme.extended_palette().primary.weak.color.scale_alpha(0.4))
+                } else {
+                    container::Style::default()
+                }
+            })
+            .into()
+        }));
+
+        let tree = scrollable(
+            column![
+                vertical_space().height(first as f32 * LINE_HEIGHT),
+                rows,
+                vertical_space().height((self.rows.len() - last) as f32 * LINE_HEIGHT),
+            ]
+            .width(self.longest_row as f32 * CHAR_WIDTH + 20.0),
+        )
+        .id(scrollable::Id::new(SCROLL_ID))
+        .direction(scrollable::Direction::Both {
+            vertical: scrollable::Scrollbar::default(),
+            horizontal: scrollable::Scrollbar::default(),
+        })
+        .on_scroll(|viewport| Message::Scrolled {
+            offset_y: viewport.absolute_offset().y,
+            height: viewport.bounds().height,
+        })
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        column![toolbar, tree].spacing(5).into()
+    }
+}
+
+/// Path of a child node, escaping `~` and `/` in the key as RFC 6901 requires.
+pub fn child_path(parent: &str, key: &str) -> String {
+    format!("{parent}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Short description of a value that fits on one line.
+fn summary(value: &Value) -> String {
+    match value {
+        Value::Object(map) => format!("{{{} keys}}", map.len()),
+        Value::Array(list) => format!("[{} items]", list.len()),
+        Value::String(text) if text.chars().count() > MAX_VALUE_CHARS => {
+            let start = text.chars().take(MAX_VALUE_CHARS).collect::<String>();
+            format!("{:?}", start + "…")
+        }
+        other => other.to_string(),
+    }
+}
+
+/// The first node after `after` in document order whose key or value contains
+/// `query`, ignoring case. Starts from the beginning if there are no matches
+/// after `after`.
+pub fn find_next(root: &Value, after: &str, query: &str) -> Option<String> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+    let matches = |key: &str, value: &Value| {
+        key.to_lowercase().contains(&query)
+            || match value {
+                Value::String(text) => text.to_lowercase().contains(&query),
+                Value::Number(_) | Value::Bool(_) => value.to_string().contains(&query),
+                _ => false,
+            }
+    };
+
+    // Depth first search where the first match after `after` wins, otherwise
+    // the first match overall:
+    let mut first = None;
+    let mut passed = false;
+    let mut stack = vec![(String::new(), String::new(), root)];
+    while let Some((path, key, value)) = stack.pop() {
+        if passed && matches(&key, value) {
+            return Some(path);
+        }
+        if first.is_none() && matches(&key, value) {
+            first = Some(path.clone());
+        }
+        passed |= path == after;
+        // Children are pushed in reverse so that they are visited in order:
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map.iter().rev() {
+                    stack.push((child_path(&path, key), key.clone(), child));
+                }
+            }
+            Value::Array(list) => {
+                // Array indexes aren't searched:
+                for (ix, child) in list.iter().enumerate().rev() {
+                    stack.push((child_path(&path, &ix.to_string()), String::new(), child));
+                }
+            }
+            _ => {}
+        }
+    }
+    first
+}
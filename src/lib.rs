@@ -0,0 +1,4 @@
+//! Code that doesn't depend on the user interface. Kept in a library so that
+//! it can be used by the integration tests in the `tests` folder.
+
+pub mod host;
@@ -0,0 +1,245 @@
+//! Read-only view of the generated links that only lays out the lines that
+//! are visible, so that sessions with many thousands of tabs can be scrolled
+//! without delay.
+
+use std::ops::Range;
+
+use iced::widget::{column, container, mouse_area, row, scrollable, text, vertical_space};
+use iced::{mouse, Element, Font, Length, Task};
+
+/// Id of the [`scrollable`] that shows the lines.
+const SCROLL_ID: &str = "link-list";
+/// Height of every line in pixels. All lines have the same height since they
+/// never wrap, which makes it easy to know which lines are visible.
+pub const LINE_HEIGHT: f32 = 20.0;
+const FONT_SIZE: f32 = 14.0;
+/// Approximate width of a character in the monospace font.
+pub const CHAR_WIDTH: f32 = FONT_SIZE * 0.6;
+/// Extra lines that are laid out above and below the visible area so that
+/// fast scrolling doesn't show empty space.
+const OVERSCAN: usize = 20;
+/// Number of lines shown before the size of the viewport is known.
+const INITIAL_VISIBLE_LINES: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Scrolled {
+        offset_y: f32,
+        height: f32,
+    },
+    /// Select a line, or extend the selection if `extend` is `true`.
+    ClickLine {
+        line: usize,
+        extend: bool,
+    },
+    /// A link was clicked. Handled by the parent since it knows how the user
+    /// wants to open links.
+    OpenLink(String),
+}
+
+#[derive(Debug, Default)]
+pub struct State {
+    text: String,
+    /// Byte offset where each line starts in `text`.
+    line_starts: Vec<usize>,
+    /// Number of characters in the longest line.
+    longest_line: usize,
+    offset_y: f32,
+    /// `None` until the list has been scrolled once.
+    viewport_height: Option<f32>,
+    /// Line where the selection started and line where it ends.
+    selection: Option<(usize, usize)>,
+}
+impl State {
+    /// Replace the text and scroll back to the top.
+    pub fn set_text<T: Send + 'static>(&mut self, text: String) -> Task<T> {
+        let content = text.strip_suffix('\n').unwrap_or(&text);
+        self.line_starts = if content.is_empty() {
+            Vec::new()
+        } else {
+            std::iter::once(0)
+                .chain(content.match_indices('\n').map(|(index, _)| index + 1))
+                .collect()
+        };
+        self.text = text;
+        self.longest_line = (0..self.line_count())
+            .map(|line| self.line(line).chars().count())
+            .max()
+            .unwrap_or(0);
+        self.offset_y = 0.0;
+        self.selection = None;
+        scrollable::scroll_to(
+            scrollable::Id::new(SCROLL_ID),
+            scrollable::AbsoluteOffset::default(),
+        )
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// A line without its line break.
+    pub fn line(&self, line: usize) -> &str {
+        let start = self.line_starts[line];
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.text.len(), |next| next - 1);
+        self.text[start..end].trim_end_matches(['\r', '\n'])
+    }
+
+    /// Index of the line at the top of the view.
+    pub fn first_visible_line(&self) -> usize {
+        ((self.offset_y / LINE_HEIGHT) as usize).min(self.line_count())
+    }
+
+    /// The lines that are laid out, including some lines outside the view.
+    pub fn rendered_lines(&self) -> Range<usize> {
+        let visible = self
+            .viewport_height
+            .map_or(INITIAL_VISIBLE_LINES, |height| {
+                (height / LINE_HEIGHT).ceil() as usize + 1
+            });
+        let first = self.first_visible_line().saturating_sub(OVERSCAN);
+        let last = (self.first_visible_line() + visible + OVERSCAN).min(self.line_count());
+        first..last
+    }
+
+    /// The selected lines.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        self.selection
+            .map(|(anchor, end)| anchor.min(end)..anchor.max(end) + 1)
+    }
+
+    /// Text of the selected lines with a line break after each line.
+    pub fn selected_text(&self) -> Option<String> {
+        let selection = self.selection()?;
+        let mut text = String::new();
+        for line in selection {
+            text.push_str(self.line(line));
+            text.push('\n');
+        }
+        Some(text)
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Scrolled { offset_y, height } => {
+                self.offset_y = offset_y.max(0.0);
+                self.viewport_height = Some(height);
+            }
+            Message::ClickLine { line, extend } => {
+                self.selection = match self.selection {
+                    Some((anchor, _)) if extend => Some((anchor, line)),
+                    // Clicking the only selected line unselects it:
+                    Some((anchor, end)) if anchor == line && end == line => None,
+                    _ => Some((line, line)),
+                };
+            }
+            Message::OpenLink(_) => {}
+        }
+    }
+
+    /// `extend_selection` should be `true` while shift is held so that clicks
+    /// select a range of lines.
+    pub fn view(&self, extend_selection: bool) -> Element<'_, Message> {
+        let rendered = self.rendered_lines();
+        let selection = self.selection().unwrap_or_default();
+        let number_width = self.line_count().max(1).to_string().len();
+        let content_width = (number_width + 2 + self.longest_line) as f32 * CHAR_WIDTH + 20.0;
+
+        let lines = column(rendered.clone().map(|line| {
+            let click = Message::ClickLine {
+                line,
+                extend: extend_selection,
+            };
+            let number = mouse_area(
+                monospace(format!("{:>number_width$}  ", line + 1)).style(text::secondary),
+            )
+            .on_press(click.clone());
+            let selected = selection.contains(&line);
+            container(row([number.into(), view_line(self.line(line), click)]))
+                .height(LINE_HEIGHT)
+                .width(Length::Fill)
+                .style(move |theme: &iced::Theme| {
+                    if selected {
+                        let palette = theme.extended_palette();
+                        container::Style::default()
+                            .background(palette.primary.weak.color.scale_alpha(0.4))
+                    } else {
+                        container::Style::default()
+                    }
+                })
+                .into()
+        }));
+
+        scrollable(
+            column![
+                vertical_space().height(rendered.start as f32 * LINE_HEIGHT),
+                lines,
+                vertical_space().height((self.line_count() - rendered.end) as f32 * LINE_HEIGHT),
+            ]
+            .width(content_width),
+        )
+        .id(scrollable::Id::new(SCROLL_ID))
+        .direction(scrollable::Direction::Both {
+            vertical: scrollable::Scrollbar::default(),
+            horizontal: scrollable::Scrollbar::default(),
+        })
+        .on_scroll(|viewport| Message::Scrolled {
+            offset_y: viewport.absolute_offset().y,
+            height: viewport.bounds().height,
+        })
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+/// Text in the monospace font with the line height of the list.
+pub fn monospace<'a>(content: impl text::IntoFragment<'a>) -> text::Text<'a> {
+    text(content)
+        .font(Font::MONOSPACE)
+        .size(FONT_SIZE)
+        .line_height(text::LineHeight::Absolute(LINE_HEIGHT.into()))
+        .wrapping(text::Wrapping::None)
+}
+
+/// A line where the first link can be clicked to open it and the rest of the
+/// line selects the line.
+fn view_line(line: &str, click: Message) -> Element<'_, Message> {
+    let Some(link) = find_link(line) else {
+        return mouse_area(monospace(line)).on_press(click).into();
+    };
+    let url = &line[link.clone()];
+    row([
+        mouse_area(monospace(&line[..link.start]))
+            .on_press(click.clone())
+            .into(),
+        mouse_area(monospace(url).style(text::primary))
+            .on_press(Message::OpenLink(url.to_owned()))
+            .interaction(mouse::Interaction::Pointer)
+            .into(),
+        mouse_area(monospace(&line[link.end..]))
+            .on_press(click)
+            .into(),
+    ])
+    .into()
+}
+
+/// Byte range of the first URL in a line, for example inside a Markdown or
+/// HTML link.
+pub fn find_link(line: &str) -> Option<Range<usize>> {
+    let separator = line.find("://")?;
+    let start = line[..separator]
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')))
+        .map_or(0, |index| index + 1);
+    let end = line[separator..]
+        .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | ')' | ']'))
+        .map_or(line.len(), |index| separator + index);
+    (start < separator && end > separator + 3).then_some(start..end)
+}
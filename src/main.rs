@@ -1,27 +1,66 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
-use host::WebSendable;
+use firefox_session_ui_iced::host::{self, WebSendable};
 use iced::widget::{
-    button, center, checkbox, column, container, horizontal_space, mouse_area, opaque, pane_grid,
-    pick_list, row, scrollable, stack, text, text_editor, text_input, tooltip, vertical_slider,
+    button, center, checkbox, column, container, horizontal_space, image, mouse_area, opaque,
+    pane_grid, pick_list, row, scrollable, stack, text, text_input, tooltip,
 };
 use iced::{time, Alignment, Color, Element, Length, Subscription, Task, Theme};
 
-mod host;
+#[cfg(not(target_family = "wasm"))]
+mod cli;
+mod commands;
+mod json_explorer;
+mod link_list;
+mod merge;
+mod mozlz4_tool;
+mod presets;
+mod recent;
+mod selection;
+mod status_log;
+#[cfg(test)]
+mod tests;
+mod timeline;
 mod wizard;
 
 pub fn main() -> iced::Result {
+    #[cfg(not(target_family = "wasm"))]
+    let args = match cli::Args::parse(std::env::args_os().skip(1)) {
+        Ok(args) if args.help => {
+            cli::attach_console();
+            print!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Ok(cli::Args {
+            mozlz4: Some(mozlz4),
+            ..
+        }) => {
+            cli::attach_console();
+            if let Err(e) = mozlz4.run() {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Ok(args) => args,
+        Err(e) => {
+            cli::attach_console();
+            eprintln!("error: {e}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // Also forwards to the browser console on the web, might need to change
+    // the browser inspector to actually see debug messages:
+    status_log::init_logger();
+
     #[cfg(target_family = "wasm")]
     {
-        // init debug tool for WebAssembly
-        wasm_logger::init(wasm_logger::Config::new(if cfg!(debug_assertions) {
-            // Might need to change the browser inspector to actually see these messages:
-            log::Level::Trace
-        } else {
-            log::Level::Info
-        }));
         console_error_panic_hook::set_once();
 
         // Show dialog if app panics (otherwise just logs to console and silently stops working):
@@ -45,7 +84,16 @@ pub fn main() -> iced::Result {
     )
     .theme(SessionDataUtility::theme)
     .subscription(SessionDataUtility::subscription)
-    .run_with(SessionDataUtility::start)
+    .run_with(move || {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            SessionDataUtility::start(args)
+        }
+        #[cfg(target_family = "wasm")]
+        {
+            SessionDataUtility::start()
+        }
+    })
 }
 
 /// From <https://github.com/iced-rs/iced/blob/a687a837653a576cb0599f7bc8ecd9c6054213a9/examples/modal/src/main.rs>
@@ -82,21 +130,87 @@ where
     }
 }
 
-/// Slider style that doesn't highlight one side of the slider's cursor.
-fn no_highlight_slider_style<Theme: iced::widget::slider::Catalog>(
-    theme: &Theme,
-    status: iced::widget::slider::Status,
-) -> iced::widget::slider::Style {
-    let theme_class = Theme::default();
-    let mut style = Theme::style(theme, &theme_class, status);
-    style.rail.backgrounds.0 = style.rail.backgrounds.1;
-    style
+/// A small square filled with a color, used to show container and tab group
+/// colors.
+fn color_swatch<'a, Message: 'a>([r, g, b]: [u8; 3]) -> Element<'a, Message> {
+    container(text(""))
+        .width(12)
+        .height(12)
+        .style(move |_theme| container::Style {
+            background: Some(Color::from_rgb8(r, g, b).into()),
+            ..container::Style::default()
+        })
+        .into()
+}
+
+/// An option in the pick list that filters tabs by Firefox container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ContainerChoice {
+    id: Option<u32>,
+    name: String,
+}
+impl std::fmt::Display for ContainerChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// A way to recover from a failed operation, offered next to the status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Recovery {
+    /// Load another session file from the same Firefox profile.
+    OpenFile(PathBuf),
+    ChooseAnotherFile,
+    FindFirefoxProfile,
+    OverwriteFile,
+    CreateFolder,
+}
+impl Recovery {
+    fn for_error(error: &host::Error) -> Vec<Self> {
+        let mut actions = Vec::new();
+        if error.is_bad_input_file() {
+            if let Some(path) = error.path() {
+                actions.extend(
+                    host::FirefoxProfileInfo::for_session_file(path)
+                        .map(|profile| profile.session_files())
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|other| other != path)
+                        .map(Recovery::OpenFile),
+                );
+            }
+            actions.push(Recovery::ChooseAnotherFile);
+            if cfg!(not(target_family = "wasm")) {
+                actions.push(Recovery::FindFirefoxProfile);
+            }
+        }
+        if matches!(error, host::Error::AlreadyExists { .. }) {
+            actions.push(Recovery::OverwriteFile);
+        }
+        if error.is_missing_folder() {
+            actions.push(Recovery::CreateFolder);
+        }
+        actions
+    }
+    fn label(&self) -> String {
+        match self {
+            Recovery::OpenFile(path) => format!(
+                "Try {}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            Recovery::ChooseAnotherFile => "Choose another file".to_owned(),
+            Recovery::FindFirefoxProfile => "Find Firefox profile".to_owned(),
+            Recovery::OverwriteFile => "Overwrite file".to_owned(),
+            Recovery::CreateFolder => "Create folder".to_owned(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum SidebarPane {
     Sidebar,
     MainContent,
+    Inspector,
 }
 
 #[derive(Debug, Clone)]
@@ -105,11 +219,24 @@ enum Message {
     SetSystemThemeMode(Theme),
     SetSplit(pane_grid::ResizeEvent),
     FirefoxProfileWizard(wizard::Message),
-    /// User interactions with the preview text editor.
-    Preview(text_editor::Action),
+    Mozlz4Tool(mozlz4_tool::Message),
+    Merge(merge::Message),
+    Timeline(timeline::Message),
+    /// Show the session history of the loaded file's profile, or of the
+    /// first profile that is found.
+    ShowTimeline,
+    Preview(link_list::Message),
+    /// Copy the lines that are selected in the preview.
+    CopyPreviewSelection,
     SetPreview(String),
+    /// Generate the preview if no newer generation was requested since.
+    GeneratePreview(u64),
+    /// Results from older generations are ignored.
+    PreviewGenerated(u64, Result<String, host::Error>),
     SetInputPath(String, Option<WebSendable<rfd::FileHandle>>),
     BrowseInputPath,
+    /// Load a file, for example from the recent files list.
+    OpenFile(PathBuf),
     LoadInputData,
     UpdateLoadedData(host::FileInfo),
     ParsedTabGroups(host::AllTabGroups),
@@ -118,24 +245,84 @@ enum Message {
         index: u32,
         select: bool,
     },
+    /// Change the selection of many tab groups at once.
+    SelectTabGroups(selection::BulkSelection),
+    SetWindowPattern(String),
+    FocusWindowPattern,
+    /// Used to detect shift-clicks.
+    ModifiersChanged(iced::keyboard::Modifiers),
+    /// Change how tabs are grouped in the sidebar and in the generated output.
+    SetGroupBy(host::GroupBy),
+    /// Only include tabs from the Firefox container with this id.
+    SetContainerFilter(Option<u32>),
+    /// Show native Firefox tab groups inside each window.
+    SetNativeTabGroups(bool),
     SetStatus(String),
+    /// An operation failed, the text describes what was attempted.
+    Failed(&'static str, host::Error),
+    Recover(Recovery),
     SetSavePath(String),
     BrowseSavePath,
     SetOverwrite(bool),
     SetCreateFolder(bool),
     SetOutputFormat(&'static str),
+    SetEmbedFavicons(bool),
+    SetJsonSelectedWindows(bool),
     CopyLinksToClipboard,
     SaveLinksToFile,
+    /// Remember the selected windows so that they can be saved together with
+    /// windows from other sessions.
+    AddToMerge,
+    SetBrowserCommand(String),
+    SetWindowPerGroup(bool),
+    /// Open the tabs in the selected groups in a browser.
+    OpenTabsInBrowser,
+    /// Answer to the question about opening many tabs.
+    ConfirmOpenTabs(bool),
+    /// Something was written using the `log` crate.
+    LogEntry(status_log::Entry),
+    ToggleStatusLog,
+    CopyStatusLog,
+    CopyToClipboard(String),
+    JsonExplorer(json_explorer::Message),
+    ToggleJsonExplorer,
+    /// Show the JSON explorer with the node at a JSON pointer selected.
+    ShowJson(String),
+    SaveStatusLog,
+    ClearStatusLog,
+    /// From a keyboard shortcut or the command palette.
+    RunCommand(commands::Command),
+    CommandPalette(commands::Message),
+    /// Select the windows and options from a saved preset, now or once a
+    /// session is loaded.
+    ApplyPreset(String),
+    SetPresetName(String),
+    /// Save the current selection as a preset named after `preset_name`.
+    SavePreset,
+    /// Delete the preset named after `preset_name`.
+    DeletePreset,
     Nothing,
 }
 
+/// Id of the text field used to select windows by name.
+const WINDOW_PATTERN_ID: &str = "window-pattern";
+
+/// Wait this long for more selection changes before generating the preview.
+const PREVIEW_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Ask before opening more tabs than this in the browser. On the web the
+/// question also warns about popup blockers, which stop all but one tab.
+const CONFIRM_OPEN_TABS_ABOVE: usize = if cfg!(target_family = "wasm") { 1 } else { 20 };
+
 #[derive(Debug)]
 struct SessionDataUtility {
     theme: Theme,
-    preview: text_editor::Content,
-    /// Guess of which line the `preview` is scrolled to (at the top of view).
-    /// Incorrect when there is word wrapping.
-    preview_scroll: u32,
+    preview: link_list::State,
+    /// Incremented for every requested preview.
+    preview_generation: u64,
+    preview_cache: host::PreviewCache,
+    /// How long to wait for more changes before generating the preview.
+    preview_debounce: Duration,
     input_path: String,
     input_data: Option<WebSendable<rfd::FileHandle>>,
     loaded_data: Option<host::FileInfo>,
@@ -143,25 +330,132 @@ struct SessionDataUtility {
     save_path: String,
     output_options: host::OutputOptions,
     tab_groups: host::AllTabGroups,
+    /// Decoded once since the sidebar is drawn often, by whether the group is
+    /// open and its index.
+    group_favicons: HashMap<(bool, u32), image::Handle>,
     status: String,
+    /// Ways to recover from the last failed operation.
+    recovery_actions: Vec<Recovery>,
+    /// Earlier status messages, `status` only shows the latest one.
+    status_log: status_log::StatusLog,
+    show_status_log: bool,
     split_divider: pane_grid::State<SidebarPane>,
     firefox_profile_wizard: wizard::State,
+    mozlz4_tool: mozlz4_tool::State,
+    merge: merge::State,
+    timeline: timeline::State,
+    recent_files: recent::RecentFiles,
+    open_in_browser: host::OpenInBrowserOptions,
+    /// URLs that will be opened if the user confirms it.
+    pending_open_urls: Option<Vec<Vec<String>>>,
+    /// The tab of the line that was last clicked in the preview.
+    inspected_tab: Option<host::TabDetails>,
+    inspected_favicon: Option<image::Handle>,
+    json_explorer: json_explorer::State,
+    /// Show the JSON explorer instead of the preview.
+    show_json_explorer: bool,
+    /// Selected once the JSON has been parsed.
+    pending_json_path: Option<String>,
+    command_palette: commands::State,
+    /// Used to select windows by name.
+    window_pattern: String,
+    /// The last tab group that was clicked, shift-clicks select all groups
+    /// between it and the clicked group.
+    selection_anchor: Option<(bool, u32)>,
+    keyboard_modifiers: iced::keyboard::Modifiers,
+    presets: presets::Presets,
+    /// Name used when saving or deleting a preset.
+    preset_name: String,
+    /// Applied once tabs are grouped as the preset wants.
+    pending_preset: Option<presets::Preset>,
 }
 impl SessionDataUtility {
+    /// `true` if the selected options make our own code write the links, see
+    /// [`host::FileInfo::uses_own_grouping`].
+    fn uses_own_grouping(&self) -> bool {
+        match &self.loaded_data {
+            Some(data) => data.uses_own_grouping(&self.selected_tab_groups),
+            None => self.selected_tab_groups.uses_own_grouping(),
+        }
+    }
+    /// Show a status message and add it to the status log.
+    fn set_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+        self.status_log.info(self.status.as_str());
+    }
+    /// Generate the preview after a short delay, so that clicking several
+    /// windows in a row only generates it once.
     fn regenerate_preview_task(&mut self) -> Task<Message> {
-        let Some(data) = self.loaded_data.clone() else {
+        if self.loaded_data.is_none() {
+            return Task::none();
+        }
+        self.set_status("Generating preview");
+        self.preview_generation += 1;
+        let generation = self.preview_generation;
+        Task::perform(host::sleep(self.preview_debounce), move |()| {
+            Message::GeneratePreview(generation)
+        })
+    }
+    /// Clear the selection and list tab groups again, needed after changing
+    /// options that affect how tabs are grouped.
+    fn regroup_tabs_task(&mut self) -> Task<Message> {
+        // Group indexes aren't comparable between groupings:
+        self.selected_tab_groups.open_group_indexes = None;
+        self.selected_tab_groups.closed_group_indexes = Some(Vec::new());
+        self.tab_groups = Default::default();
+        self.group_favicons.clear();
+
+        match self.loaded_data.clone() {
+            Some(data) if data.is_parsed() => self.update(Message::UpdateLoadedData(data)),
+            _ => Task::none(),
+        }
+    }
+    /// Parse the session's JSON for the JSON explorer if that hasn't been done
+    /// yet.
+    fn load_json_task(&mut self) -> Task<Message> {
+        if self.json_explorer.is_loaded() {
+            return Task::none();
+        }
+        let Some(data) = self.loaded_data.as_ref().and_then(|data| data.json_data()) else {
             return Task::none();
         };
-        let options = self.selected_tab_groups.clone();
-        self.status = "Generating preview".to_string();
         Task::perform(
-            async move { data.to_text_links(options).await },
-            |result| match result {
-                Ok(preview) => Message::SetPreview(preview),
-                Err(e) => Message::SetStatus(format!("Failed to generate preview: {e}")),
-            },
+            host::spawn_blocking(move || {
+                serde_json::from_slice(&data)
+                    .map(Arc::new)
+                    .map_err(|e| e.to_string())
+            }),
+            |result| Message::JsonExplorer(json_explorer::Message::Loaded(result)),
         )
     }
+    /// Select the groups from `pending_preset` if the tabs are grouped the way
+    /// it wants, otherwise regroup the tabs first.
+    fn apply_pending_preset(&mut self) -> Option<Task<Message>> {
+        let preset = self.pending_preset.as_ref()?;
+        if preset.changes_grouping(&self.selected_tab_groups) {
+            preset.apply_options(&mut self.selected_tab_groups, &mut self.output_options);
+            return Some(self.regroup_tabs_task());
+        }
+        let preset = self.pending_preset.take()?;
+        let missing = preset.apply_selection(&self.tab_groups, &mut self.selected_tab_groups);
+        if !missing.is_empty() {
+            log::warn!(
+                "Preset \"{}\" selects windows that aren't in this session: {}",
+                preset.name,
+                missing.join(", ")
+            );
+        }
+        None
+    }
+    /// Write the presets to disk without blocking the UI.
+    fn save_presets_task(&self) -> Task<Message> {
+        Task::perform(self.presets.save(), |result| {
+            if let Err(e) = result {
+                log::warn!("Failed to save presets: {e}");
+            }
+            Message::Nothing
+        })
+    }
     fn tab_group_view<'a>(
         &'a self,
         index: usize,
@@ -177,7 +471,21 @@ impl SessionDataUtility {
         .as_ref()
         .is_some_and(|indexes| indexes.contains(&index));
 
-        button(group.name.as_str())
+        let favicon = self.group_favicons.get(&(open_window, group.index));
+        let label = if group.color.is_some() || favicon.is_some() {
+            Element::from(
+                row![]
+                    .push_maybe(group.color.map(color_swatch))
+                    .push_maybe(favicon.map(|handle| image(handle).width(16).height(16)))
+                    .push(text(group.name.as_str()))
+                    .spacing(8)
+                    .align_y(Alignment::Center),
+            )
+        } else {
+            Element::from(text(group.name.as_str()))
+        };
+
+        let group_button = button(label)
             .width(Length::Fill)
             .style(if is_selected {
                 iced::widget::button::success
@@ -188,8 +496,69 @@ impl SessionDataUtility {
                 open: open_window,
                 index,
                 select: !is_selected,
-            })
-            .into()
+            });
+        let is_bookmarks = self
+            .loaded_data
+            .as_ref()
+            .is_some_and(|data| data.source() == host::DataSource::Bookmarks);
+        let group_button: Element<'_, Message> = match &group.json_path {
+            Some(path) => row![
+                group_button,
+                tooltip(
+                    button("{}")
+                        .style(iced::widget::button::text)
+                        .on_press(Message::ShowJson(path.clone())),
+                    container(text(if is_bookmarks {
+                        "Show the folder's JSON"
+                    } else {
+                        "Show the window's JSON"
+                    }))
+                    .padding(8)
+                    .style(iced::widget::container::bordered_box),
+                    tooltip::Position::Right
+                ),
+            ]
+            .align_y(Alignment::Center)
+            .into(),
+            None => group_button.into(),
+        };
+        // Bookmark folders are shown as a tree:
+        let group_button: Element<'_, Message> = if group.depth > 0 {
+            container(group_button)
+                .padding(iced::Padding::ZERO.left(group.depth as f32 * 20.0))
+                .into()
+        } else {
+            group_button
+        };
+        if group.sub_groups.is_empty() {
+            return group_button;
+        }
+
+        column![
+            group_button,
+            column(group.sub_groups.iter().map(|sub_group| {
+                row![
+                    color_swatch(sub_group.color.unwrap_or([0x7c, 0x7c, 0x7d])),
+                    text(format!(
+                        "{} ({} tabs){}",
+                        sub_group.name,
+                        sub_group.tab_count,
+                        if sub_group.collapsed {
+                            ", collapsed"
+                        } else {
+                            ""
+                        }
+                    ))
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .into()
+            }))
+            .spacing(4)
+            .padding(iced::Padding::ZERO.left(20))
+        ]
+        .spacing(6)
+        .into()
     }
 }
 impl SessionDataUtility {
@@ -206,10 +575,24 @@ impl SessionDataUtility {
                     )
                     .expect("Splitting panel should succeed");
                 panes.resize(split, 0.2);
+                let (main_pane, _) = panes
+                    .iter()
+                    .find(|(_, state)| **state == SidebarPane::MainContent)
+                    .expect("main content pane was just created");
+                let (_, split) = panes
+                    .split(
+                        pane_grid::Axis::Vertical,
+                        *main_pane,
+                        SidebarPane::Inspector,
+                    )
+                    .expect("Splitting panel should succeed");
+                panes.resize(split, 0.7);
                 panes
             },
-            preview: text_editor::Content::new(),
-            preview_scroll: 0,
+            preview: Default::default(),
+            preview_generation: 0,
+            preview_cache: Default::default(),
+            preview_debounce: PREVIEW_DEBOUNCE,
             input_path: "".to_string(),
             input_data: None,
             loaded_data: None,
@@ -235,26 +618,76 @@ impl SessionDataUtility {
                     host::TabGroup {
                         index: 0,
                         name: "Window 1".into(),
+                        color: None,
+                        sub_groups: Vec::new(),
+                        favicon: None,
+                        depth: 0,
+                        json_path: None,
                     },
                     host::TabGroup {
                         index: 1,
                         name: "Window 2".into(),
+                        color: None,
+                        sub_groups: Vec::new(),
+                        favicon: None,
+                        depth: 0,
+                        json_path: None,
                     },
                 ],
                 closed: vec![host::TabGroup {
                     index: 3,
                     name: "Closed window 1".into(),
+                    color: None,
+                    sub_groups: Vec::new(),
+                    favicon: None,
+                    depth: 0,
+                    json_path: None,
                 }],
             },
             #[cfg(not(debug_assertions))]
             tab_groups: Default::default(),
+            group_favicons: HashMap::new(),
             status: "".to_string(),
+            recovery_actions: Vec::new(),
+            status_log: Default::default(),
+            show_status_log: false,
             firefox_profile_wizard: wizard::State::new(),
+            mozlz4_tool: mozlz4_tool::State::new(),
+            merge: merge::State::new(),
+            timeline: timeline::State::new(),
+            recent_files: recent::RecentFiles::load(),
+            open_in_browser: Default::default(),
+            pending_open_urls: None,
+            inspected_tab: None,
+            inspected_favicon: None,
+            json_explorer: Default::default(),
+            show_json_explorer: false,
+            pending_json_path: None,
+            command_palette: Default::default(),
+            window_pattern: String::new(),
+            selection_anchor: None,
+            keyboard_modifiers: Default::default(),
+            presets: presets::Presets::load(),
+            preset_name: String::new(),
+            pending_preset: None,
         }
     }
+    #[cfg(target_family = "wasm")]
     fn start() -> (Self, Task<Message>) {
         (Self::new(), Task::none())
     }
+    #[cfg(not(target_family = "wasm"))]
+    fn start(args: cli::Args) -> (Self, Task<Message>) {
+        let mut app = Self::new();
+        let mut tasks = Vec::new();
+        if let Some(name) = args.preset {
+            tasks.push(app.update(Message::ApplyPreset(name)));
+        }
+        if let Some(file) = args.file {
+            tasks.push(app.update(Message::OpenFile(file)));
+        }
+        (app, Task::batch(tasks))
+    }
 
     fn title(&self) -> String {
         "Firefox Session Data Utility".into()
@@ -279,34 +712,130 @@ impl SessionDataUtility {
                 self.firefox_profile_wizard.update(msg);
                 Task::none()
             }
-            Message::SetPreview(preview) => {
-                self.status = "Successfully loaded session data".to_string();
-                self.preview = text_editor::Content::with_text(&preview);
-                self.preview_scroll = 0;
+            Message::Mozlz4Tool(msg) => self.mozlz4_tool.update(msg).map(Message::Mozlz4Tool),
+            Message::Merge(merge::Message::Save) => {
+                self.merge.update(merge::Message::Save);
+                let sources = self.merge.sources().to_vec();
+                let deduplicate = self.merge.deduplicate();
+                let save_path = PathBuf::from(self.save_path.as_str());
+                let generate_options = self.selected_tab_groups.clone();
+                let output_options = self.output_options.clone();
+
+                self.recovery_actions.clear();
+                self.set_status("Saving merged links to file");
+                Task::perform(
+                    host::save_merged_links(
+                        sources,
+                        deduplicate,
+                        save_path,
+                        generate_options,
+                        output_options,
+                    ),
+                    |result| match result {
+                        Ok(()) => Message::SetStatus(
+                            "Successfully saved merged links to a file".to_string(),
+                        ),
+                        Err(e) => Message::Failed("Failed to save merged links to file", e),
+                    },
+                )
+            }
+            Message::Merge(msg) => {
+                self.merge.update(msg);
                 Task::none()
             }
-            Message::Preview(action) => {
-                if let text_editor::Action::Scroll { lines } = &action {
-                    self.preview_scroll = self
-                        .preview_scroll
-                        .saturating_add_signed(*lines)
-                        .min(self.preview.line_count() as u32);
-                }
-                if cfg!(debug_assertions) || !action.is_edit() {
-                    let (prev_line, prev_col) = self.preview.cursor_position();
-
-                    // Update the state of the multi-line text editor:
-                    self.preview.perform(action);
-
-                    let (new_line, new_col) = self.preview.cursor_position();
-                    if prev_line != new_line || prev_col != new_col {
-                        // Also need to update tracked scroll when moving cursor outside
-                        // of the visible area.
-                        self.preview_scroll = new_line as u32;
+            Message::Timeline(timeline::Message::Open(path)) => {
+                let hide = self
+                    .timeline
+                    .update(timeline::Message::Open(path.clone()))
+                    .map(Message::Timeline);
+                self.input_path = path.to_string_lossy().into_owned();
+                Task::batch([hide, self.update(Message::LoadInputData)])
+            }
+            Message::Timeline(msg) => self.timeline.update(msg).map(Message::Timeline),
+            Message::ShowTimeline => {
+                let profile = self
+                    .loaded_data
+                    .as_ref()
+                    .and_then(|data| host::FirefoxProfileInfo::for_session_file(&data.file_path))
+                    .or_else(|| host::FirefoxProfileInfo::all_profiles().into_iter().next());
+                match profile {
+                    Some(profile) => {
+                        self.update(Message::Timeline(timeline::Message::Load(profile)))
+                    }
+                    None => {
+                        self.set_status("Couldn't find a Firefox profile");
+                        Task::none()
                     }
                 }
+            }
+            Message::GeneratePreview(generation) => {
+                let Some(data) = self.loaded_data.clone() else {
+                    return Task::none();
+                };
+                if generation != self.preview_generation {
+                    return Task::none();
+                }
+                let options = self.selected_tab_groups.clone();
+                let cache = self.preview_cache.clone();
+                Task::perform(
+                    async move { data.to_text_preview(options, cache).await },
+                    move |result| Message::PreviewGenerated(generation, result),
+                )
+            }
+            Message::PreviewGenerated(generation, result) => {
+                if generation != self.preview_generation {
+                    return Task::none();
+                }
+                match result {
+                    Ok(preview) => self.update(Message::SetPreview(preview)),
+                    Err(e) => self.update(Message::Failed("Failed to generate preview", e)),
+                }
+            }
+            Message::SetPreview(preview) => {
+                self.recovery_actions.clear();
+                self.set_status("Successfully loaded session data");
+                self.preview.set_text(preview)
+            }
+            Message::Preview(link_list::Message::OpenLink(url)) => Task::perform(
+                host::open_urls_in_browser(vec![vec![url]], self.open_in_browser.clone()),
+                |result| match result {
+                    Ok(()) => Message::SetStatus("Opened link in browser".to_string()),
+                    Err(e) => Message::Failed("Failed to open link in browser", e),
+                },
+            ),
+            Message::Preview(message) => {
+                if let link_list::Message::ClickLine { line, .. } = message {
+                    let link_at = |line: usize| {
+                        let text = self.preview.line(line);
+                        link_list::find_link(text).map(|link| &text[link])
+                    };
+                    self.inspected_tab = link_at(line).and_then(|url| {
+                        // Tabs with the same URL are told apart by their order:
+                        let occurrence = (0..line).filter(|&ix| link_at(ix) == Some(url)).count();
+                        self.loaded_data.as_ref()?.inspect_tab(
+                            url,
+                            occurrence,
+                            &self.tab_groups,
+                            &self.selected_tab_groups,
+                        )
+                    });
+                    self.inspected_favicon = self
+                        .inspected_tab
+                        .as_ref()
+                        .and_then(|tab| host::DataUrl::parse(tab.favicon.as_deref()?))
+                        .filter(host::DataUrl::is_image)
+                        .map(|url| image::Handle::from_bytes(url.data));
+                }
+                self.preview.update(message);
                 Task::none()
             }
+            Message::CopyPreviewSelection => match self.preview.selected_text() {
+                Some(text) => Task::batch([
+                    iced::clipboard::write(text.clone()),
+                    iced::clipboard::write_primary(text),
+                ]),
+                None => Task::none(),
+            },
             Message::SetInputPath(p, data) => {
                 self.input_path = p;
                 self.input_data = data;
@@ -326,19 +855,37 @@ impl SessionDataUtility {
                 .map(|(name, handle)| Message::SetInputPath(name, Some(WebSendable(handle))))
                 .unwrap_or(Message::Nothing)
             }),
+            Message::OpenFile(path) => {
+                self.input_path = path.to_string_lossy().into_owned();
+                self.input_data = None;
+                self.update(Message::LoadInputData)
+            }
             Message::LoadInputData => {
                 let mut data = host::FileInfo::new(PathBuf::from(self.input_path.clone()));
                 data.file_handle = self.input_data.clone();
                 self.loaded_data = Some(data.clone());
+                // Ignore previews for the previous file:
+                self.preview_generation += 1;
+                self.preview_cache = Default::default();
+                self.inspected_tab = None;
+                self.inspected_favicon = None;
+                self.json_explorer = Default::default();
+                self.show_json_explorer = false;
+                self.pending_json_path = None;
                 self.selected_tab_groups.open_group_indexes = None;
                 self.selected_tab_groups.closed_group_indexes = Some(Vec::new());
-                self.status = "Reading input file".to_string();
+                // The new file might come from a profile with other containers
+                // and might not have any tab groups:
+                self.selected_tab_groups.container_filter = None;
+                self.selected_tab_groups.native_tab_groups = false;
+                self.set_status("Reading input file");
+                self.recovery_actions.clear();
                 // FIXME: handle cancellation
                 Task::perform(
                     async move { data.load_data().await.map(|_| data) },
                     |result| match result {
                         Ok(data) => Message::UpdateLoadedData(data),
-                        Err(e) => Message::SetStatus(format!("Failed to read file: {e}")),
+                        Err(e) => Message::Failed("Failed to read file", e),
                     },
                 )
             }
@@ -347,43 +894,73 @@ impl SessionDataUtility {
 
                 match &data.data {
                     Some(host::FileData::Compressed { .. }) => {
-                        self.status = "Decompressing data".to_string();
+                        self.set_status("Decompressing data");
                         Task::perform(
                             async move { data.decompress_data().await.map(|_| data) },
                             |result| match result {
                                 Ok(data) => Message::UpdateLoadedData(data),
-                                Err(e) => {
-                                    Message::SetStatus(format!("Failed to decompress data: {e}"))
-                                }
+                                Err(e) => Message::Failed("Failed to decompress data", e),
                             },
                         )
                     }
-                    Some(host::FileData::Uncompressed { .. }) => {
-                        self.status = "Parsing session data".to_string();
+                    Some(host::FileData::Uncompressed { .. }) if !data.is_parsed() => {
+                        self.set_status("Parsing session data");
                         Task::perform(
                             async move { data.parse_session_data().await.map(|_| data) },
                             |result| match result {
                                 Ok(data) => Message::UpdateLoadedData(data),
-                                Err(e) => {
-                                    Message::SetStatus(format!("Failed to parse session data: {e}"))
-                                }
+                                Err(e) => Message::Failed("Failed to parse session data", e),
+                            },
+                        )
+                    }
+                    // Data that only our own code can read stays uncompressed
+                    // after parsing:
+                    Some(host::FileData::Uncompressed { .. } | host::FileData::Parsed { .. }) => {
+                        let options = self.selected_tab_groups.clone();
+                        Task::perform(
+                            async move { data.get_groups_from_session(&options).await },
+                            |result| match result {
+                                Ok(all_groups) => Message::ParsedTabGroups(all_groups),
+                                Err(e) => Message::Failed("Failed to list windows in session", e),
                             },
                         )
                     }
-                    Some(host::FileData::Parsed { .. }) => Task::perform(
-                        async move { data.get_groups_from_session(true).await },
-                        |result| match result {
-                            Ok(all_groups) => Message::ParsedTabGroups(all_groups),
-                            Err(e) => Message::SetStatus(format!(
-                                "Failed to list windows in session: {e}"
-                            )),
-                        },
-                    ),
                     None => unreachable!("Always have data when updating file info"),
                 }
             }
             Message::ParsedTabGroups(groups) => {
+                self.group_favicons = [(true, &groups.open), (false, &groups.closed)]
+                    .into_iter()
+                    .flat_map(|(open, list)| {
+                        list.iter().filter_map(move |group| {
+                            let favicon = group.favicon.as_ref()?;
+                            Some((
+                                (open, group.index),
+                                image::Handle::from_bytes(favicon.to_vec()),
+                            ))
+                        })
+                    })
+                    .collect();
                 self.tab_groups = groups;
+                self.selection_anchor = None;
+                if let Some(task) = self.apply_pending_preset() {
+                    return task;
+                }
+                #[cfg(not(target_family = "wasm"))]
+                if let Some(data) = &self.loaded_data {
+                    let path = &*data.file_path;
+                    // Don't bump the time when only regrouping tabs:
+                    if self.recent_files.files().first().map(|f| &f.path) != Some(path) {
+                        self.recent_files.add(path.clone());
+                        let save = Task::perform(self.recent_files.save(), |result| {
+                            if let Err(e) = result {
+                                log::warn!("Failed to save recent files: {e}");
+                            }
+                            Message::Nothing
+                        });
+                        return Task::batch([save, self.regenerate_preview_task()]);
+                    }
+                }
                 self.regenerate_preview_task()
             }
             Message::ChangeTabGroupSelection {
@@ -391,6 +968,23 @@ impl SessionDataUtility {
                 open,
                 select,
             } => {
+                match self.selection_anchor {
+                    Some((anchor_open, from))
+                        if self.keyboard_modifiers.shift()
+                            && anchor_open == open
+                            && from != index =>
+                    {
+                        return self.update(Message::SelectTabGroups(
+                            selection::BulkSelection::Range {
+                                open,
+                                from,
+                                to: index,
+                                select,
+                            },
+                        ));
+                    }
+                    _ => self.selection_anchor = Some((open, index)),
+                }
                 let (mut indexes, mut other) = (
                     &mut self.selected_tab_groups.open_group_indexes,
                     &mut self.selected_tab_groups.closed_group_indexes,
@@ -426,10 +1020,190 @@ impl SessionDataUtility {
                     Task::none()
                 }
             }
+            Message::SelectTabGroups(bulk) => {
+                let Some((open, closed)) = bulk.apply(&self.tab_groups, &self.selected_tab_groups)
+                else {
+                    if let selection::BulkSelection::Matching(pattern) = bulk {
+                        self.set_status(format!("No windows match \"{pattern}\""));
+                    }
+                    return Task::none();
+                };
+                let options = &mut self.selected_tab_groups;
+                if (&options.open_group_indexes, &options.closed_group_indexes)
+                    == (&Some(open.clone()), &Some(closed.clone()))
+                {
+                    return Task::none();
+                }
+                options.open_group_indexes = Some(open);
+                options.closed_group_indexes = Some(closed);
+                self.regenerate_preview_task()
+            }
+            Message::SetWindowPattern(pattern) => {
+                self.window_pattern = pattern;
+                Task::none()
+            }
+            Message::FocusWindowPattern => iced::widget::text_input::focus(WINDOW_PATTERN_ID),
+            Message::ModifiersChanged(modifiers) => {
+                self.keyboard_modifiers = modifiers;
+                Task::none()
+            }
+            Message::SetGroupBy(group_by) => {
+                if self.selected_tab_groups.group_by == group_by {
+                    return Task::none();
+                }
+                self.selected_tab_groups.group_by = group_by;
+                self.regroup_tabs_task()
+            }
+            Message::SetContainerFilter(container_filter) => {
+                if self.selected_tab_groups.container_filter == container_filter {
+                    return Task::none();
+                }
+                self.selected_tab_groups.container_filter = container_filter;
+                self.regroup_tabs_task()
+            }
+            Message::SetNativeTabGroups(v) => {
+                self.selected_tab_groups.native_tab_groups = v;
+                self.regroup_tabs_task()
+            }
             Message::SetStatus(status) => {
-                self.status = status;
+                self.set_status(status);
+                self.recovery_actions.clear();
+                Task::none()
+            }
+            Message::Failed(context, error) => {
+                self.status = format!("{context}: {error}");
+                self.status_log.error(self.status.as_str());
+                self.recovery_actions = Recovery::for_error(&error);
+                Task::none()
+            }
+            Message::LogEntry(entry) => {
+                self.status_log.push(entry);
+                Task::none()
+            }
+            Message::ToggleStatusLog => {
+                self.show_status_log = !self.show_status_log;
+                Task::none()
+            }
+            Message::CopyStatusLog => Task::batch([
+                iced::clipboard::write(self.status_log.to_text()),
+                iced::clipboard::write_primary(self.status_log.to_text()),
+            ]),
+            Message::JsonExplorer(message @ json_explorer::Message::Loaded(_)) => {
+                let _ = self.json_explorer.update(message);
+                match self.pending_json_path.take() {
+                    Some(path) => self.json_explorer.reveal(&path).map(Message::JsonExplorer),
+                    None => Task::none(),
+                }
+            }
+            Message::JsonExplorer(message) => self
+                .json_explorer
+                .update(message)
+                .map(Message::JsonExplorer),
+            Message::ToggleJsonExplorer => {
+                self.show_json_explorer = !self.show_json_explorer;
+                if self.show_json_explorer {
+                    self.load_json_task()
+                } else {
+                    Task::none()
+                }
+            }
+            Message::ShowJson(path) => {
+                self.show_json_explorer = true;
+                if self.json_explorer.is_loaded() {
+                    self.json_explorer.reveal(&path).map(Message::JsonExplorer)
+                } else {
+                    self.pending_json_path = Some(path);
+                    self.load_json_task()
+                }
+            }
+            Message::CopyToClipboard(text) => Task::batch([
+                iced::clipboard::write(text.clone()),
+                iced::clipboard::write_primary(text),
+            ]),
+            Message::SaveStatusLog => Task::perform(
+                host::prompt_save_text("firefox-session-ui-log.txt", self.status_log.to_text()),
+                |result| match result {
+                    Ok(true) => Message::SetStatus("Saved log to file".to_string()),
+                    Ok(false) => Message::Nothing,
+                    Err(e) => Message::Failed("Failed to save log to file", e),
+                },
+            ),
+            Message::ClearStatusLog => {
+                self.status_log.clear();
+                Task::none()
+            }
+            Message::ApplyPreset(name) => {
+                let Some(preset) = self.presets.get(&name).cloned() else {
+                    return self.update(Message::SetStatus(format!(
+                        "There is no preset named \"{name}\""
+                    )));
+                };
+                self.preset_name = name;
+                preset.apply_options(&mut self.selected_tab_groups, &mut self.output_options);
+                self.pending_preset = Some(preset);
+                let has_parsed_data = self
+                    .loaded_data
+                    .as_ref()
+                    .is_some_and(host::FileInfo::is_parsed);
+                if has_parsed_data {
+                    // Always regroup since the preset's options were already applied:
+                    self.regroup_tabs_task()
+                } else {
+                    Task::none()
+                }
+            }
+            Message::SetPresetName(name) => {
+                self.preset_name = name;
                 Task::none()
             }
+            Message::SavePreset => {
+                let name = self.preset_name.trim().to_owned();
+                if name.is_empty() {
+                    return self
+                        .update(Message::SetStatus("Enter a name for the preset".to_owned()));
+                }
+                self.presets.add(presets::Preset::new(
+                    name.clone(),
+                    &self.tab_groups,
+                    &self.selected_tab_groups,
+                    &self.output_options,
+                ));
+                Task::batch([
+                    self.save_presets_task(),
+                    self.update(Message::SetStatus(format!("Saved preset \"{name}\""))),
+                ])
+            }
+            Message::DeletePreset => {
+                self.presets.remove(self.preset_name.trim());
+                self.save_presets_task()
+            }
+            Message::RunCommand(command) => {
+                let _ = self.command_palette.update(commands::Message::Hide);
+                self.update(command.message())
+            }
+            Message::CommandPalette(msg) => {
+                let (task, command) = self.command_palette.update(msg);
+                let task = task.map(Message::CommandPalette);
+                match command {
+                    Some(command) => Task::batch([task, self.update(Message::RunCommand(command))]),
+                    None => task,
+                }
+            }
+            Message::Recover(recovery) => match recovery {
+                Recovery::OpenFile(path) => self.update(Message::OpenFile(path)),
+                Recovery::ChooseAnotherFile => self.update(Message::BrowseInputPath),
+                Recovery::FindFirefoxProfile => {
+                    self.update(Message::FirefoxProfileWizard(wizard::Message::Show))
+                }
+                Recovery::OverwriteFile => {
+                    self.output_options.overwrite = true;
+                    self.update(Message::SaveLinksToFile)
+                }
+                Recovery::CreateFolder => {
+                    self.output_options.create_folder = true;
+                    self.update(Message::SaveLinksToFile)
+                }
+            },
             Message::SetSavePath(v) => {
                 self.save_path = v;
                 Task::none()
@@ -456,16 +1230,21 @@ impl SessionDataUtility {
                 self.output_options.create_folder = v;
                 Task::none()
             }
+            Message::SetEmbedFavicons(v) => {
+                self.output_options.embed_favicons = v;
+                Task::none()
+            }
+            Message::SetJsonSelectedWindows(v) => {
+                self.output_options.json_selected_windows = v;
+                Task::none()
+            }
             Message::SetOutputFormat(v) => {
-                self.output_options.format = *host::FormatInfo::all()
-                    .iter()
-                    .find(|f| f.as_str() == v)
-                    .expect("Invalid output format");
+                assert!(self.output_options.set_format(v), "Invalid output format");
                 Task::none()
             }
             Message::CopyLinksToClipboard => Task::batch([
-                iced::clipboard::write(self.preview.text()),
-                iced::clipboard::write_primary(self.preview.text()),
+                iced::clipboard::write(self.preview.text().to_owned()),
+                iced::clipboard::write_primary(self.preview.text().to_owned()),
             ]),
             Message::SaveLinksToFile => {
                 let Some(data) = self.loaded_data.clone() else {
@@ -475,14 +1254,81 @@ impl SessionDataUtility {
                 let selected = self.selected_tab_groups.clone();
                 let output_options = self.output_options.clone();
 
-                self.status = "Saving links to file".to_string();
+                self.recovery_actions.clear();
+                if output_options.json.is_some() {
+                    self.set_status("Saving JSON to file");
+                    return Task::perform(
+                        async move { data.save_json(save_path, selected, output_options).await },
+                        |result| match result {
+                            Ok(()) => {
+                                Message::SetStatus("Successfully saved JSON to a file".to_string())
+                            }
+                            Err(e) => Message::Failed("Failed to save JSON to file", e),
+                        },
+                    );
+                }
+                self.set_status("Saving links to file");
                 Task::perform(
                     async move { data.save_links(save_path, selected, output_options).await },
                     |result| match result {
                         Ok(()) => {
                             Message::SetStatus("Successfully saved links to a file".to_string())
                         }
-                        Err(e) => Message::SetStatus(format!("Failed to save links to file: {e}")),
+                        Err(e) => Message::Failed("Failed to save links to file", e),
+                    },
+                )
+            }
+            Message::AddToMerge => {
+                let Some(data) = &self.loaded_data else {
+                    return Task::none();
+                };
+                match data.merge_source(&self.tab_groups, &self.selected_tab_groups) {
+                    Ok(source) => {
+                        self.merge.update(merge::Message::Add(source));
+                        Task::none()
+                    }
+                    Err(e) => self.update(Message::Failed("Failed to add windows to merge", e)),
+                }
+            }
+            Message::SetBrowserCommand(v) => {
+                self.open_in_browser.browser_command = v;
+                Task::none()
+            }
+            Message::SetWindowPerGroup(v) => {
+                self.open_in_browser.window_per_group = v;
+                Task::none()
+            }
+            Message::OpenTabsInBrowser => {
+                let Some(data) = &self.loaded_data else {
+                    return Task::none();
+                };
+                match data.selected_urls(&self.tab_groups, &self.selected_tab_groups) {
+                    Ok(urls)
+                        if urls.iter().map(Vec::len).sum::<usize>() > CONFIRM_OPEN_TABS_ABOVE =>
+                    {
+                        self.pending_open_urls = Some(urls);
+                        Task::none()
+                    }
+                    Ok(urls) => {
+                        self.pending_open_urls = Some(urls);
+                        self.update(Message::ConfirmOpenTabs(true))
+                    }
+                    Err(e) => self.update(Message::Failed("Failed to find tabs to open", e)),
+                }
+            }
+            Message::ConfirmOpenTabs(confirmed) => {
+                let Some(urls) = self.pending_open_urls.take() else {
+                    return Task::none();
+                };
+                if !confirmed {
+                    return Task::none();
+                }
+                self.set_status("Opening tabs in browser");
+                Task::perform(
+                    host::open_urls_in_browser(urls, self.open_in_browser.clone()),
+                    |result| match result {
+                        Ok(()) => Message::SetStatus("Opened tabs in browser".to_string()),
+                        Err(e) => Message::Failed("Failed to open tabs in browser", e),
                     },
                 )
             }
@@ -491,28 +1337,103 @@ impl SessionDataUtility {
     }
 
     fn view_sidebar(&self) -> Element<'_, Message> {
+        let group_by = self.selected_tab_groups.group_by;
+        let containers = self
+            .loaded_data
+            .as_ref()
+            .map(|data| data.containers())
+            .unwrap_or_default();
+        let container_filter = (!containers.is_empty()).then(|| {
+            let choices = [ContainerChoice {
+                id: None,
+                name: "All".to_owned(),
+            }]
+            .into_iter()
+            .chain([ContainerChoice {
+                id: Some(0),
+                name: "No container".to_owned(),
+            }])
+            .chain(containers.iter().map(|c| ContainerChoice {
+                id: Some(c.id),
+                name: c.name.clone(),
+            }))
+            .collect::<Vec<_>>();
+            let selected = choices
+                .iter()
+                .find(|c| c.id == self.selected_tab_groups.container_filter)
+                .cloned();
+            Element::from(
+                row![
+                    text("Container: "),
+                    pick_list(choices, selected, |c| Message::SetContainerFilter(c.id))
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            )
+        });
+
         container(scrollable(
             column(
-                self.tab_groups
-                    .open
-                    .iter()
-                    .enumerate()
-                    .map(|(index, group)| self.tab_group_view(index, group, true))
-                    .chain(
-                        [
-                            Element::from(text("")),
-                            Element::from(text("Closed Windows:")),
-                        ]
-                        .into_iter()
-                        .filter(|_| !self.tab_groups.closed.is_empty()),
-                    )
-                    .chain(
-                        self.tab_groups
-                            .closed
-                            .iter()
-                            .enumerate()
-                            .map(|(index, group)| self.tab_group_view(index, group, false)),
-                    ),
+                [Element::from(
+                    row![
+                        text("Group by: "),
+                        pick_list(host::GroupBy::all(), Some(group_by), Message::SetGroupBy)
+                    ]
+                    .spacing(5)
+                    .align_y(Alignment::Center),
+                )]
+                .into_iter()
+                .chain(
+                    self.loaded_data
+                        .as_ref()
+                        .map(|data| data.source())
+                        .filter(|source| *source != host::DataSource::Session)
+                        .map(|source| text(format!("Source: {}", source.name())).into()),
+                )
+                .chain(container_filter)
+                .chain(
+                    self.loaded_data
+                        .as_ref()
+                        .filter(|data| data.has_native_tab_groups())
+                        .map(|_| {
+                            checkbox(
+                                "Show Firefox tab groups",
+                                self.selected_tab_groups.native_tab_groups,
+                            )
+                            .on_toggle(Message::SetNativeTabGroups)
+                            .into()
+                        }),
+                )
+                .chain(
+                    (!self.tab_groups.open.is_empty() || !self.tab_groups.closed.is_empty())
+                        .then(|| self.view_bulk_selection()),
+                )
+                .chain(
+                    self.tab_groups
+                        .open
+                        .iter()
+                        .enumerate()
+                        .map(|(index, group)| self.tab_group_view(index, group, true)),
+                )
+                .chain(
+                    [
+                        Element::from(text("")),
+                        Element::from(text(if group_by == host::GroupBy::Window {
+                            "Closed Windows:"
+                        } else {
+                            "From Closed Windows:"
+                        })),
+                    ]
+                    .into_iter()
+                    .filter(|_| !self.tab_groups.closed.is_empty()),
+                )
+                .chain(
+                    self.tab_groups
+                        .closed
+                        .iter()
+                        .enumerate()
+                        .map(|(index, group)| self.tab_group_view(index, group, false)),
+                ),
             )
             .spacing(10)
             .padding(16)
@@ -524,6 +1445,104 @@ impl SessionDataUtility {
         .into()
     }
 
+    /// Buttons above the list of windows that change which windows are
+    /// selected all at once, and a pattern for selecting windows by name.
+    fn view_bulk_selection(&self) -> Element<'_, Message> {
+        let bulk_button = |label, bulk| {
+            button(text(label).size(14))
+                .style(iced::widget::button::secondary)
+                .on_press(Message::SelectTabGroups(bulk))
+        };
+        column![
+            row![
+                bulk_button("All", selection::BulkSelection::All),
+                bulk_button("None", selection::BulkSelection::None),
+                bulk_button("Invert", selection::BulkSelection::Invert),
+                bulk_button("Closed", selection::BulkSelection::AllClosed),
+            ]
+            .spacing(5)
+            .wrap(),
+            text_input("Select windows matching (* and ?)", &self.window_pattern)
+                .id(WINDOW_PATTERN_ID)
+                .on_input(Message::SetWindowPattern)
+                .on_submit(Message::SelectTabGroups(
+                    selection::BulkSelection::Matching(self.window_pattern.clone())
+                )),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    /// Shown instead of the preview before any session has been loaded.
+    fn view_start_screen(&self) -> Element<'_, Message> {
+        let recent = self.recent_files.files().iter().map(|file| {
+            button(text(file.to_string()))
+                .width(Length::Fill)
+                .style(iced::widget::button::secondary)
+                .on_press(Message::OpenFile(file.path.clone()))
+                .into()
+        });
+        center(
+            column![
+                text("No session loaded").size(24),
+                text("Open a Firefox sessionstore file to see its tabs."),
+                row![button("Browse").on_press(Message::BrowseInputPath)]
+                    .push_maybe(cfg!(not(target_family = "wasm")).then(|| {
+                        button("Find Firefox profile")
+                            .on_press(Message::FirefoxProfileWizard(wizard::Message::Show))
+                    }))
+                    .push_maybe(cfg!(not(target_family = "wasm")).then(|| {
+                        button("Compress or decompress mozlz4 files")
+                            .style(iced::widget::button::secondary)
+                            .on_press(Message::Mozlz4Tool(mozlz4_tool::Message::Show))
+                    }))
+                    .spacing(10),
+            ]
+            .push_maybe((!self.recent_files.files().is_empty()).then(|| {
+                column([text("Recent files:").into()].into_iter().chain(recent)).spacing(5)
+            }))
+            .spacing(15)
+            .max_width(700)
+            .align_x(Alignment::Start),
+        )
+        .into()
+    }
+
+    fn view_preview(&self) -> Element<'_, Message> {
+        column([
+            row![
+                text("Tabs as links: "),
+                horizontal_space(),
+                text(format!(
+                    "Line {}/{}",
+                    (self.preview.first_visible_line() + 1).min(self.preview.line_count()),
+                    self.preview.line_count()
+                )),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .into(),
+            self.preview
+                .view(self.keyboard_modifiers.shift())
+                .map(Message::Preview),
+            self.preview.selection().map_or_else(
+                || text("Click a line to select it, shift-click to select more lines").into(),
+                |selection| -> Element<'_, Message> {
+                    row![
+                        text(format!("{} lines selected", selection.len())),
+                        button("Copy selected lines").on_press(Message::CopyPreviewSelection),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                    .into()
+                },
+            ),
+        ])
+        .spacing(5)
+        .height(Length::Fill)
+        .into()
+    }
+
     fn view_main_content(&self) -> Element<'_, Message> {
         column![
             row![
@@ -534,7 +1553,20 @@ impl SessionDataUtility {
             .push_maybe(cfg!(not(target_family = "wasm")).then(|| {
                 button("Wizard").on_press(Message::FirefoxProfileWizard(wizard::Message::Show))
             }))
+            .push_maybe(
+                cfg!(not(target_family = "wasm"))
+                    .then(|| button("History").on_press(Message::ShowTimeline)),
+            )
             .push(button("Browse").on_press(Message::BrowseInputPath))
+            .push_maybe((!self.recent_files.files().is_empty()).then(|| {
+                pick_list(
+                    self.recent_files.files(),
+                    None::<recent::RecentFile>,
+                    |file| Message::OpenFile(file.path),
+                )
+                .placeholder("Recent")
+                .width(100)
+            }))
             .spacing(5)
             .align_y(Alignment::Center),
             row![
@@ -549,45 +1581,24 @@ impl SessionDataUtility {
                 .on_input(|_| Message::Nothing),
                 button("Load new data").on_press(Message::LoadInputData)
             ]
+            .push_maybe(self.loaded_data.is_some().then(|| {
+                button(if self.show_json_explorer {
+                    "Show links"
+                } else {
+                    "Show JSON"
+                })
+                .style(iced::widget::button::secondary)
+                .on_press(Message::ToggleJsonExplorer)
+            }))
             .spacing(5)
             .align_y(Alignment::Center),
-            column([
-                text("Tabs as links: ").into(),
-                #[cfg(debug_assertions)]
-                {
-                    text(format!(
-                        "Scroll: {}/{}",
-                        self.preview_scroll,
-                        self.preview.line_count()
-                    ))
-                    .into()
-                },
-                row![
-                    text_editor(&self.preview)
-                        .on_action(Message::Preview)
-                        .height(Length::Fill),
-                    {
-                        let max = self.preview.line_count() as u32;
-                        vertical_slider(
-                            0..=max,
-                            max.saturating_sub(self.preview_scroll),
-                            move |new| {
-                                let new = max.saturating_sub(new);
-                                Message::Preview(text_editor::Action::Scroll {
-                                    lines: i32::try_from(
-                                        i64::from(new) - i64::from(self.preview_scroll),
-                                    )
-                                    .expect("too large scroll distance when using slider"),
-                                })
-                            },
-                        )
-                    }
-                    .style(no_highlight_slider_style)
-                ]
-                .width(Length::Fill)
-                .into(),
-            ])
-            .height(Length::Fill),
+            if self.loaded_data.is_none() {
+                self.view_start_screen()
+            } else if self.show_json_explorer {
+                self.json_explorer.view().map(Message::JsonExplorer)
+            } else {
+                self.view_preview()
+            },
         ]
         .push_maybe(
             cfg!(not(target_family = "wasm")).then_some(
@@ -617,35 +1628,124 @@ impl SessionDataUtility {
                 .spacing(10),
             ),
         )
+        .push(
+            row![
+                text("Preset: "),
+                text_input("Name", &self.preset_name).on_input(Message::SetPresetName),
+                button("Save preset").on_press(Message::SavePreset),
+            ]
+            .push_maybe(
+                self.presets
+                    .get(self.preset_name.trim())
+                    .is_some()
+                    .then(|| {
+                        button("Delete")
+                            .style(iced::widget::button::secondary)
+                            .on_press(Message::DeletePreset)
+                    }),
+            )
+            .push_maybe((!self.presets.names().is_empty()).then(|| {
+                pick_list(self.presets.names(), None::<String>, Message::ApplyPreset)
+                    .placeholder("Apply preset")
+                    .width(150)
+            }))
+            .spacing(5)
+            .align_y(Alignment::Center),
+        )
         .push(
             row![
                 button("Copy links to clipboard").on_press(Message::CopyLinksToClipboard),
                 horizontal_space(),
-                tooltip(
-                    pick_list(
-                        host::FormatInfo::all()
-                            .iter()
-                            .map(|v| v.as_str())
-                            .collect::<Vec<_>>(),
-                        Some(self.output_options.format.as_str()),
-                        Message::SetOutputFormat
-                    ),
-                    container(text(self.output_options.format.to_string()))
-                        .padding(8)
-                        .style(iced::widget::container::bordered_box),
-                    tooltip::Position::Top
-                ),
-                button("Save links to file").on_press(Message::SaveLinksToFile),
             ]
-            .spacing(5),
+            .push_maybe(self.output_options.can_embed_favicons().then(|| {
+                checkbox("Embed favicons", self.output_options.embed_favicons)
+                    .on_toggle(Message::SetEmbedFavicons)
+            }))
+            .push_maybe(self.output_options.json.is_some().then(|| {
+                checkbox(
+                    "Only selected windows",
+                    self.output_options.json_selected_windows,
+                )
+                .on_toggle_maybe(
+                    (self.tab_groups.can_select_json_windows()
+                        && self
+                            .loaded_data
+                            .as_ref()
+                            .is_none_or(|data| data.source() == host::DataSource::Session))
+                    .then_some(Message::SetJsonSelectedWindows),
+                )
+            }))
+            .push(tooltip(
+                pick_list(
+                    host::OutputOptions::format_names(self.uses_own_grouping()),
+                    Some(self.output_options.format_name()),
+                    Message::SetOutputFormat,
+                ),
+                container(text(self.output_options.format_description()))
+                    .padding(8)
+                    .style(iced::widget::container::bordered_box),
+                tooltip::Position::Top,
+            ))
+            .push(button("Save links to file").on_press(Message::SaveLinksToFile))
+            .push(
+                button("Add to merge")
+                    .style(iced::widget::button::secondary)
+                    .on_press(Message::AddToMerge),
+            )
+            .spacing(5)
+            .align_y(Alignment::Center),
+        )
+        .push(
+            row![]
+                .push_maybe(cfg!(not(target_family = "wasm")).then(|| {
+                    text_input(
+                        "Browser command (default: firefox)",
+                        &self.open_in_browser.browser_command,
+                    )
+                    .on_input(Message::SetBrowserCommand)
+                }))
+                .push(
+                    checkbox(
+                        "New window per group",
+                        self.open_in_browser.window_per_group,
+                    )
+                    .on_toggle_maybe(
+                        cfg!(not(target_family = "wasm")).then_some(Message::SetWindowPerGroup),
+                    ),
+                )
+                .push(button("Open tabs in browser").on_press(Message::OpenTabsInBrowser))
+                .spacing(10)
+                .align_y(Alignment::Center),
         )
         .push(
             row![
                 text("Status: "),
                 text_input("", self.status.as_str()).on_input(|_| Message::Nothing)
             ]
+            .extend(self.recovery_actions.iter().map(|recovery| {
+                button(text(recovery.label()))
+                    .style(iced::widget::button::secondary)
+                    .on_press(Message::Recover(recovery.clone()))
+                    .into()
+            }))
+            .push(
+                button(text(
+                    match self
+                        .status_log
+                        .count_at_least(status_log::Severity::Warning)
+                    {
+                        _ if self.show_status_log => "Hide log".to_string(),
+                        0 => "Show log".to_string(),
+                        problems => format!("Show log ({problems} problems)"),
+                    },
+                ))
+                .style(iced::widget::button::secondary)
+                .on_press(Message::ToggleStatusLog),
+            )
+            .spacing(5)
             .align_y(Alignment::Center),
         )
+        .push_maybe(self.show_status_log.then(|| self.view_status_log()))
         .spacing(20)
         .padding(10)
         .align_x(Alignment::Start)
@@ -653,21 +1753,204 @@ impl SessionDataUtility {
         .into()
     }
 
+    /// Everything Firefox stored about the tab that was last clicked in the
+    /// preview.
+    fn view_inspector(&self) -> Element<'_, Message> {
+        let Some(tab) = &self.inspected_tab else {
+            return container(text("Click a tab in the preview to inspect it"))
+                .padding(10)
+                .into();
+        };
+        let yes_no = |value: bool| if value { "Yes" } else { "No" }.to_owned();
+        let fields = [
+            ("Title", Some(tab.title.clone())),
+            ("URL", Some(tab.url.clone())),
+            ("Window", Some(tab.window.clone())),
+            ("Favicon", tab.favicon.clone()),
+            (
+                "Last accessed",
+                tab.last_accessed.map(|ms| {
+                    status_log::format_utc(std::time::UNIX_EPOCH + Duration::from_millis(ms))
+                        + " UTC"
+                }),
+            ),
+            ("Pinned", Some(yes_no(tab.pinned))),
+            ("Hidden", Some(yes_no(tab.hidden))),
+            ("Muted", Some(yes_no(tab.muted))),
+            ("Container", Some(tab.container.clone())),
+            ("Parent tab", tab.parent.clone()),
+            ("Scroll position", tab.scroll.clone()),
+            ("Form data", Some(yes_no(tab.has_form_data))),
+            ("History length", Some(tab.history_length.to_string())),
+        ];
+        let rows = fields.into_iter().map(|(label, value)| {
+            let Some(value) = value else {
+                return row![text(label).width(120), text("-")].spacing(10).into();
+            };
+            // Favicons are often huge data URLs:
+            let shown = if value.chars().count() > 200 {
+                value.chars().take(200).collect::<String>() + "…"
+            } else {
+                value.clone()
+            };
+            row![
+                text(label).width(120),
+                text(shown).width(Length::Fill),
+                button("Copy")
+                    .style(iced::widget::button::secondary)
+                    .on_press(Message::CopyToClipboard(value)),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .into()
+        });
+        scrollable(
+            column(
+                [row![]
+                    .push_maybe(
+                        self.inspected_favicon
+                            .as_ref()
+                            .map(|handle| image(handle).width(24).height(24)),
+                    )
+                    .push(text("Tab details").size(20))
+                    .push(horizontal_space())
+                    .push(
+                        button("Show JSON")
+                            .style(iced::widget::button::secondary)
+                            .on_press_maybe(
+                                self.loaded_data
+                                    .as_ref()
+                                    .is_some_and(|data| data.source().is_json())
+                                    .then(|| Message::ShowJson(tab.json_path.clone())),
+                            ),
+                    )
+                    .spacing(8)
+                    .align_y(Alignment::Center)
+                    .into()]
+                .into_iter()
+                .chain(rows),
+            )
+            .spacing(8)
+            .padding(10),
+        )
+        .into()
+    }
+
+    fn view_status_log(&self) -> Element<'_, Message> {
+        let entries = column(self.status_log.entries().rev().map(|entry| {
+            let line = text(entry.to_string()).size(14);
+            match entry.severity {
+                status_log::Severity::Info => line,
+                status_log::Severity::Warning => line.color(Color::from_rgb8(0xd0, 0x80, 0x00)),
+                status_log::Severity::Error => line.style(iced::widget::text::danger),
+            }
+            .into()
+        }))
+        .push_maybe(self.status_log.is_empty().then(|| text("No messages yet")))
+        .spacing(2);
+
+        column![
+            row![
+                text(format!(
+                    "Log ({} entries, newest first)",
+                    self.status_log.len()
+                )),
+                horizontal_space(),
+                button("Copy log").on_press(Message::CopyStatusLog),
+                button("Save log").on_press(Message::SaveStatusLog),
+                button("Clear")
+                    .style(iced::widget::button::secondary)
+                    .on_press(Message::ClearStatusLog),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            container(scrollable(entries).width(Length::Fill))
+                .padding(5)
+                .max_height(200)
+                .style(iced::widget::container::bordered_box),
+        ]
+        .spacing(5)
+        .into()
+    }
+
     fn view(&self) -> Element<'_, Message> {
         let content = pane_grid(&self.split_divider, |_pane, state, _is_maximized| {
             pane_grid::Content::new(match state {
                 SidebarPane::Sidebar => self.view_sidebar(),
                 SidebarPane::MainContent => self.view_main_content(),
+                SidebarPane::Inspector => self.view_inspector(),
             })
         })
         .on_resize(10, Message::SetSplit);
 
-        modal(
+        let content = modal(
             content,
             self.firefox_profile_wizard
                 .view()
                 .map(|ele| ele.map(Message::FirefoxProfileWizard)),
             Message::FirefoxProfileWizard(wizard::Message::Hide),
+        );
+
+        let content = modal(
+            content,
+            self.mozlz4_tool
+                .view()
+                .map(|ele| ele.map(Message::Mozlz4Tool)),
+            Message::Mozlz4Tool(mozlz4_tool::Message::Hide),
+        );
+
+        let content = modal(
+            content,
+            self.merge.view().map(|ele| ele.map(Message::Merge)),
+            Message::Merge(merge::Message::Hide),
+        );
+
+        let content = modal(
+            content,
+            self.timeline.view().map(|ele| ele.map(Message::Timeline)),
+            Message::Timeline(timeline::Message::Hide),
+        );
+
+        let content = modal(
+            content,
+            self.command_palette.view(),
+            Message::CommandPalette(commands::Message::Hide),
+        );
+
+        modal(
+            content,
+            self.pending_open_urls.as_ref().map(|urls| {
+                container(
+                    column![text(format!(
+                        "Open {} tabs in {} browser windows?",
+                        urls.iter().map(Vec::len).sum::<usize>(),
+                        if self.open_in_browser.window_per_group {
+                            urls.len()
+                        } else {
+                            1
+                        }
+                    )),]
+                    .push_maybe(cfg!(target_family = "wasm").then(|| {
+                        text(
+                            "Browsers block all but the first tab unless popups are allowed \
+                             for this page.",
+                        )
+                    }))
+                    .push(
+                        row![
+                            button("Open").on_press(Message::ConfirmOpenTabs(true)),
+                            button("Cancel")
+                                .style(iced::widget::button::secondary)
+                                .on_press(Message::ConfirmOpenTabs(false)),
+                        ]
+                        .spacing(10),
+                    )
+                    .spacing(20),
+                )
+                .padding(20)
+                .style(iced::widget::container::bordered_box)
+            }),
+            Message::ConfirmOpenTabs(false),
         )
     }
 
@@ -678,8 +1961,12 @@ impl SessionDataUtility {
     fn subscription(&self) -> Subscription<Message> {
         // "time" requires one of the following iced features to be enabled:
         // tokio, async-std, or smol
-        time::every(time::Duration::from_secs(10))
-            .map(|_| Message::SetSystemThemeMode(system_theme_mode()))
+        Subscription::batch([
+            time::every(Duration::from_secs(10))
+                .map(|_| Message::SetSystemThemeMode(system_theme_mode())),
+            status_log::subscription().map(Message::LogEntry),
+            commands::subscription(),
+        ])
     }
 }
 
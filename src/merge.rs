@@ -0,0 +1,155 @@
+//! A modal window that collects the selected windows of several sessions so
+//! that they can be saved as one document.
+
+use iced::widget::{button, checkbox, column, container, row, scrollable, text, text_input};
+use iced::{Alignment, Element, Length};
+
+use crate::host::MergeSource;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Hide,
+    Show,
+    /// Add the selected windows of a loaded session.
+    Add(MergeSource),
+    Remove(usize),
+    /// Move a source before the one above it.
+    MoveUp(usize),
+    /// Change the heading of a source.
+    SetName(usize, String),
+    SetDeduplicate(bool),
+    /// Handled by the main window since it knows where to save the links.
+    Save,
+}
+
+#[derive(Debug, Clone)]
+pub struct State {
+    active: bool,
+    sources: Vec<MergeSource>,
+    /// Leave out tabs whose URL is in an earlier source or group.
+    deduplicate: bool,
+}
+impl State {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            sources: Vec::new(),
+            deduplicate: true,
+        }
+    }
+
+    pub fn sources(&self) -> &[MergeSource] {
+        &self.sources
+    }
+    pub fn deduplicate(&self) -> bool {
+        self.deduplicate
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Hide => self.active = false,
+            Message::Show => self.active = true,
+            Message::Add(mut source) => {
+                // Adding windows from the same file again replaces them but
+                // keeps the heading:
+                match self
+                    .sources
+                    .iter_mut()
+                    .find(|existing| existing.file_path == source.file_path)
+                {
+                    Some(existing) => {
+                        source.name = std::mem::take(&mut existing.name);
+                        *existing = source;
+                    }
+                    None => self.sources.push(source),
+                }
+                self.active = true;
+            }
+            Message::Remove(ix) => {
+                if ix < self.sources.len() {
+                    self.sources.remove(ix);
+                }
+            }
+            Message::MoveUp(ix) => {
+                if (1..self.sources.len()).contains(&ix) {
+                    self.sources.swap(ix - 1, ix);
+                }
+            }
+            Message::SetName(ix, name) => {
+                if let Some(source) = self.sources.get_mut(ix) {
+                    source.name = name;
+                }
+            }
+            Message::SetDeduplicate(v) => self.deduplicate = v,
+            Message::Save => self.active = false,
+        }
+    }
+
+    pub fn view(&self) -> Option<Element<'_, Message>> {
+        if !self.active {
+            return None;
+        }
+        let sources = self.sources.iter().enumerate().map(|(ix, source)| {
+            row![
+                text_input("Heading", &source.name)
+                    .on_input(move |name| Message::SetName(ix, name)),
+                text(format!(
+                    "{} windows, {} tabs",
+                    source.group_count(),
+                    source.tab_count()
+                )),
+                button("Up").on_press_maybe((ix > 0).then_some(Message::MoveUp(ix))),
+                button("Remove")
+                    .style(iced::widget::button::danger)
+                    .on_press(Message::Remove(ix)),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center)
+            .into()
+        });
+
+        let content = container(
+            column![
+                text("Merge sessions into one export").size(24),
+                text(
+                    "Load a session, select its windows and add them here, then do the same \
+                     for the other sessions. Each session gets a heading of its own."
+                ),
+            ]
+            .push(if self.sources.is_empty() {
+                Element::from(text("No windows have been added yet."))
+            } else {
+                scrollable(column(sources).spacing(5).padding([0, 15]))
+                    .height(Length::Shrink)
+                    .into()
+            })
+            .push(
+                checkbox(
+                    "Skip tabs that are already in an earlier session or window",
+                    self.deduplicate,
+                )
+                .on_toggle(Message::SetDeduplicate),
+            )
+            .push(text(
+                "Links are saved to the path and in the format chosen in the main window.",
+            ))
+            .push(
+                row![
+                    button("Save merged links")
+                        .on_press_maybe((!self.sources.is_empty()).then_some(Message::Save)),
+                    button("Close")
+                        .style(iced::widget::button::secondary)
+                        .on_press(Message::Hide),
+                ]
+                .spacing(10),
+            )
+            .spacing(15),
+        )
+        .width(700)
+        .max_height(600)
+        .padding(10)
+        .style(iced::widget::container::bordered_box);
+
+        Some(container(content).padding(30).into())
+    }
+}
@@ -0,0 +1,178 @@
+//! A modal window that compresses or decompresses any file in Firefox's
+//! mozlz4 format, not just session files.
+
+use std::path::PathBuf;
+
+use iced::widget::{button, checkbox, column, container, pick_list, row, text, text_input};
+use iced::{Alignment, Element, Task};
+
+use crate::host::{self, Mozlz4Mode};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Hide,
+    Show,
+    SetMode(Mozlz4Mode),
+    SetInput(String),
+    /// An empty output path writes next to the input file.
+    SetOutput(String),
+    SetOverwrite(bool),
+    BrowseInput,
+    Convert,
+    Converted(Result<PathBuf, host::Error>),
+}
+
+#[derive(Debug, Clone)]
+pub struct State {
+    active: bool,
+    mode: Mozlz4Mode,
+    input: String,
+    output: String,
+    overwrite: bool,
+    converting: bool,
+    /// Result of the last conversion.
+    status: Option<Result<String, String>>,
+}
+impl State {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            mode: Mozlz4Mode::Decompress,
+            input: String::new(),
+            output: String::new(),
+            overwrite: false,
+            converting: false,
+            status: None,
+        }
+    }
+
+    /// Where the file will be written.
+    fn output_path(&self) -> Option<PathBuf> {
+        if self.input.trim().is_empty() {
+            None
+        } else if self.output.trim().is_empty() {
+            Some(self.mode.default_output_path(self.input.trim().as_ref()))
+        } else {
+            Some(PathBuf::from(self.output.trim()))
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Hide => self.active = false,
+            Message::Show => self.active = true,
+            Message::SetMode(mode) => {
+                self.mode = mode;
+                self.status = None;
+            }
+            Message::SetInput(input) => {
+                self.input = input;
+                self.status = None;
+            }
+            Message::SetOutput(output) => {
+                self.output = output;
+                self.status = None;
+            }
+            Message::SetOverwrite(overwrite) => self.overwrite = overwrite,
+            Message::BrowseInput => {
+                return Task::perform(
+                    host::prompt_any_file("Select a mozlz4 file or a file to compress"),
+                    |handle| match handle {
+                        #[cfg(not(target_family = "wasm"))]
+                        Some(handle) => {
+                            Message::SetInput(handle.path().to_string_lossy().into_owned())
+                        }
+                        #[cfg(target_family = "wasm")]
+                        Some(handle) => Message::SetInput(handle.file_name()),
+                        // Cancelled, keep the modal open:
+                        None => Message::Show,
+                    },
+                );
+            }
+            Message::Convert => {
+                let Some(output) = self.output_path() else {
+                    return Task::none();
+                };
+                self.converting = true;
+                self.status = None;
+                return Task::perform(
+                    host::convert_mozlz4(
+                        self.mode,
+                        PathBuf::from(self.input.trim()),
+                        Some(output),
+                        self.overwrite,
+                    ),
+                    Message::Converted,
+                );
+            }
+            Message::Converted(result) => {
+                self.converting = false;
+                self.status = Some(match result {
+                    Ok(path) => Ok(format!("Wrote \"{}\"", path.display())),
+                    Err(e) => Err(format!("Failed to {}: {e}", self.mode)),
+                });
+            }
+        }
+        Task::none()
+    }
+
+    pub fn view(&self) -> Option<Element<'_, Message>> {
+        if !self.active {
+            return None;
+        }
+        let default_output = self
+            .output_path()
+            .filter(|_| self.output.trim().is_empty())
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let content = container(
+            column![
+                text("Compress or decompress mozlz4 files").size(24),
+                text(
+                    "Firefox stores sessions, search engines (search.json.mozlz4), \
+                     add-ons (addonStartup.json.lz4) and bookmark backups in this format."
+                ),
+                row![
+                    pick_list(Mozlz4Mode::all(), Some(self.mode), Message::SetMode),
+                    text_input("Input file", &self.input).on_input(Message::SetInput),
+                    button("Browse").on_press(Message::BrowseInput),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+                row![
+                    text("Save to: "),
+                    text_input(&default_output, &self.output).on_input(Message::SetOutput),
+                ]
+                .align_y(Alignment::Center),
+                checkbox("Overwrite file if it already exists", self.overwrite)
+                    .on_toggle(Message::SetOverwrite),
+                row![
+                    button(if self.mode == Mozlz4Mode::Compress {
+                        "Compress"
+                    } else {
+                        "Decompress"
+                    })
+                    .on_press_maybe(
+                        (!self.converting && self.output_path().is_some())
+                            .then_some(Message::Convert)
+                    ),
+                    button("Close")
+                        .style(iced::widget::button::secondary)
+                        .on_press(Message::Hide),
+                ]
+                .spacing(10),
+            ]
+            .push_maybe(self.status.as_ref().map(|status| match status {
+                Ok(done) => text(done),
+                Err(e) => text(e).style(text::danger),
+            }))
+            .spacing(15),
+        )
+        .width(600)
+        .padding(10)
+        .style(iced::widget::container::bordered_box);
+
+        Some(container(content).padding(30).into())
+    }
+}
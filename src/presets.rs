@@ -0,0 +1,323 @@
+//! Named sets of selected windows and output options that are remembered
+//! between runs.
+//!
+//! Windows are remembered by name since [`host::TabGroup::index`] and the
+//! position of a window in the sidebar change between sessions.
+
+use std::future::Future;
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use crate::host;
+
+/// Name of the file inside [`host::config_folder`] that stores the presets.
+const FILE_NAME: &str = "presets.json";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preset {
+    pub name: String,
+    /// Names of the selected open windows. `None` selects all open windows.
+    pub open_groups: Option<Vec<String>>,
+    /// Names of the selected closed windows. `None` selects all closed
+    /// windows.
+    pub closed_groups: Option<Vec<String>>,
+    pub sort_groups: bool,
+    pub table_of_content: bool,
+    pub group_by: host::GroupBy,
+    pub container_filter: Option<u32>,
+    pub native_tab_groups: bool,
+    pub output: host::OutputOptions,
+}
+impl Preset {
+    /// Remember the current options and the names of the selected groups.
+    pub fn new(
+        name: String,
+        groups: &host::AllTabGroups,
+        options: &host::GenerateOptions,
+        output: &host::OutputOptions,
+    ) -> Self {
+        let names = |list: &[host::TabGroup], indexes: &Option<Vec<u32>>| {
+            indexes.as_ref().map(|indexes| {
+                indexes
+                    .iter()
+                    .filter_map(|&index| list.get(index as usize))
+                    .map(|group| group.name.clone())
+                    .collect()
+            })
+        };
+        Self {
+            name,
+            open_groups: names(&groups.open, &options.open_group_indexes),
+            closed_groups: names(&groups.closed, &options.closed_group_indexes),
+            sort_groups: options.sort_groups,
+            table_of_content: options.table_of_content,
+            group_by: options.group_by,
+            container_filter: options.container_filter,
+            native_tab_groups: options.native_tab_groups,
+            output: output.clone(),
+        }
+    }
+
+    /// `true` if the tabs must be regrouped before the selected groups can be
+    /// found.
+    pub fn changes_grouping(&self, options: &host::GenerateOptions) -> bool {
+        self.group_by != options.group_by
+            || self.container_filter != options.container_filter
+            || self.native_tab_groups != options.native_tab_groups
+    }
+
+    /// Change everything except which groups are selected.
+    pub fn apply_options(
+        &self,
+        options: &mut host::GenerateOptions,
+        output: &mut host::OutputOptions,
+    ) {
+        options.sort_groups = self.sort_groups;
+        options.table_of_content = self.table_of_content;
+        options.group_by = self.group_by;
+        options.container_filter = self.container_filter;
+        options.native_tab_groups = self.native_tab_groups;
+        *output = self.output.clone();
+    }
+
+    /// Select the groups with the remembered names. Returns the names that
+    /// didn't match any group.
+    pub fn apply_selection(
+        &self,
+        groups: &host::AllTabGroups,
+        options: &mut host::GenerateOptions,
+    ) -> Vec<String> {
+        let mut missing = Vec::new();
+        let mut find = |list: &[host::TabGroup], names: &Option<Vec<String>>| {
+            names.as_ref().map(|names| {
+                let indexes = (0..)
+                    .zip(list)
+                    .filter(|(_, group)| names.contains(&group.name))
+                    .map(|(index, _)| index)
+                    .collect::<Vec<u32>>();
+                missing.extend(
+                    names
+                        .iter()
+                        .filter(|name| !list.iter().any(|group| &group.name == *name))
+                        .cloned(),
+                );
+                indexes
+            })
+        };
+        options.open_group_indexes = find(&groups.open, &self.open_groups);
+        options.closed_group_indexes = find(&groups.closed, &self.closed_groups);
+        missing
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "open_groups": self.open_groups,
+            "closed_groups": self.closed_groups,
+            "sort_groups": self.sort_groups,
+            "table_of_content": self.table_of_content,
+            "group_by": self.group_by.as_str(),
+            "container_filter": self.container_filter,
+            "native_tab_groups": self.native_tab_groups,
+            "format": self.output.format_name(),
+            "overwrite": self.output.overwrite,
+            "create_folder": self.output.create_folder,
+            "embed_favicons": self.output.embed_favicons,
+            "json_selected_windows": self.output.json_selected_windows,
+        })
+    }
+    fn from_json(json: &Value) -> Option<Self> {
+        let names = |key: &str| -> Option<Option<Vec<String>>> {
+            match json.get(key)? {
+                Value::Null => Some(None),
+                value => Some(Some(
+                    value
+                        .as_array()?
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(ToOwned::to_owned)
+                        .collect(),
+                )),
+            }
+        };
+        let flag = |key: &str| json.get(key).and_then(Value::as_bool);
+        let defaults = host::GenerateOptions::default();
+        let group_by = json.get("group_by")?.as_str()?;
+        let mut output = host::OutputOptions {
+            overwrite: flag("overwrite").unwrap_or(false),
+            create_folder: flag("create_folder").unwrap_or(false),
+            embed_favicons: flag("embed_favicons").unwrap_or(false),
+            json_selected_windows: flag("json_selected_windows").unwrap_or(false),
+            ..Default::default()
+        };
+        if !output.set_format(json.get("format")?.as_str()?) {
+            return None;
+        }
+        Some(Self {
+            name: json.get("name")?.as_str()?.to_owned(),
+            open_groups: names("open_groups")?,
+            closed_groups: names("closed_groups")?,
+            sort_groups: flag("sort_groups").unwrap_or(defaults.sort_groups),
+            table_of_content: flag("table_of_content").unwrap_or(defaults.table_of_content),
+            group_by: *host::GroupBy::all()
+                .iter()
+                .find(|g| g.as_str() == group_by)?,
+            container_filter: json
+                .get("container_filter")
+                .and_then(Value::as_u64)
+                .and_then(|id| u32::try_from(id).ok()),
+            native_tab_groups: flag("native_tab_groups").unwrap_or(false),
+            output,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Presets {
+    /// Sorted by name.
+    presets: Vec<Preset>,
+    /// Where the presets are saved. `None` if they should only be kept in
+    /// memory.
+    save_path: Option<PathBuf>,
+}
+impl Presets {
+    /// Read the presets that were saved by a previous run.
+    pub fn load() -> Self {
+        let Some(path) = host::config_folder().map(|folder| folder.join(FILE_NAME)) else {
+            return Self::default();
+        };
+        let presets = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice::<Value>(&data)
+                .map_err(|e| log::warn!("Failed to parse {}: {e}", path.display()))
+                .ok()
+                .and_then(|json| {
+                    Some(
+                        json.get("presets")?
+                            .as_array()?
+                            .iter()
+                            .filter_map(Preset::from_json)
+                            .collect(),
+                    )
+                })
+                .unwrap_or_default(),
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("Failed to read {}: {e}", path.display());
+                }
+                Vec::new()
+            }
+        };
+        Self {
+            presets,
+            save_path: Some(path),
+        }
+    }
+
+    /// Write the presets to disk in the background like
+    /// [`RecentFiles::save`](crate::recent::RecentFiles::save).
+    pub fn save(&self) -> impl Future<Output = Result<(), host::Error>> + 'static {
+        let json = json!({
+            "presets": self.presets.iter().map(Preset::to_json).collect::<Vec<_>>(),
+        });
+        host::save_config_file(self.save_path.clone(), json.to_string())
+    }
+
+    /// Add a preset, replacing any preset with the same name.
+    pub fn add(&mut self, preset: Preset) {
+        self.remove(&preset.name);
+        let index = self
+            .presets
+            .partition_point(|other| other.name < preset.name);
+        self.presets.insert(index, preset);
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.presets.retain(|preset| preset.name != name);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.presets
+            .iter()
+            .map(|preset| preset.name.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn groups(open: &[&str]) -> host::AllTabGroups {
+        host::AllTabGroups {
+            open: (0..)
+                .zip(open)
+                .map(|(index, name)| host::TabGroup {
+                    index,
+                    name: (*name).to_owned(),
+                    color: None,
+                    sub_groups: Vec::new(),
+                    favicon: None,
+                    depth: 0,
+                    json_path: None,
+                })
+                .collect(),
+            closed: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn select_windows_by_name() {
+        let options = host::GenerateOptions {
+            open_group_indexes: Some(vec![1, 2]),
+            ..Default::default()
+        };
+        let preset = Preset::new(
+            "Weekly".to_owned(),
+            &groups(&["Mail", "Work", "News"]),
+            &options,
+            &host::OutputOptions::default(),
+        );
+        assert_eq!(
+            preset.open_groups.as_deref(),
+            Some(&["Work".to_owned(), "News".to_owned()][..])
+        );
+        assert_eq!(preset.closed_groups.as_deref(), Some(&[][..]));
+
+        // The windows moved and one of them is gone:
+        let mut options = host::GenerateOptions::default();
+        let missing = preset.apply_selection(&groups(&["Work", "Mail"]), &mut options);
+        assert_eq!(options.open_group_indexes, Some(vec![0]));
+        assert_eq!(options.closed_group_indexes, Some(vec![]));
+        assert_eq!(missing, ["News"]);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut output = host::OutputOptions {
+            overwrite: true,
+            embed_favicons: true,
+            ..Default::default()
+        };
+        assert!(output.set_format("markdown"));
+        let preset = Preset {
+            name: "Weekly".to_owned(),
+            open_groups: Some(vec!["Work".to_owned()]),
+            closed_groups: None,
+            sort_groups: false,
+            table_of_content: false,
+            group_by: host::GroupBy::Domain,
+            container_filter: Some(3),
+            native_tab_groups: true,
+            output,
+        };
+        assert_eq!(Preset::from_json(&preset.to_json()), Some(preset));
+
+        // Presets that can't be understood are skipped:
+        assert_eq!(Preset::from_json(&json!({ "name": "Broken" })), None);
+    }
+}
@@ -0,0 +1,156 @@
+//! A list of recently opened session files that is remembered between runs.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use serde_json::{json, Value};
+
+use crate::host;
+
+/// Name of the file inside [`host::config_folder`] that stores the list.
+const FILE_NAME: &str = "recent-files.json";
+/// Don't remember more files than this.
+const MAX_FILES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    pub opened_at: SystemTime,
+    /// Name of the Firefox profile that the file was stored in.
+    pub profile: Option<String>,
+}
+impl RecentFile {
+    fn to_json(&self) -> Value {
+        json!({
+            "path": self.path.to_string_lossy(),
+            "opened_at": self
+                .opened_at
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            "profile": self.profile,
+        })
+    }
+    fn from_json(json: &Value) -> Option<Self> {
+        Some(Self {
+            path: PathBuf::from(json.get("path")?.as_str()?),
+            opened_at: SystemTime::UNIX_EPOCH
+                + Duration::from_secs(json.get("opened_at")?.as_u64()?),
+            profile: json
+                .get("profile")
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned),
+        })
+    }
+}
+impl std::fmt::Display for RecentFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(profile) = &self.profile {
+            write!(f, " ({profile})")?;
+        }
+        write!(f, " - {}", time_ago(self.opened_at))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RecentFiles {
+    /// Most recently opened file first.
+    files: Vec<RecentFile>,
+    /// Where the list is saved. `None` if the list should only be kept in
+    /// memory.
+    save_path: Option<PathBuf>,
+}
+impl RecentFiles {
+    /// Read the list that was saved by a previous run. Returns an empty list
+    /// if nothing was saved.
+    pub fn load() -> Self {
+        let Some(path) = host::config_folder().map(|folder| folder.join(FILE_NAME)) else {
+            return Self::default();
+        };
+        let files = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice::<Value>(&data)
+                .map_err(|e| log::warn!("Failed to parse {}: {e}", path.display()))
+                .ok()
+                .and_then(|json| {
+                    Some(
+                        json.get("files")?
+                            .as_array()?
+                            .iter()
+                            .filter_map(RecentFile::from_json)
+                            .collect(),
+                    )
+                })
+                .unwrap_or_default(),
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("Failed to read {}: {e}", path.display());
+                }
+                Vec::new()
+            }
+        };
+        Self {
+            files,
+            save_path: Some(path),
+        }
+    }
+
+    /// Write the list to disk in the background so that the app isn't blocked
+    /// by a slow drive.
+    pub fn save(&self) -> impl Future<Output = Result<(), String>> + 'static {
+        let path = self.save_path.clone();
+        let json = json!({
+            "files": self.files.iter().map(RecentFile::to_json).collect::<Vec<_>>(),
+        });
+        async move {
+            let path = path.ok_or("no folder to store settings in")?;
+            host::spawn_blocking(move || {
+                if let Some(folder) = path.parent() {
+                    std::fs::create_dir_all(folder).map_err(|e| {
+                        format!("failed to create folder at {}: {e}", folder.display())
+                    })?;
+                }
+                std::fs::write(&path, json.to_string())
+                    .map_err(|e| format!("failed to write {}: {e}", path.display()))
+            })
+            .await
+        }
+    }
+
+    /// Remember that a file was opened, moving it first if it was already in
+    /// the list.
+    pub fn add(&mut self, path: PathBuf) {
+        let profile = host::FirefoxProfileInfo::for_session_file(&path)
+            .map(|profile| profile.name().into_owned());
+        self.files.retain(|file| file.path != path);
+        self.files.insert(
+            0,
+            RecentFile {
+                path,
+                opened_at: SystemTime::now(),
+                profile,
+            },
+        );
+        self.files.truncate(MAX_FILES);
+    }
+
+    pub fn files(&self) -> &[RecentFile] {
+        &self.files
+    }
+}
+
+/// Describe how long ago a time was, for example `"5 minutes ago"`.
+fn time_ago(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs();
+    let (amount, unit) = match secs {
+        0..60 => return "just now".to_owned(),
+        60..3600 => (secs / 60, "minute"),
+        3600..86400 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
+    };
+    format!("{amount} {unit}{} ago", if amount == 1 { "" } else { "s" })
+}
@@ -0,0 +1,229 @@
+//! Changes to which tab groups are selected that affect many groups at once.
+
+use firefox_session_ui_iced::host::{AllTabGroups, GenerateOptions, TabGroup};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BulkSelection {
+    /// Every open and closed group.
+    All,
+    None,
+    /// Select the groups that aren't selected and unselect the others.
+    Invert,
+    /// Only the groups from closed windows.
+    AllClosed,
+    /// Groups whose name matches a pattern, see [`matches_pattern`].
+    Matching(String),
+    /// Shift-click in the sidebar: change every group between two positions in
+    /// the open or closed list.
+    Range {
+        open: bool,
+        from: u32,
+        to: u32,
+        select: bool,
+    },
+}
+impl BulkSelection {
+    /// The selected open and closed groups after the change, or `None` if no
+    /// group would be selected by a pattern.
+    ///
+    /// Groups that are only implicitly selected (when `open_group_indexes` is
+    /// `None`) aren't highlighted in the sidebar so they are treated as
+    /// unselected here.
+    pub fn apply(
+        &self,
+        groups: &AllTabGroups,
+        current: &GenerateOptions,
+    ) -> Option<(Vec<u32>, Vec<u32>)> {
+        let all = |list: &[TabGroup]| (0..list.len() as u32).collect::<Vec<_>>();
+        let visible = |indexes: &Option<Vec<u32>>| indexes.clone().unwrap_or_default();
+        Some(match self {
+            BulkSelection::All => (all(&groups.open), all(&groups.closed)),
+            BulkSelection::None => (Vec::new(), Vec::new()),
+            BulkSelection::Invert => {
+                let invert = |list: &[TabGroup], selected: &Option<Vec<u32>>| {
+                    let selected = visible(selected);
+                    all(list)
+                        .into_iter()
+                        .filter(|index| !selected.contains(index))
+                        .collect()
+                };
+                (
+                    invert(&groups.open, &current.open_group_indexes),
+                    invert(&groups.closed, &current.closed_group_indexes),
+                )
+            }
+            BulkSelection::AllClosed => (Vec::new(), all(&groups.closed)),
+            BulkSelection::Matching(pattern) => {
+                let matching = |list: &[TabGroup]| {
+                    (0..)
+                        .zip(list)
+                        .filter(|(_, group)| matches_pattern(pattern, &group.name))
+                        .map(|(index, _)| index)
+                        .collect::<Vec<u32>>()
+                };
+                let selection = (matching(&groups.open), matching(&groups.closed));
+                if selection.0.is_empty() && selection.1.is_empty() {
+                    return None;
+                }
+                selection
+            }
+            &BulkSelection::Range {
+                open,
+                from,
+                to,
+                select,
+            } => {
+                let mut selection = (
+                    visible(&current.open_group_indexes),
+                    visible(&current.closed_group_indexes),
+                );
+                let indexes = if open {
+                    &mut selection.0
+                } else {
+                    &mut selection.1
+                };
+                for index in from.min(to)..=from.max(to) {
+                    if !select {
+                        indexes.retain(|v| *v != index);
+                    } else if !indexes.contains(&index) {
+                        indexes.push(index);
+                    }
+                }
+                selection
+            }
+        })
+    }
+}
+
+/// Case insensitive match where `*` matches any text and `?` matches a single
+/// character. Patterns without wildcards match anywhere in the name.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    let name = name.to_lowercase();
+    if !pattern.contains(['*', '?']) {
+        return name.contains(&pattern);
+    }
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    // Backtrack to the last `*` when a character doesn't match:
+    let (mut p, mut n) = (0, 0);
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn groups() -> AllTabGroups {
+        let group = |index: u32, name: &str| TabGroup {
+            index,
+            name: name.to_owned(),
+            color: None,
+            sub_groups: Vec::new(),
+            favicon: None,
+            depth: 0,
+            json_path: None,
+        };
+        AllTabGroups {
+            open: vec![group(0, "Window 1"), group(1, "Window 2")],
+            closed: vec![group(2, "Closed window 1")],
+        }
+    }
+
+    #[test]
+    fn bulk_selection() {
+        let groups = groups();
+        let apply = |bulk: BulkSelection, open: Option<Vec<u32>>, closed: Option<Vec<u32>>| {
+            let current = GenerateOptions {
+                open_group_indexes: open,
+                closed_group_indexes: closed,
+                ..Default::default()
+            };
+            bulk.apply(&groups, &current)
+        };
+        assert_eq!(
+            apply(BulkSelection::All, None, None),
+            Some((vec![0, 1], vec![0]))
+        );
+        assert_eq!(
+            apply(BulkSelection::None, None, None),
+            Some((vec![], vec![]))
+        );
+        assert_eq!(
+            apply(BulkSelection::AllClosed, Some(vec![1]), None),
+            Some((vec![], vec![0]))
+        );
+        assert_eq!(
+            apply(BulkSelection::Invert, Some(vec![]), Some(vec![0])),
+            Some((vec![0, 1], vec![]))
+        );
+        // Implicitly selected groups aren't highlighted so they count as
+        // unselected:
+        assert_eq!(
+            apply(BulkSelection::Invert, None, None),
+            Some((vec![0, 1], vec![0]))
+        );
+
+        let matching =
+            |pattern: &str| apply(BulkSelection::Matching(pattern.to_owned()), None, None);
+        assert_eq!(matching("window 2"), Some((vec![1], vec![])));
+        assert_eq!(matching("*CLOSED*"), Some((vec![], vec![0])));
+        assert_eq!(matching("nothing"), None);
+    }
+
+    #[test]
+    fn ranges() {
+        let groups = groups();
+        let current = GenerateOptions {
+            open_group_indexes: Some(vec![1]),
+            closed_group_indexes: None,
+            ..Default::default()
+        };
+        let range = |from, to, select| BulkSelection::Range {
+            open: true,
+            from,
+            to,
+            select,
+        };
+        assert_eq!(
+            range(1, 0, true).apply(&groups, &current),
+            Some((vec![1, 0], vec![]))
+        );
+        assert_eq!(
+            range(0, 1, false).apply(&groups, &current),
+            Some((vec![], vec![]))
+        );
+    }
+
+    #[test]
+    fn window_patterns() {
+        assert!(matches_pattern("work", "My Work Window"));
+        assert!(matches_pattern("w?rk*", "Work stuff"));
+        assert!(!matches_pattern("w?rk", "Work stuff"));
+        assert!(matches_pattern("*a*b*c", "xxaxxbxxc"));
+        assert!(!matches_pattern("*a*b*c", "xxaxxcxxb"));
+        assert!(matches_pattern("*", ""));
+    }
+}
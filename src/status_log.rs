@@ -0,0 +1,239 @@
+//! History of status messages and `log` crate output, shown in a collapsible
+//! panel below the status bar.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Mutex, OnceLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use iced::futures::{channel::mpsc, stream, StreamExt};
+use iced::Subscription;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+impl Severity {
+    fn from_level(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => Self::Error,
+            log::Level::Warn => Self::Warning,
+            log::Level::Info | log::Level::Debug | log::Level::Trace => Self::Info,
+        }
+    }
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "INFO",
+            Severity::Warning => "WARNING",
+            Severity::Error => "ERROR",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub time: SystemTime,
+    pub severity: Severity,
+    pub message: String,
+}
+impl Entry {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            time: now(),
+            severity,
+            message: message.into(),
+        }
+    }
+}
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<7} {}",
+            format_utc(self.time),
+            self.severity.as_str(),
+            self.message
+        )
+    }
+}
+
+/// The most recent log entries, older entries are dropped once there are more
+/// than [`StatusLog::MAX_ENTRIES`].
+#[derive(Debug, Default)]
+pub struct StatusLog {
+    entries: VecDeque<Entry>,
+}
+impl StatusLog {
+    pub const MAX_ENTRIES: usize = 1000;
+
+    pub fn push(&mut self, entry: Entry) {
+        if self.entries.len() >= Self::MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Entry::new(Severity::Info, message));
+    }
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Entry::new(Severity::Error, message));
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &Entry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Number of entries with at least the specified severity.
+    pub fn count_at_least(&self, severity: Severity) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.severity >= severity)
+            .count()
+    }
+    /// The whole log as text with one entry per line.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for entry in &self.entries {
+            use fmt::Write;
+            let _ = writeln!(text, "{entry}");
+        }
+        text
+    }
+}
+
+/// [`SystemTime::now`] panics on the web so ask JavaScript instead.
+pub fn now() -> SystemTime {
+    #[cfg(target_family = "wasm")]
+    {
+        UNIX_EPOCH + Duration::from_millis(js_sys::Date::now() as u64)
+    }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        SystemTime::now()
+    }
+}
+
+/// Format a time as `YYYY-MM-DD hh:mm:ss` in UTC.
+pub fn format_utc(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01, see
+    // <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+/// Sends log records to the UI.
+static SENDER: OnceLock<mpsc::UnboundedSender<Entry>> = OnceLock::new();
+/// Taken by the first [`subscription`] that runs.
+static RECEIVER: Mutex<Option<mpsc::UnboundedReceiver<Entry>>> = Mutex::new(None);
+
+/// Captures `log` crate output so that it can be shown in the log panel.
+struct Logger;
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        // Dependencies like wgpu are very chatty so only show their warnings:
+        metadata.level() <= log::Level::Warn
+            || (metadata.target().starts_with("firefox_session")
+                && metadata.level() <= log::max_level())
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let message = record.args().to_string();
+
+        #[cfg(target_family = "wasm")]
+        {
+            let text = wasm_bindgen::JsValue::from_str(&format!(
+                "{} {}: {message}",
+                record.level(),
+                record.target()
+            ));
+            match record.level() {
+                log::Level::Error => web_sys::console::error_1(&text),
+                log::Level::Warn => web_sys::console::warn_1(&text),
+                log::Level::Info => web_sys::console::info_1(&text),
+                log::Level::Debug | log::Level::Trace => web_sys::console::debug_1(&text),
+            }
+        }
+        if let Some(sender) = SENDER.get() {
+            let _ =
+                sender.unbounded_send(Entry::new(Severity::from_level(record.level()), message));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install a logger that forwards `log` crate output to the log panel (and to
+/// the browser console when running as a website).
+pub fn init_logger() {
+    let (sender, receiver) = mpsc::unbounded();
+    if SENDER.set(sender).is_err() {
+        return;
+    }
+    *RECEIVER.lock().unwrap() = Some(receiver);
+
+    if log::set_logger(&Logger).is_ok() {
+        log::set_max_level(if cfg!(debug_assertions) {
+            log::LevelFilter::Debug
+        } else {
+            log::LevelFilter::Info
+        });
+    }
+}
+
+/// Entries written via the `log` crate since the app was started.
+pub fn subscription() -> Subscription<Entry> {
+    Subscription::run(|| match RECEIVER.lock().unwrap().take() {
+        Some(receiver) => receiver.boxed(),
+        None => stream::empty().boxed(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn timestamps() {
+        let format = |secs| format_utc(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(format(0), "1970-01-01 00:00:00");
+        assert_eq!(format(951_782_400 + 3661), "2000-02-29 01:01:01");
+        assert_eq!(format(1_792_281_599), "2026-10-17 23:59:59");
+    }
+}
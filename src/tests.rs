@@ -0,0 +1,1820 @@
+//! Drives [`SessionDataUtility`] with messages without opening a window.
+//!
+//! [`Harness`] calls `update` and runs the returned tasks on a tokio runtime,
+//! the same way that iced would. The view tests lay out and draw the UI with
+//! a tiny-skia renderer that is never connected to a window so they don't need
+//! a GPU, and can simulate mouse clicks to find out which message a part of the
+//! UI would emit.
+
+use super::*;
+
+use iced::futures::StreamExt;
+use iced_runtime::Action;
+
+/// Result of running a task to completion.
+#[derive(Debug, Default)]
+struct TaskOutput {
+    /// Messages that would be sent back to `update`.
+    messages: Vec<Message>,
+    /// Other actions for the iced runtime, for example clipboard writes.
+    actions: usize,
+}
+
+struct Harness {
+    app: SessionDataUtility,
+    runtime: tokio::runtime::Runtime,
+}
+impl Harness {
+    /// An app without any loaded data that doesn't save its recent files.
+    fn new() -> Self {
+        let mut app = SessionDataUtility::new();
+        app.tab_groups = Default::default();
+        app.recent_files = Default::default();
+        app.presets = Default::default();
+        app.timeline = Default::default();
+        app.preview_debounce = Duration::ZERO;
+        app.theme = Theme::Light;
+        Self {
+            app,
+            runtime: tokio::runtime::Runtime::new().expect("failed to start tokio runtime"),
+        }
+    }
+
+    /// An app that has two open windows and one closed window but whose data
+    /// hasn't been read yet.
+    fn with_groups() -> Self {
+        let mut harness = Self::new();
+        let group = |index: u32, name: &str| host::TabGroup {
+            index,
+            name: name.to_owned(),
+            color: None,
+            sub_groups: Vec::new(),
+            favicon: None,
+            depth: 0,
+            json_path: None,
+        };
+        harness.app.loaded_data = Some(host::FileInfo::new(PathBuf::from("sessionstore.jsonlz4")));
+        harness.app.tab_groups = host::AllTabGroups {
+            open: vec![group(0, "Window 1"), group(1, "Window 2")],
+            closed: vec![group(2, "Closed window 1")],
+        };
+        harness
+    }
+
+    fn selection(&self) -> (Option<Vec<u32>>, Option<Vec<u32>>) {
+        let selected = &self.app.selected_tab_groups;
+        (
+            selected.open_group_indexes.clone(),
+            selected.closed_group_indexes.clone(),
+        )
+    }
+
+    /// Update the app and report if any task was returned, without running it.
+    fn send(&mut self, message: Message) -> bool {
+        iced_runtime::task::into_stream(self.app.update(message)).is_some()
+    }
+
+    /// Update the app and run the returned task to completion. Messages
+    /// produced by the task are returned instead of being sent to the app.
+    fn run(&mut self, message: Message) -> TaskOutput {
+        let Some(stream) = iced_runtime::task::into_stream(self.app.update(message)) else {
+            return TaskOutput::default();
+        };
+        self.runtime.block_on(
+            stream.fold(TaskOutput::default(), |mut output, action| async move {
+                match action {
+                    Action::Output(message) => output.messages.push(message),
+                    _ => output.actions += 1,
+                }
+                output
+            }),
+        )
+    }
+
+    /// Send a message and then keep sending the messages that the returned
+    /// tasks produce until there is nothing left to do.
+    fn settle(&mut self, message: Message) {
+        let mut queue = vec![message];
+        for _ in 0..100 {
+            let Some(message) = queue.pop() else {
+                return;
+            };
+            queue.extend(self.run(message).messages);
+        }
+        panic!("app didn't stop sending messages");
+    }
+}
+
+#[test]
+fn selecting_closed_window_unselects_implicit_open_windows() {
+    let mut harness = Harness::with_groups();
+    assert_eq!(harness.selection(), (None, Some(vec![])));
+
+    assert!(harness.send(Message::ChangeTabGroupSelection {
+        open: false,
+        index: 0,
+        select: true,
+    }));
+    assert_eq!(harness.selection(), (Some(vec![]), Some(vec![0])));
+    assert_eq!(harness.app.status, "Generating preview");
+}
+
+#[test]
+fn unselecting_last_window_selects_all_open_windows() {
+    let mut harness = Harness::with_groups();
+    assert!(harness.send(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 1,
+        select: true,
+    }));
+    assert_eq!(harness.selection(), (Some(vec![1]), Some(vec![])));
+
+    assert!(harness.send(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 1,
+        select: false,
+    }));
+    assert_eq!(harness.selection(), (None, Some(vec![])));
+
+    // Same when the last selected group was a closed window:
+    assert!(harness.send(Message::ChangeTabGroupSelection {
+        open: false,
+        index: 0,
+        select: true,
+    }));
+    assert!(harness.send(Message::ChangeTabGroupSelection {
+        open: false,
+        index: 0,
+        select: false,
+    }));
+    assert_eq!(harness.selection(), (None, Some(vec![])));
+}
+
+#[test]
+fn selecting_open_and_closed_windows() {
+    let mut harness = Harness::with_groups();
+    for (open, index) in [(true, 0), (false, 0), (true, 1)] {
+        assert!(harness.send(Message::ChangeTabGroupSelection {
+            open,
+            index,
+            select: true,
+        }));
+    }
+    assert_eq!(harness.selection(), (Some(vec![0, 1]), Some(vec![0])));
+    assert_eq!(harness.app.selected_tab_groups.selected_groups(), 3);
+
+    // Unselecting one of several groups keeps the others:
+    assert!(harness.send(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 0,
+        select: false,
+    }));
+    assert_eq!(harness.selection(), (Some(vec![1]), Some(vec![0])));
+}
+
+#[test]
+fn unchanged_selection_doesnt_regenerate_preview() {
+    let mut harness = Harness::with_groups();
+
+    // Nothing to unselect while all open windows are implicitly selected:
+    assert!(!harness.send(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 0,
+        select: false,
+    }));
+    assert!(!harness.send(Message::ChangeTabGroupSelection {
+        open: false,
+        index: 0,
+        select: false,
+    }));
+    assert_eq!(harness.selection(), (None, Some(vec![])));
+
+    assert!(harness.send(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 0,
+        select: true,
+    }));
+    assert!(!harness.send(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 0,
+        select: true,
+    }));
+    assert_eq!(harness.selection(), (Some(vec![0]), Some(vec![])));
+}
+
+#[test]
+fn selection_without_loaded_data() {
+    let mut harness = Harness::new();
+    assert!(!harness.send(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 0,
+        select: true,
+    }));
+    assert_eq!(harness.selection(), (Some(vec![0]), Some(vec![])));
+    assert_eq!(harness.app.status, "");
+}
+
+#[test]
+fn changing_grouping_clears_selection() {
+    let mut harness = Harness::with_groups();
+    harness.send(Message::ChangeTabGroupSelection {
+        open: false,
+        index: 0,
+        select: true,
+    });
+
+    // Already grouped by window:
+    assert!(!harness.send(Message::SetGroupBy(host::GroupBy::Window)));
+    assert_eq!(harness.selection(), (Some(vec![]), Some(vec![0])));
+
+    // Data hasn't been parsed so there is nothing to list yet:
+    assert!(!harness.send(Message::SetGroupBy(host::GroupBy::Domain)));
+    assert_eq!(
+        harness.app.selected_tab_groups.group_by,
+        host::GroupBy::Domain
+    );
+    assert_eq!(harness.selection(), (None, Some(vec![])));
+    assert_eq!(harness.app.tab_groups, host::AllTabGroups::default());
+}
+
+#[test]
+fn load_session_file() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plain.jsonlz4");
+    let mut harness = Harness::new();
+    harness.send(Message::SetInputPath(
+        path.to_string_lossy().into_owned(),
+        None,
+    ));
+    harness.settle(Message::LoadInputData);
+
+    assert_eq!(harness.app.status, "Successfully loaded session data");
+    assert!(!harness.app.tab_groups.open.is_empty());
+    assert!(!harness.app.preview.text().trim().is_empty());
+    assert_eq!(
+        harness
+            .app
+            .loaded_data
+            .as_ref()
+            .map(|data| &*data.file_path),
+        Some(&path)
+    );
+    assert_eq!(harness.app.recent_files.files()[0].path, path);
+    // Every step is kept in the status log:
+    let log = harness
+        .app
+        .status_log
+        .entries()
+        .map(|entry| entry.message.as_str())
+        .collect::<Vec<_>>();
+    for step in [
+        "Reading input file",
+        "Decompressing data",
+        "Parsing session data",
+        "Successfully loaded session data",
+    ] {
+        assert!(log.contains(&step), "{step:?} not in {log:?}");
+    }
+
+    // Selecting a window regenerates the preview after a short delay:
+    let output = harness.run(Message::ChangeTabGroupSelection {
+        open: true,
+        index: harness.app.tab_groups.open[0].index,
+        select: true,
+    });
+    assert!(matches!(
+        &output.messages[..],
+        [Message::GeneratePreview(_)]
+    ));
+    let output = harness.run(output.messages.into_iter().next().unwrap());
+    assert!(matches!(
+        &output.messages[..],
+        [Message::PreviewGenerated(_, Ok(_))]
+    ));
+
+    // Listing tabs by domain:
+    harness.settle(Message::SetGroupBy(host::GroupBy::Domain));
+    assert!(!harness.app.tab_groups.open.is_empty());
+    assert_eq!(harness.selection(), (None, Some(vec![])));
+}
+
+#[test]
+fn inspect_clicked_tab() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plain.jsonlz4");
+    let mut harness = Harness::new();
+    harness.send(Message::SetInputPath(
+        path.to_string_lossy().into_owned(),
+        None,
+    ));
+    harness.settle(Message::LoadInputData);
+
+    let preview = &harness.app.preview;
+    let (line, url) = (0..preview.line_count())
+        .find_map(|line| {
+            let text = preview.line(line);
+            Some((line, text[link_list::find_link(text)?].to_owned()))
+        })
+        .expect("preview has links");
+    harness.send(Message::Preview(link_list::Message::ClickLine {
+        line,
+        extend: false,
+    }));
+    let tab = harness.app.inspected_tab.clone().expect("tab was found");
+    assert_eq!(tab.url, url);
+    assert!(tab.history_length >= 1);
+    assert!(tab.window.contains("indow"));
+
+    assert_eq!(harness.run(Message::CopyToClipboard(tab.url)).actions, 2);
+
+    // Lines without links don't show a tab:
+    let heading = (0..harness.app.preview.line_count())
+        .find(|&line| link_list::find_link(harness.app.preview.line(line)).is_none())
+        .expect("preview has headings");
+    harness.send(Message::Preview(link_list::Message::ClickLine {
+        line: heading,
+        extend: false,
+    }));
+    assert_eq!(harness.app.inspected_tab, None);
+}
+
+#[test]
+fn json_explorer_search() {
+    let json = serde_json::json!({
+        "windows": [{ "tabs": [
+            { "entries": [{ "url": "https://example.com/" }] },
+            { "entries": [{ "url": "https://EXAMPLE.org/" }], "pinned": true },
+        ] }],
+        "a/b~c": 0,
+    });
+    assert_eq!(json_explorer::child_path("", "a/b~c"), "/a~1b~0c");
+    assert_eq!(json.pointer("/a~1b~0c"), Some(&serde_json::json!(0)));
+
+    let first = json_explorer::find_next(&json, "", "example").unwrap();
+    assert_eq!(first, "/windows/0/tabs/0/entries/0/url");
+    let second = json_explorer::find_next(&json, &first, "example").unwrap();
+    assert_eq!(second, "/windows/0/tabs/1/entries/0/url");
+    // Wraps around to the first match:
+    assert_eq!(
+        json_explorer::find_next(&json, &second, "example").as_ref(),
+        Some(&first)
+    );
+    // Keys and other values also match, but not array indexes:
+    assert_eq!(
+        json_explorer::find_next(&json, "", "pinned").as_deref(),
+        Some("/windows/0/tabs/1/pinned")
+    );
+    assert_eq!(json_explorer::find_next(&json, "", "1").as_deref(), None);
+}
+
+#[test]
+fn show_json_of_inspected_tab() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plain.jsonlz4");
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(path));
+
+    let data = harness
+        .app
+        .loaded_data
+        .as_ref()
+        .unwrap()
+        .json_data()
+        .unwrap();
+    let json = serde_json::from_slice::<serde_json::Value>(&data).unwrap();
+    let preview = &harness.app.preview;
+    let line = (0..preview.line_count())
+        .find(|&line| link_list::find_link(preview.line(line)).is_some())
+        .unwrap();
+    harness.send(Message::Preview(link_list::Message::ClickLine {
+        line,
+        extend: false,
+    }));
+    let tab = harness.app.inspected_tab.clone().unwrap();
+    let tab_json = json
+        .pointer(&tab.json_path)
+        .expect("path points at the tab");
+    let index = tab_json["index"].as_u64().unwrap() as usize;
+    assert_eq!(tab_json["entries"][index - 1]["url"], tab.url.as_str());
+
+    // Parses the JSON first, then selects the tab:
+    harness.settle(Message::ShowJson(tab.json_path.clone()));
+    assert!(harness.app.show_json_explorer);
+    assert!(harness.app.json_explorer.is_loaded());
+    // Scrolls to the tab once the JSON is parsed:
+    assert_eq!(harness.run(Message::ShowJson(tab.json_path)).actions, 1);
+    assert_eq!(
+        harness
+            .run(Message::ShowJson("/not/in/session".to_owned()))
+            .actions,
+        0
+    );
+
+    harness.send(Message::ToggleJsonExplorer);
+    assert!(!harness.app.show_json_explorer);
+}
+
+#[test]
+fn copy_links_writes_to_clipboard() {
+    let mut harness = Harness::with_groups();
+    harness.send(Message::SetPreview("http://www.example.com".to_owned()));
+    let output = harness.run(Message::CopyLinksToClipboard);
+    assert!(output.messages.is_empty());
+    // Both the normal clipboard and the primary selection:
+    assert_eq!(output.actions, 2);
+}
+
+#[test]
+fn preview_only_lays_out_visible_lines() {
+    let mut harness = Harness::new();
+    let text = (0..10_000)
+        .map(|i| format!("- [Tab {i}](https://example.com/{i})\n"))
+        .collect::<String>();
+    harness.send(Message::SetPreview(text));
+    assert_eq!(harness.app.preview.line_count(), 10_000);
+    assert_eq!(
+        harness.app.preview.line(1),
+        "- [Tab 1](https://example.com/1)"
+    );
+
+    harness.send(Message::Preview(link_list::Message::Scrolled {
+        offset_y: 5000.0 * link_list::LINE_HEIGHT,
+        height: 20.0 * link_list::LINE_HEIGHT,
+    }));
+    assert_eq!(harness.app.preview.first_visible_line(), 5000);
+    let rendered = harness.app.preview.rendered_lines();
+    assert!(rendered.contains(&5000) && rendered.contains(&5020));
+    assert!(rendered.len() < 100);
+
+    // New text scrolls back to the top:
+    harness.send(Message::SetPreview("http://www.example.com".to_owned()));
+    assert_eq!(harness.app.preview.first_visible_line(), 0);
+    assert_eq!(harness.app.preview.rendered_lines(), 0..1);
+}
+
+#[test]
+fn preview_links_and_selection() {
+    assert_eq!(
+        link_list::find_link("- [Tab](https://example.com/a?b=c)"),
+        Some(8..33)
+    );
+    assert_eq!(
+        link_list::find_link(r#"<a href="http://example.com">Tab</a>"#),
+        Some(9..27)
+    );
+    assert_eq!(link_list::find_link("Window 1"), None);
+
+    let mut harness = Harness::with_groups();
+    harness.send(Message::SetPreview("a\nb\nc\nd\n".to_owned()));
+    assert!(harness.run(Message::CopyPreviewSelection).actions == 0);
+
+    harness.send(Message::Preview(link_list::Message::ClickLine {
+        line: 1,
+        extend: false,
+    }));
+    harness.send(Message::Preview(link_list::Message::ClickLine {
+        line: 2,
+        extend: true,
+    }));
+    assert_eq!(
+        harness.app.preview.selected_text().as_deref(),
+        Some("b\nc\n")
+    );
+    assert_eq!(harness.run(Message::CopyPreviewSelection).actions, 2);
+
+    // Extending upwards keeps the first clicked line:
+    harness.send(Message::Preview(link_list::Message::ClickLine {
+        line: 0,
+        extend: true,
+    }));
+    assert_eq!(harness.app.preview.selection(), Some(0..2));
+}
+
+#[test]
+fn cancel_opening_tabs() {
+    let mut harness = Harness::with_groups();
+    harness.app.pending_open_urls = Some(vec![vec!["http://www.example.com".to_owned()]; 30]);
+    assert!(!harness.send(Message::ConfirmOpenTabs(false)));
+    assert!(harness.app.pending_open_urls.is_none());
+
+    // Nothing left to confirm:
+    assert!(!harness.send(Message::ConfirmOpenTabs(true)));
+}
+
+/// List the open windows in reverse, like `firefox_session_data` can when it
+/// sorts windows, and return the groups in their original order.
+fn sort_windows_backwards(groups: &mut host::AllTabGroups) -> host::AllTabGroups {
+    let original = groups.clone();
+    groups.open.reverse();
+    for (ix, group) in (0..).zip(&mut groups.open) {
+        group.index = ix;
+    }
+    original
+}
+
+#[test]
+fn open_tabs_of_sorted_windows() {
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(PathBuf::from("sessionstore.jsonlz4")));
+    let original = sort_windows_backwards(&mut harness.app.tab_groups);
+    let data = harness.app.loaded_data.as_ref().unwrap();
+    let selected = |index: u32| host::GenerateOptions {
+        open_group_indexes: Some(vec![index]),
+        ..Default::default()
+    };
+
+    let last = original.open.len() as u32 - 1;
+    let urls = data
+        .selected_urls(&harness.app.tab_groups, &selected(0))
+        .unwrap();
+    assert_eq!(
+        urls,
+        data.selected_urls(&original, &selected(last)).unwrap()
+    );
+    assert_ne!(urls, data.selected_urls(&original, &selected(0)).unwrap());
+
+    // Sidebery and Tree Style Tab panels aren't windows:
+    let mut panels = original.clone();
+    for group in &mut panels.open {
+        group.json_path = None;
+    }
+    let result = data.selected_urls(&panels, &selected(0));
+    assert!(
+        matches!(result, Err(host::Error::NotWindows { .. })),
+        "{result:?}"
+    );
+    // Unless tabs are grouped by our own code:
+    let by_host = host::GenerateOptions {
+        group_by: host::GroupBy::Host,
+        ..Default::default()
+    };
+    assert!(!data.selected_urls(&panels, &by_host).unwrap().is_empty());
+}
+
+#[test]
+fn merge_sorted_windows() {
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(PathBuf::from("sessionstore.jsonlz4")));
+    let original = sort_windows_backwards(&mut harness.app.tab_groups);
+    harness.send(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 0,
+        select: true,
+    });
+    harness.send(Message::AddToMerge);
+
+    let last = original.open.len() as u32 - 1;
+    let expected = harness
+        .app
+        .loaded_data
+        .as_ref()
+        .unwrap()
+        .merge_source(
+            &original,
+            &host::GenerateOptions {
+                open_group_indexes: Some(vec![last]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let sources = harness.app.merge.sources();
+    assert_eq!(sources[0].group_count(), 1);
+    assert_eq!(sources[0].tab_count(), expected.tab_count());
+    // The first window has a different number of tabs in the generated session:
+    assert!(!original.open[0]
+        .name
+        .ends_with(&format!("({})", expected.tab_count())));
+}
+
+/// An empty folder that a test can write files to.
+fn temp_folder(test_name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!(
+        "firefox-session-ui-iced-app-{}-{test_name}",
+        std::process::id()
+    ));
+    std::fs::remove_dir_all(&folder).ok();
+    std::fs::create_dir_all(&folder).expect("failed to create temporary folder");
+    folder
+}
+
+#[test]
+fn recover_from_missing_input_file() {
+    let mut harness = Harness::new();
+    harness.send(Message::SetInputPath(
+        temp_folder("missing_input")
+            .join("missing.jsonlz4")
+            .to_string_lossy()
+            .into_owned(),
+        None,
+    ));
+    harness.settle(Message::LoadInputData);
+    assert!(
+        harness.app.status.starts_with("Failed to read file: "),
+        "{}",
+        harness.app.status
+    );
+    assert!(harness
+        .app
+        .recovery_actions
+        .contains(&Recovery::ChooseAnotherFile));
+
+    // Trying again forgets about the old error:
+    harness.send(Message::LoadInputData);
+    assert!(harness.app.recovery_actions.is_empty());
+}
+
+#[test]
+fn recover_by_loading_other_session_file() {
+    let profile = temp_folder("other_session_file");
+    let backups = profile.join("sessionstore-backups");
+    std::fs::create_dir_all(&backups).unwrap();
+    let corrupt = backups.join("corrupt.jsonlz4");
+    std::fs::write(&corrupt, b"mozLz40\0not lz4 data").unwrap();
+    let previous = backups.join("previous.jsonlz4");
+    std::fs::copy(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plain.jsonlz4"),
+        &previous,
+    )
+    .unwrap();
+
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(corrupt));
+    assert!(
+        harness
+            .app
+            .status
+            .starts_with("Failed to decompress data: "),
+        "{}",
+        harness.app.status
+    );
+    assert_eq!(
+        harness.app.recovery_actions.first(),
+        Some(&Recovery::OpenFile(previous.clone()))
+    );
+
+    harness.settle(Message::Recover(Recovery::OpenFile(previous)));
+    assert_eq!(harness.app.status, "Successfully loaded session data");
+    assert!(harness.app.recovery_actions.is_empty());
+    std::fs::remove_dir_all(&profile).ok();
+}
+
+#[test]
+fn recover_from_failed_save() {
+    let folder = temp_folder("failed_save");
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plain.jsonlz4"),
+    ));
+    harness.send(Message::SetOverwrite(false));
+    harness.send(Message::SetCreateFolder(false));
+
+    // Folder doesn't exist:
+    let save_path = folder.join("links").join("links.txt");
+    harness.send(Message::SetSavePath(
+        save_path.to_string_lossy().into_owned(),
+    ));
+    harness.settle(Message::SaveLinksToFile);
+    assert_eq!(harness.app.recovery_actions, [Recovery::CreateFolder]);
+    harness.settle(Message::Recover(Recovery::CreateFolder));
+    assert_eq!(harness.app.status, "Successfully saved links to a file");
+    assert!(save_path.exists());
+
+    // File already exists:
+    harness.settle(Message::SaveLinksToFile);
+    assert_eq!(harness.app.recovery_actions, [Recovery::OverwriteFile]);
+    harness.settle(Message::Recover(Recovery::OverwriteFile));
+    assert_eq!(harness.app.status, "Successfully saved links to a file");
+    assert!(harness.app.output_options.overwrite);
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[test]
+fn favicon_data_urls() {
+    let url = host::DataUrl::parse("data:image/png;base64,iVBORw0KGgo=").unwrap();
+    assert_eq!(url.media_type, "image/png");
+    assert_eq!(url.data, b"\x89PNG\r\n\x1a\n");
+    assert!(url.is_image());
+
+    let url = host::DataUrl::parse("DATA:text/plain,a%20b").unwrap();
+    assert_eq!(url.data, b"a b");
+    assert!(!url.is_image());
+
+    assert_eq!(
+        host::DataUrl::parse("https://example.com/favicon.ico"),
+        None
+    );
+    assert_eq!(
+        host::DataUrl::parse("data:image/png;base64,not*base64"),
+        None
+    );
+}
+
+#[test]
+fn favicons_in_sidebar_and_html() {
+    let folder = temp_folder("favicons");
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plain.jsonlz4"),
+    ));
+    let favicons = harness
+        .app
+        .tab_groups
+        .open
+        .iter()
+        .chain(&harness.app.tab_groups.closed)
+        .filter(|g| g.favicon.is_some());
+    assert_eq!(favicons.count(), harness.app.group_favicons.len());
+    assert!(!harness.app.group_favicons.is_empty());
+
+    let html = host::FormatInfo::all()
+        .iter()
+        .find(|format| {
+            host::OutputOptions {
+                format: **format,
+                ..Default::default()
+            }
+            .can_embed_favicons()
+        })
+        .expect("HTML output is supported");
+    // PDF files are written without favicons:
+    assert!(!host::OutputOptions {
+        format: host::FormatInfo::PDF,
+        ..Default::default()
+    }
+    .can_embed_favicons());
+    harness.send(Message::SetOutputFormat(html.as_str()));
+    harness.send(Message::SetEmbedFavicons(true));
+    harness.send(Message::SetCreateFolder(true));
+    let save_path = folder.join("links.html");
+    harness.send(Message::SetSavePath(
+        save_path.to_string_lossy().into_owned(),
+    ));
+    harness.settle(Message::SaveLinksToFile);
+    assert_eq!(harness.app.status, "Successfully saved links to a file");
+    let written = std::fs::read_to_string(&save_path).unwrap();
+    assert!(written.contains("<img src=\"data:image/"));
+    // Icons that would be downloaded aren't embedded:
+    assert!(!written.contains("<img src=\"https:"));
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[test]
+fn save_decompressed_json() {
+    use selection::BulkSelection;
+
+    let folder = temp_folder("json");
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plain.jsonlz4"),
+    ));
+    let data = harness.app.loaded_data.as_ref().unwrap();
+    let raw = data.json_data().expect("JSON is kept after parsing");
+    harness.send(Message::SetCreateFolder(true));
+
+    harness.send(Message::SetOutputFormat("json"));
+    assert!(!harness.app.output_options.can_embed_favicons());
+    let save_path = folder.join("raw");
+    harness.send(Message::SetSavePath(
+        save_path.to_string_lossy().into_owned(),
+    ));
+    harness.settle(Message::SaveLinksToFile);
+    assert_eq!(harness.app.status, "Successfully saved JSON to a file");
+    assert_eq!(
+        std::fs::read(save_path.with_extension("json")).unwrap(),
+        *raw
+    );
+
+    harness.send(Message::SetOutputFormat("pretty json"));
+    harness.send(Message::SetJsonSelectedWindows(true));
+    harness.send(Message::SelectTabGroups(BulkSelection::Matching(
+        "window 2".to_owned(),
+    )));
+    let save_path = folder.join("pretty.json");
+    harness.send(Message::SetSavePath(
+        save_path.to_string_lossy().into_owned(),
+    ));
+    harness.settle(Message::SaveLinksToFile);
+    assert_eq!(harness.app.status, "Successfully saved JSON to a file");
+    let written = std::fs::read_to_string(&save_path).unwrap();
+    assert!(written.lines().count() > 1);
+    let written = serde_json::from_str::<serde_json::Value>(&written).unwrap();
+    let original = serde_json::from_slice::<serde_json::Value>(&raw).unwrap();
+    assert_eq!(written["windows"].as_array().unwrap().len(), 1);
+    assert_eq!(written["windows"][0], original["windows"][1]);
+    // The pattern also matches "Closed window 2":
+    assert_eq!(written["_closedWindows"].as_array().unwrap().len(), 1);
+    assert_eq!(written["_closedWindows"][0], original["_closedWindows"][1]);
+
+    // Groups that aren't windows can't be mapped to the JSON:
+    assert!(harness.app.tab_groups.can_select_json_windows());
+    harness.settle(Message::SetGroupBy(host::GroupBy::Domain));
+    assert!(!harness.app.tab_groups.can_select_json_windows());
+    harness.settle(Message::SaveLinksToFile);
+    assert!(
+        harness.app.status.starts_with("Failed to save JSON"),
+        "{}",
+        harness.app.status
+    );
+
+    // Other formats still save links:
+    harness.send(Message::SetOutputFormat("text"));
+    assert_eq!(harness.app.output_options.json, None);
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[test]
+fn bookmark_backup_folder_tree() {
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/bookmarks.jsonlz4"),
+    ));
+    let data = harness.app.loaded_data.as_ref().unwrap();
+    assert_eq!(data.source(), host::DataSource::Bookmarks);
+    assert!(data.is_parsed());
+    let raw = data.json_data().unwrap();
+    let json = serde_json::from_slice::<serde_json::Value>(&raw).unwrap();
+
+    let folders = &harness.app.tab_groups.open;
+    let names = folders
+        .iter()
+        .filter(|folder| folder.depth == 0)
+        // Names end with the number of bookmarks:
+        .map(|folder| folder.name.rsplit_once(" (").unwrap().0)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "Bookmarks Menu",
+            "Bookmarks Toolbar",
+            "Other Bookmarks",
+            "Mobile Bookmarks"
+        ]
+    );
+    assert!(harness.app.tab_groups.closed.is_empty());
+    // Sub folders follow their parent folder:
+    for pair in folders.windows(2) {
+        assert!(pair[1].depth <= pair[0].depth + 1);
+    }
+    assert!(folders.iter().any(|folder| folder.depth > 0));
+    for folder in folders {
+        let folder_json = json.pointer(folder.json_path.as_deref().unwrap()).unwrap();
+        assert_eq!(folder_json["type"], "text/x-moz-place-container");
+        if folder.depth > 0 {
+            let title = folder_json["title"].as_str().unwrap();
+            assert!(folder.name.starts_with(title));
+        }
+    }
+
+    let preview = &harness.app.preview;
+    let line = (0..preview.line_count())
+        .find(|&line| link_list::find_link(preview.line(line)).is_some())
+        .unwrap();
+    harness.send(Message::Preview(link_list::Message::ClickLine {
+        line,
+        extend: false,
+    }));
+    let tab = harness.app.inspected_tab.clone().unwrap();
+    assert!(tab.json_path.starts_with("/children/"));
+    assert_eq!(
+        json.pointer(&tab.json_path).unwrap()["uri"],
+        tab.url.as_str()
+    );
+
+    // Exported like tabs:
+    let folder = temp_folder("bookmarks");
+    let save_path = folder.join("bookmarks.txt");
+    harness.send(Message::SetOutputFormat("text"));
+    harness.send(Message::SetSavePath(
+        save_path.to_string_lossy().into_owned(),
+    ));
+    harness.settle(Message::SaveLinksToFile);
+    assert_eq!(harness.app.status, "Successfully saved links to a file");
+    let written = std::fs::read_to_string(&save_path).unwrap();
+    assert!(written.contains(&tab.url));
+    std::fs::remove_dir_all(&folder).ok();
+
+    // Bookmarks are listed again after changing how they are grouped:
+    harness.settle(Message::SetGroupBy(host::GroupBy::Domain));
+    assert!(!harness.app.tab_groups.open.is_empty());
+}
+
+#[test]
+fn other_session_manager_exports() {
+    for (file_name, source) in [
+        (
+            "tab_session_manager.json",
+            host::DataSource::TabSessionManager,
+        ),
+        ("session_buddy.json", host::DataSource::SessionBuddy),
+        ("onetab.txt", host::DataSource::OneTab),
+    ] {
+        let mut harness = Harness::new();
+        harness.settle(Message::OpenFile(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join(file_name),
+        ));
+        let data = harness.app.loaded_data.as_ref().unwrap();
+        assert_eq!(data.source(), source, "{file_name}");
+        assert_eq!(data.json_data().is_some(), source.is_json(), "{file_name}");
+        let groups = &harness.app.tab_groups.open;
+        assert!(!groups.is_empty(), "{file_name}");
+        assert!(
+            groups
+                .iter()
+                .all(|group| group.json_path.is_some() == source.is_json()),
+            "{file_name}"
+        );
+
+        // Tabs can be regrouped and inspected like tabs from a session:
+        harness.settle(Message::SetGroupBy(host::GroupBy::Domain));
+        harness.settle(Message::SelectTabGroups(selection::BulkSelection::All));
+        let preview = &harness.app.preview;
+        let line = (0..preview.line_count())
+            .find(|&line| link_list::find_link(preview.line(line)).is_some())
+            .unwrap();
+        harness.send(Message::Preview(link_list::Message::ClickLine {
+            line,
+            extend: false,
+        }));
+        let tab = harness.app.inspected_tab.clone().unwrap();
+        if let Some(raw) = harness.app.loaded_data.as_ref().unwrap().json_data() {
+            let json = serde_json::from_slice::<serde_json::Value>(&raw).unwrap();
+            assert_eq!(
+                json.pointer(&tab.json_path).unwrap()["url"],
+                tab.url.as_str(),
+                "{file_name}"
+            );
+        }
+    }
+}
+
+#[test]
+fn tab_session_manager_windows_are_sorted_by_id() {
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tab_session_manager.json"),
+    ));
+    let raw = harness
+        .app
+        .loaded_data
+        .as_ref()
+        .unwrap()
+        .json_data()
+        .unwrap();
+    let json = serde_json::from_slice::<serde_json::Value>(&raw).unwrap();
+    let paths = harness
+        .app
+        .tab_groups
+        .open
+        .iter()
+        .map(|group| group.json_path.clone().unwrap())
+        .collect::<Vec<_>>();
+    let mut expected = Vec::new();
+    for (ix, session) in json.as_array().unwrap().iter().enumerate() {
+        let mut ids = session["windows"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|id| id.parse::<u64>().unwrap())
+            .collect::<Vec<_>>();
+        ids.sort();
+        expected.extend(ids.iter().map(|id| format!("/{ix}/windows/{id}")));
+    }
+    assert_eq!(paths, expected);
+}
+
+#[test]
+fn chromium_session_file() {
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/chromium_session"),
+    ));
+    let data = harness.app.loaded_data.as_ref().unwrap();
+    assert_eq!(data.source(), host::DataSource::Chromium);
+    assert!(data.json_data().is_none());
+    let groups = &harness.app.tab_groups;
+    assert!(!groups.open.is_empty());
+    assert!(groups
+        .open
+        .iter()
+        .chain(&groups.closed)
+        .all(|group| group.json_path.is_none()));
+
+    let preview = &harness.app.preview;
+    let line = (0..preview.line_count())
+        .find(|&line| link_list::find_link(preview.line(line)).is_some())
+        .unwrap();
+    harness.send(Message::Preview(link_list::Message::ClickLine {
+        line,
+        extend: false,
+    }));
+    let tab = harness.app.inspected_tab.clone().unwrap();
+    assert!(tab.history_length >= 1);
+
+    let folder = temp_folder("chromium");
+    let save_path = folder.join("links.md");
+    harness.send(Message::SetOutputFormat("markdown"));
+    harness.send(Message::SetSavePath(
+        save_path.to_string_lossy().into_owned(),
+    ));
+    harness.settle(Message::SaveLinksToFile);
+    assert_eq!(harness.app.status, "Successfully saved links to a file");
+    assert!(std::fs::read_to_string(&save_path)
+        .unwrap()
+        .contains(&tab.url));
+
+    // Only firefox_session_data writes PDF files, so PDF isn't offered:
+    assert!(harness.app.uses_own_grouping());
+    assert!(!host::OutputOptions::format_names(true).contains(&"pdf"));
+    let save_path = folder.join("links.pdf");
+    harness.send(Message::SetOutputFormat("pdf"));
+    harness.send(Message::SetSavePath(
+        save_path.to_string_lossy().into_owned(),
+    ));
+    harness.settle(Message::SaveLinksToFile);
+    assert!(
+        harness
+            .app
+            .status
+            .starts_with("Failed to save links to file: the \"pdf\" format"),
+        "{}",
+        harness.app.status
+    );
+    assert!(!save_path.exists());
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[test]
+fn merge_sessions_into_one_export() {
+    use selection::BulkSelection;
+
+    let folder = temp_folder("merge");
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(fixtures.join("plain.jsonlz4")));
+    harness.send(Message::SelectTabGroups(BulkSelection::Matching(
+        "window 1".to_owned(),
+    )));
+    harness.send(Message::AddToMerge);
+    assert!(harness.app.merge.view().is_some());
+    let first = harness.app.merge.sources()[0].tab_count();
+
+    harness.send(Message::Merge(merge::Message::Hide));
+    harness.settle(Message::OpenFile(fixtures.join("onetab.txt")));
+    harness.send(Message::AddToMerge);
+    harness.send(Message::Merge(merge::Message::SetName(
+        1,
+        "From OneTab".to_owned(),
+    )));
+    harness.send(Message::Merge(merge::Message::MoveUp(1)));
+    // Adding the same file again replaces its windows but keeps the heading:
+    harness.send(Message::AddToMerge);
+    let sources = harness.app.merge.sources();
+    assert_eq!(
+        sources
+            .iter()
+            .map(|source| source.name.as_str())
+            .collect::<Vec<_>>(),
+        ["From OneTab", "fixtures/plain.jsonlz4"]
+    );
+    assert_eq!(sources[1].tab_count(), first);
+    let tab_count = sources
+        .iter()
+        .map(host::MergeSource::tab_count)
+        .sum::<usize>();
+
+    harness.send(Message::Merge(merge::Message::SetDeduplicate(false)));
+    harness.send(Message::SetOutputFormat("markdown"));
+    let save_path = folder.join("merged.md");
+    harness.send(Message::SetSavePath(
+        save_path.to_string_lossy().into_owned(),
+    ));
+    harness.settle(Message::Merge(merge::Message::Save));
+    assert_eq!(
+        harness.app.status,
+        "Successfully saved merged links to a file"
+    );
+    assert!(harness.app.merge.view().is_none());
+    let written = std::fs::read_to_string(&save_path).unwrap();
+    let onetab = written.find("\n# From OneTab\n").unwrap();
+    assert!(onetab < written.find("\n# fixtures/plain.jsonlz4\n").unwrap());
+    assert!(written.contains("**From OneTab**"));
+    assert!(written.contains("\n## Group 1\n"));
+    assert_eq!(
+        written
+            .lines()
+            .filter(|line| line.starts_with("- ["))
+            .count(),
+        tab_count
+    );
+
+    // Only firefox_session_data writes PDF files:
+    let save_path = folder.join("merged.pdf");
+    harness.send(Message::SetOutputFormat("pdf"));
+    harness.send(Message::SetSavePath(
+        save_path.to_string_lossy().into_owned(),
+    ));
+    harness.settle(Message::Merge(merge::Message::Save));
+    assert!(
+        harness
+            .app
+            .status
+            .starts_with("Failed to save merged links to file: "),
+        "{}",
+        harness.app.status
+    );
+    assert!(!save_path.exists());
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+/// A Firefox profile folder whose session files are copies of the fixtures.
+fn test_profile(test_name: &str) -> PathBuf {
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let profile = temp_folder(test_name).join("abc.default-release");
+    let backups = profile.join("sessionstore-backups");
+    std::fs::create_dir_all(&backups).unwrap();
+    for (fixture, file) in [
+        ("plain.jsonlz4", profile.join("sessionstore.jsonlz4")),
+        ("extensions.jsonlz4", backups.join("recovery.jsonlz4")),
+        ("edge_cases.jsonlz4", backups.join("previous.jsonlz4")),
+        (
+            "plain.jsonlz4",
+            backups.join("upgrade.jsonlz4-20240101000000"),
+        ),
+    ] {
+        std::fs::copy(fixtures.join(fixture), file).unwrap();
+    }
+    // Not a session file:
+    std::fs::write(backups.join("notes.txt"), b"").unwrap();
+    // Marks the folder as a Firefox profile:
+    std::fs::write(profile.join("times.json"), b"{}").unwrap();
+    profile
+}
+
+#[test]
+fn session_history_timeline() {
+    let profile = test_profile("timeline");
+    let backups = profile.join("sessionstore-backups");
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(backups.join("recovery.jsonlz4")));
+    harness.settle(Message::ShowTimeline);
+    assert!(harness.app.timeline.view().is_some());
+    let timeline = harness.app.timeline.timeline().unwrap();
+    assert!(timeline.failed.is_empty(), "{:?}", timeline.failed);
+    let snapshots = &timeline.snapshots;
+    assert_eq!(snapshots.len(), 4);
+    assert!(snapshots
+        .windows(2)
+        .all(|pair| pair[0].time <= pair[1].time));
+    assert!(snapshots.iter().any(|snapshot| snapshot
+        .file_path
+        .ends_with("upgrade.jsonlz4-20240101000000")));
+    assert!(
+        host::FileInfo::new(backups.join("upgrade.jsonlz4-20240101000000"))
+            .is_compressed_file_format()
+    );
+    // The newest session is selected first:
+    assert_eq!(harness.app.timeline.position(), 3);
+
+    let found = timeline.find_tabs("https://");
+    assert!(!found.is_empty());
+    for tab in &found {
+        assert!(snapshots[tab.first_seen].contains(&tab.url));
+        assert!(snapshots[tab.last_seen].contains(&tab.url));
+        assert!(snapshots[..tab.first_seen]
+            .iter()
+            .all(|snapshot| !snapshot.contains(&tab.url)));
+        assert!(snapshots[tab.last_seen + 1..]
+            .iter()
+            .all(|snapshot| !snapshot.contains(&tab.url)));
+    }
+    let oldest = snapshots[0].file_path.clone();
+
+    harness.send(Message::Timeline(timeline::Message::SetPosition(0)));
+    assert_eq!(harness.app.timeline.position(), 0);
+    harness.settle(Message::Timeline(timeline::Message::Open(oldest.clone())));
+    assert!(harness.app.timeline.view().is_none());
+    assert_eq!(
+        harness
+            .app
+            .loaded_data
+            .as_ref()
+            .map(|data| data.file_path.as_path()),
+        Some(oldest.as_path())
+    );
+    std::fs::remove_dir_all(profile.parent().unwrap()).ok();
+}
+
+#[test]
+fn status_log_keeps_errors_after_status_changes() {
+    let mut harness = Harness::new();
+    harness.send(Message::Failed(
+        "Failed to save links to file",
+        host::Error::NotReady {
+            step: "load a session first",
+        },
+    ));
+    harness.send(Message::SetStatus("Opened tabs in browser".to_owned()));
+    harness.send(Message::LogEntry(status_log::Entry::new(
+        status_log::Severity::Warning,
+        "Failed to save recent files",
+    )));
+    assert_eq!(harness.app.status, "Opened tabs in browser");
+
+    let log = &harness.app.status_log;
+    assert_eq!(
+        log.entries()
+            .map(|entry| (entry.severity, entry.message.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            (
+                status_log::Severity::Error,
+                "Failed to save links to file: must load a session first"
+            ),
+            (status_log::Severity::Info, "Opened tabs in browser"),
+            (status_log::Severity::Warning, "Failed to save recent files"),
+        ]
+    );
+    assert_eq!(log.count_at_least(status_log::Severity::Warning), 2);
+    assert_eq!(log.to_text().lines().count(), 3);
+
+    assert_eq!(harness.run(Message::CopyStatusLog).actions, 2);
+    harness.send(Message::ClearStatusLog);
+    assert!(harness.app.status_log.is_empty());
+}
+
+/// The results of each [`selection::BulkSelection`] are tested in the
+/// `selection` module, this only checks how the app reacts to them.
+#[test]
+fn bulk_selection() {
+    use selection::BulkSelection;
+
+    let mut harness = Harness::with_groups();
+    assert!(harness.send(Message::SelectTabGroups(BulkSelection::All)));
+    assert_eq!(harness.selection(), (Some(vec![0, 1]), Some(vec![0])));
+    // Already selected:
+    assert!(!harness.send(Message::SelectTabGroups(BulkSelection::All)));
+
+    // No match keeps the selection:
+    assert!(
+        !harness.send(Message::SelectTabGroups(BulkSelection::Matching(
+            "nothing".to_owned()
+        )))
+    );
+    assert_eq!(harness.selection(), (Some(vec![0, 1]), Some(vec![0])));
+    assert_eq!(harness.app.status, "No windows match \"nothing\"");
+}
+
+#[test]
+fn shift_click_selects_range() {
+    let mut harness = Harness::with_groups();
+    harness.app.tab_groups.open.push(host::TabGroup {
+        index: 3,
+        name: "Window 3".to_owned(),
+        color: None,
+        sub_groups: Vec::new(),
+        favicon: None,
+        depth: 0,
+        json_path: None,
+    });
+    assert!(harness.send(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 0,
+        select: true,
+    }));
+    harness.send(Message::ModifiersChanged(iced::keyboard::Modifiers::SHIFT));
+    assert!(harness.send(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 2,
+        select: true,
+    }));
+    assert_eq!(harness.selection(), (Some(vec![0, 1, 2]), Some(vec![])));
+
+    // Ranges can also unselect groups, the first clicked group is still the
+    // start of the range:
+    assert!(harness.send(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 1,
+        select: false,
+    }));
+    assert_eq!(harness.selection(), (Some(vec![2]), Some(vec![])));
+
+    // Not a range between open and closed windows:
+    assert!(harness.send(Message::ChangeTabGroupSelection {
+        open: false,
+        index: 0,
+        select: true,
+    }));
+    assert_eq!(harness.selection(), (Some(vec![2]), Some(vec![0])));
+}
+
+#[test]
+fn presets_select_windows_by_name() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plain.jsonlz4");
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(path.clone()));
+    harness.settle(Message::SetGroupBy(host::GroupBy::Domain));
+    let last = harness.app.tab_groups.open.len() as u32 - 1;
+    let selected_name = harness.app.tab_groups.open[last as usize].name.clone();
+    harness.settle(Message::ChangeTabGroupSelection {
+        open: true,
+        index: last,
+        select: true,
+    });
+    harness.send(Message::SetOutputFormat("markdown"));
+    harness.send(Message::SetPresetName("Weekly".to_owned()));
+    harness.send(Message::SavePreset);
+    assert_eq!(harness.app.presets.names(), ["Weekly"]);
+
+    // Applying the preset regroups the tabs and then finds the group by name:
+    harness.settle(Message::SetGroupBy(host::GroupBy::Window));
+    harness.send(Message::SetOutputFormat("text"));
+    harness.settle(Message::ApplyPreset("Weekly".to_owned()));
+    assert_eq!(
+        harness.app.selected_tab_groups.group_by,
+        host::GroupBy::Domain
+    );
+    assert_eq!(harness.selection(), (Some(vec![last]), Some(vec![])));
+    assert_eq!(harness.app.output_options.format.as_str(), "markdown");
+
+    // A preset can be chosen before the session is loaded, for example from
+    // the command line:
+    let presets = harness.app.presets.clone();
+    let mut harness = Harness::new();
+    harness.app.presets = presets;
+    harness.settle(Message::ApplyPreset("Weekly".to_owned()));
+    harness.settle(Message::OpenFile(path));
+    assert_eq!(
+        harness.app.tab_groups.open[last as usize].name,
+        selected_name
+    );
+    assert_eq!(harness.selection(), (Some(vec![last]), Some(vec![])));
+    assert!(harness.app.pending_preset.is_none());
+
+    harness.send(Message::DeletePreset);
+    assert!(harness.app.presets.names().is_empty());
+}
+
+#[test]
+fn command_line_arguments() {
+    let parse = |args: &[&str]| cli::Args::parse(args.iter().map(Into::into));
+    assert_eq!(
+        parse(&["--preset", "Weekly", "session.jsonlz4"]),
+        Ok(cli::Args {
+            file: Some(PathBuf::from("session.jsonlz4")),
+            preset: Some("Weekly".to_owned()),
+            help: false,
+            mozlz4: None,
+        })
+    );
+    assert_eq!(
+        parse(&["--preset=Weekly"]).map(|args| args.preset),
+        Ok(Some("Weekly".to_owned()))
+    );
+    assert!(parse(&["--preset"]).is_err());
+    assert!(parse(&["--unknown"]).is_err());
+    assert!(parse(&["a.jsonlz4", "b.jsonlz4"]).is_err());
+}
+
+#[test]
+fn mozlz4_command_line() {
+    use host::Mozlz4Mode::{Compress, Decompress};
+
+    let parse = |args: &[&str]| cli::Args::parse(args.iter().map(Into::into));
+    assert_eq!(
+        parse(&["mozlz4", "decompress", "search.json.mozlz4"]).map(|args| args.mozlz4),
+        Ok(Some(cli::Mozlz4Args {
+            mode: Decompress,
+            input: PathBuf::from("search.json.mozlz4"),
+            output: None,
+            force: false,
+        }))
+    );
+    assert_eq!(
+        parse(&["mozlz4", "compress", "--force", "a.json", "b.jsonlz4"]).map(|args| args.mozlz4),
+        Ok(Some(cli::Mozlz4Args {
+            mode: Compress,
+            input: PathBuf::from("a.json"),
+            output: Some(PathBuf::from("b.jsonlz4")),
+            force: true,
+        }))
+    );
+    assert!(parse(&["mozlz4", "--help"]).is_ok_and(|args| args.help));
+    assert!(parse(&["mozlz4"]).is_err());
+    assert!(parse(&["mozlz4", "extract", "a"]).is_err());
+    assert!(parse(&["mozlz4", "compress"]).is_err());
+    assert!(parse(&["mozlz4", "compress", "a", "b", "c"]).is_err());
+
+    let output = |mode: host::Mozlz4Mode, input: &str| mode.default_output_path(input.as_ref());
+    assert_eq!(
+        output(Decompress, "/profile/search.json.mozlz4"),
+        PathBuf::from("/profile/search.json")
+    );
+    assert_eq!(
+        output(Decompress, "addonStartup.json.lz4"),
+        PathBuf::from("addonStartup.json")
+    );
+    assert_eq!(
+        output(Decompress, "sessionstore.jsonlz4"),
+        PathBuf::from("sessionstore.json")
+    );
+    assert_eq!(output(Decompress, "data"), PathBuf::from("data.json"));
+    assert_eq!(
+        output(Compress, "search.json"),
+        PathBuf::from("search.json.mozlz4")
+    );
+}
+
+#[test]
+fn mozlz4_round_trip() {
+    use host::Mozlz4Mode::{Compress, Decompress};
+
+    let folder = temp_folder("mozlz4");
+    let json = folder.join("search.json");
+    let content = r#"{"engines":[{"name":"DuckDuckGo"},{"name":"DuckDuckGo"}]}"#.repeat(50);
+    std::fs::write(&json, &content).unwrap();
+
+    let compressed = host::convert_mozlz4_file(Compress, &json, None, false).unwrap();
+    assert_eq!(compressed, folder.join("search.json.mozlz4"));
+    let data = std::fs::read(&compressed).unwrap();
+    assert!(data.starts_with(b"mozLz40\0"));
+    assert!(data.len() < content.len());
+
+    // Don't overwrite the original file without permission:
+    assert!(matches!(
+        host::convert_mozlz4_file(Decompress, &compressed, None, false),
+        Err(host::Error::AlreadyExists { .. })
+    ));
+    std::fs::remove_file(&json).unwrap();
+    assert_eq!(
+        host::convert_mozlz4_file(Decompress, &compressed, None, false).unwrap(),
+        json
+    );
+    assert_eq!(std::fs::read_to_string(&json).unwrap(), content);
+
+    // Plain JSON isn't mozlz4 data:
+    let error = host::convert_mozlz4_file(Decompress, &json, None, true).unwrap_err();
+    assert!(matches!(error, host::Error::NotMozlz4 { .. }));
+    assert!(error.to_string().contains("isn't mozlz4 compressed"));
+
+    // Truncated data is reported as corrupt:
+    std::fs::write(&compressed, &data[..data.len() / 2]).unwrap();
+    assert!(matches!(
+        host::convert_mozlz4_file(Decompress, &compressed, None, true),
+        Err(host::Error::Decompress { .. })
+    ));
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[test]
+fn mozlz4_tool_modal() {
+    let folder = temp_folder("mozlz4-tool");
+    let input = folder.join("addonStartup.json");
+    std::fs::write(&input, "{}").unwrap();
+
+    let mut harness = Harness::new();
+    harness.send(Message::Mozlz4Tool(mozlz4_tool::Message::Show));
+    harness.send(Message::Mozlz4Tool(mozlz4_tool::Message::SetMode(
+        host::Mozlz4Mode::Compress,
+    )));
+    harness.send(Message::Mozlz4Tool(mozlz4_tool::Message::SetInput(
+        input.to_string_lossy().into_owned(),
+    )));
+    harness.send(Message::Mozlz4Tool(mozlz4_tool::Message::SetOutput(
+        folder
+            .join("addonStartup.json.lz4")
+            .to_string_lossy()
+            .into_owned(),
+    )));
+    harness.settle(Message::Mozlz4Tool(mozlz4_tool::Message::Convert));
+    // Stays open so that more files can be converted:
+    assert!(harness.app.mozlz4_tool.view().is_some());
+    let data = std::fs::read(folder.join("addonStartup.json.lz4")).unwrap();
+    assert!(data.starts_with(b"mozLz40\0"));
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[test]
+fn stale_previews_are_discarded() {
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plain.jsonlz4"),
+    ));
+    let before = harness.app.preview.text().to_owned();
+    let first = harness.app.preview_generation + 1;
+    for select in [true, false] {
+        assert!(harness.send(Message::ChangeTabGroupSelection {
+            open: true,
+            index: 0,
+            select,
+        }));
+    }
+    let latest = harness.app.preview_generation;
+    assert_eq!(latest, first + 1);
+
+    // The first change was superseded before its delay ended:
+    assert!(harness
+        .run(Message::GeneratePreview(first))
+        .messages
+        .is_empty());
+    let output = harness.run(Message::GeneratePreview(latest));
+    let [Message::PreviewGenerated(generation, Ok(text))] = &output.messages[..] else {
+        panic!("expected a preview but got {:?}", output.messages);
+    };
+    assert_eq!(*generation, latest);
+    let text = text.clone();
+
+    // Results from older generations don't overwrite newer ones:
+    harness.send(Message::PreviewGenerated(first, Ok("stale".to_owned())));
+    assert_eq!(harness.app.preview.text(), before);
+    harness.send(Message::PreviewGenerated(latest, Ok(text.clone())));
+    assert_eq!(harness.app.preview.text().trim_end(), text.trim_end());
+}
+
+#[test]
+fn preview_reuses_text_of_cached_groups() {
+    let mut harness = Harness::new();
+    harness.settle(Message::OpenFile(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plain.jsonlz4"),
+    ));
+    // Grouped by our own code so that each group is cached:
+    harness.settle(Message::SetGroupBy(host::GroupBy::Domain));
+    harness.settle(Message::SelectTabGroups(selection::BulkSelection::All));
+    let group_count = harness.app.tab_groups.open.len() + harness.app.tab_groups.closed.len();
+    assert_eq!(harness.app.preview_cache.cached_groups(), group_count);
+
+    harness.settle(Message::SelectTabGroups(selection::BulkSelection::Invert));
+    harness.settle(Message::ChangeTabGroupSelection {
+        open: true,
+        index: 0,
+        select: true,
+    });
+    assert_eq!(harness.app.preview_cache.cached_groups(), group_count);
+
+    // Same output as converting everything again:
+    let data = harness.app.loaded_data.clone().unwrap();
+    let expected = harness
+        .runtime
+        .block_on(data.to_text_links(harness.app.selected_tab_groups.clone()))
+        .unwrap();
+    assert_eq!(harness.app.preview.text().trim_end(), expected.trim_end());
+
+    // Other options convert the groups again, only the implicitly selected
+    // open groups are shown after regrouping:
+    harness.settle(Message::SetGroupBy(host::GroupBy::Host));
+    assert_eq!(
+        harness.app.preview_cache.cached_groups(),
+        harness.app.tab_groups.open.len()
+    );
+}
+
+#[test]
+fn keyboard_shortcuts() {
+    use iced::keyboard::{key::Named, Key, Modifiers};
+
+    let key = |c: &str| Key::Character(c.into());
+    assert_eq!(
+        commands::command_for_key(&key("s"), Modifiers::CTRL, false),
+        Some(commands::Command::SaveLinks)
+    );
+    // Shift can make the character upper case:
+    assert_eq!(
+        commands::command_for_key(&key("A"), Modifiers::CTRL | Modifiers::SHIFT, false),
+        Some(commands::Command::SelectNoWindows)
+    );
+    assert_eq!(
+        commands::command_for_key(&Key::Named(Named::F5), Modifiers::empty(), true),
+        Some(commands::Command::ReloadFile)
+    );
+    // Text fields use Ctrl+A to select text:
+    assert_eq!(
+        commands::command_for_key(&key("a"), Modifiers::CTRL, true),
+        None
+    );
+    assert_eq!(
+        commands::command_for_key(&key("s"), Modifiers::empty(), false),
+        None
+    );
+}
+
+#[test]
+fn command_palette_fuzzy_search() {
+    assert!(commands::fuzzy_score("svlnk", "Save links to file").is_some());
+    assert!(commands::fuzzy_score("links save", "Save links to file").is_none());
+    // Word starts and consecutive characters are preferred:
+    assert!(
+        commands::fuzzy_score("sl", "Save links to file")
+            > commands::fuzzy_score("sl", "Select all windows")
+    );
+
+    let mut harness = Harness::with_groups();
+    harness.send(Message::RunCommand(commands::Command::ShowCommandPalette));
+    assert!(harness.app.command_palette.view().is_some());
+    harness.send(Message::CommandPalette(commands::Message::SetQuery(
+        "group dom".to_owned(),
+    )));
+    assert_eq!(
+        harness.app.command_palette.matches().first(),
+        Some(&commands::Command::GroupBy(host::GroupBy::Domain))
+    );
+    harness.send(Message::CommandPalette(commands::Message::Submit));
+    assert!(harness.app.command_palette.view().is_none());
+    assert_eq!(
+        harness.app.selected_tab_groups.group_by,
+        host::GroupBy::Domain
+    );
+}
+
+#[cfg(feature = "iced_skia")]
+mod view {
+    use super::*;
+
+    use iced::{Point, Size};
+    use iced_runtime::core::{clipboard, mouse, renderer};
+    use iced_runtime::user_interface::{Cache, UserInterface};
+
+    const WINDOW_SIZE: Size = Size::new(1024.0, 768.0);
+
+    fn headless_renderer() -> iced::Renderer {
+        let renderer = iced_tiny_skia::Renderer::new(iced::Font::DEFAULT, iced::Pixels(16.0));
+        #[cfg(feature = "iced_wgpu")]
+        let renderer = iced::Renderer::Secondary(renderer);
+        renderer
+    }
+
+    /// Lay out and draw the app's UI, then click at each point and return the
+    /// messages that were emitted by each click.
+    fn click(app: &SessionDataUtility, points: &[Point]) -> Vec<(Point, Vec<Message>)> {
+        let mut renderer = headless_renderer();
+        let mut ui = UserInterface::build(app.view(), WINDOW_SIZE, Cache::new(), &mut renderer);
+        ui.draw(
+            &mut renderer,
+            &app.theme(),
+            &renderer::Style {
+                text_color: app.theme().palette().text,
+            },
+            mouse::Cursor::Unavailable,
+        );
+
+        points
+            .iter()
+            .map(|&point| {
+                let mut messages = Vec::new();
+                ui.update(
+                    &[
+                        iced::Event::Mouse(mouse::Event::CursorMoved { position: point }),
+                        iced::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)),
+                        iced::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)),
+                    ],
+                    mouse::Cursor::Available(point),
+                    &mut renderer,
+                    &mut clipboard::Null,
+                    &mut messages,
+                );
+                (point, messages)
+            })
+            .collect()
+    }
+
+    /// Points spread out over the whole window.
+    fn grid() -> Vec<Point> {
+        let step = 10.0;
+        (0..(WINDOW_SIZE.height / step) as u32)
+            .flat_map(|y| {
+                (0..(WINDOW_SIZE.width / step) as u32)
+                    .map(move |x| Point::new(x as f32 * step + 5.0, y as f32 * step + 5.0))
+            })
+            .collect()
+    }
+
+    /// Find a place in the UI that emits a certain message when clicked.
+    fn find(app: &SessionDataUtility, is_match: impl Fn(&Message) -> bool) -> Option<Point> {
+        click(app, &grid())
+            .into_iter()
+            .find(|(_, messages)| messages.iter().any(&is_match))
+            .map(|(point, _)| point)
+    }
+
+    #[test]
+    fn start_screen() {
+        let harness = Harness::new();
+        assert!(find(&harness.app, |m| matches!(m, Message::BrowseInputPath)).is_some());
+        assert!(find(&harness.app, |m| matches!(m, Message::LoadInputData)).is_some());
+        // Nothing selected and nothing loaded:
+        assert!(find(&harness.app, |m| matches!(
+            m,
+            Message::ChangeTabGroupSelection { .. }
+        ))
+        .is_none());
+    }
+
+    #[test]
+    fn sidebar_lists_tab_groups() {
+        let mut harness = Harness::with_groups();
+        harness.app.tab_groups.open[1].sub_groups = vec![host::NativeTabGroup {
+            name: "Research".to_owned(),
+            color: Some([0x80, 0x00, 0x80]),
+            collapsed: true,
+            tab_count: 2,
+        }];
+
+        for (open, index) in [(true, 0), (true, 1), (false, 0)] {
+            let point = find(&harness.app, |m| {
+                matches!(
+                    m,
+                    Message::ChangeTabGroupSelection { open: o, index: i, select: true }
+                        if *o == open && *i == index
+                )
+            })
+            .unwrap_or_else(|| panic!("no button to select group {index} (open: {open})"));
+            // The sidebar is on the left:
+            assert!(point.x < WINDOW_SIZE.width / 2.0);
+        }
+
+        // Selected groups are unselected when clicked:
+        harness.send(Message::ChangeTabGroupSelection {
+            open: false,
+            index: 0,
+            select: true,
+        });
+        assert!(find(&harness.app, |m| matches!(
+            m,
+            Message::ChangeTabGroupSelection {
+                open: false,
+                index: 0,
+                select: false
+            }
+        ))
+        .is_some());
+    }
+
+    #[test]
+    fn clicking_outside_modal_closes_it() {
+        let mut harness = Harness::new();
+        harness.send(Message::FirefoxProfileWizard(wizard::Message::Show));
+        let clicks = click(&harness.app, &[Point::new(2.0, 2.0)]);
+        assert!(matches!(
+            &clicks[0].1[..],
+            [Message::FirefoxProfileWizard(wizard::Message::Hide)]
+        ));
+
+        let mut harness = Harness::with_groups();
+        harness.app.pending_open_urls = Some(vec![vec!["http://www.example.com".to_owned()]; 30]);
+        let clicks = click(&harness.app, &[Point::new(2.0, 2.0)]);
+        assert!(matches!(
+            &clicks[0].1[..],
+            [Message::ConfirmOpenTabs(false)]
+        ));
+        assert!(find(&harness.app, |m| matches!(
+            m,
+            Message::ConfirmOpenTabs(true)
+        ))
+        .is_some());
+    }
+
+    #[test]
+    fn timeline_bars_select_sessions() {
+        let profile = test_profile("timeline_view");
+        let mut harness = Harness::new();
+        harness.settle(Message::OpenFile(profile.join("sessionstore.jsonlz4")));
+        harness.settle(Message::ShowTimeline);
+        let bar = find(&harness.app, |m| {
+            matches!(m, Message::Timeline(timeline::Message::SetPosition(0)))
+        });
+        assert!(bar.is_some());
+        assert!(find(&harness.app, |m| matches!(
+            m,
+            Message::Timeline(timeline::Message::Open(_))
+        ))
+        .is_some());
+        std::fs::remove_dir_all(profile.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn status_log_panel() {
+        let mut harness = Harness::with_groups();
+        let toggle = find(&harness.app, |m| matches!(m, Message::ToggleStatusLog));
+        assert!(toggle.is_some());
+        assert!(find(&harness.app, |m| matches!(m, Message::SaveStatusLog)).is_none());
+
+        harness.send(Message::ToggleStatusLog);
+        assert!(find(&harness.app, |m| matches!(m, Message::CopyStatusLog)).is_some());
+        assert!(find(&harness.app, |m| matches!(m, Message::SaveStatusLog)).is_some());
+    }
+
+    #[test]
+    fn preview_links_can_be_clicked() {
+        let mut harness = Harness::with_groups();
+        harness.send(Message::SetPreview(
+            "Window 1\n- [Tab](https://example.com/)\n".to_owned(),
+        ));
+        assert!(find(&harness.app, |m| matches!(
+            m,
+            Message::Preview(link_list::Message::OpenLink(url)) if url == "https://example.com/"
+        ))
+        .is_some());
+        assert!(find(&harness.app, |m| matches!(
+            m,
+            Message::Preview(link_list::Message::ClickLine { line: 0, .. })
+        ))
+        .is_some());
+    }
+}
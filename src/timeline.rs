@@ -0,0 +1,317 @@
+//! A modal window that reads every session file of a Firefox profile and shows
+//! how many windows and tabs were open over time.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use iced::widget::{
+    button, column, container, horizontal_space, mouse_area, row, scrollable, slider, text,
+    text_input, vertical_space,
+};
+use iced::{Alignment, Element, Length, Task};
+
+use crate::host::{self, FirefoxProfileInfo, Timeline};
+use crate::status_log::format_utc;
+
+/// Height of the bar for the snapshot with the most tabs.
+const BAR_HEIGHT: f32 = 80.0;
+
+/// Don't list more tabs than this for a snapshot or a search.
+const MAX_LISTED_TABS: usize = 10;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Hide,
+    /// Read the session files of a profile.
+    Load(FirefoxProfileInfo),
+    Loaded(Arc<Timeline>),
+    /// Select a snapshot by its index.
+    SetPosition(usize),
+    SetQuery(String),
+    /// Copy the profile's session files to the archive folder.
+    Archive,
+    Archived(Result<usize, host::Error>),
+    /// Handled by the main window, which loads the file.
+    Open(PathBuf),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    active: bool,
+    /// Where session files are archived, [`None`] if archiving isn't
+    /// possible.
+    archive: Option<PathBuf>,
+    profile: Option<FirefoxProfileInfo>,
+    loading: bool,
+    timeline: Option<Arc<Timeline>>,
+    /// Index of the selected snapshot.
+    position: usize,
+    /// Find tabs whose URL or title contains this.
+    query: String,
+    /// Result of the last archiving.
+    status: Option<Result<String, String>>,
+}
+impl State {
+    pub fn new() -> Self {
+        Self {
+            archive: host::archive_folder(),
+            ..Self::default()
+        }
+    }
+
+    pub fn timeline(&self) -> Option<&Timeline> {
+        self.timeline.as_deref()
+    }
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Hide => self.active = false,
+            Message::Load(profile) => {
+                self.active = true;
+                self.loading = true;
+                self.profile = Some(profile.clone());
+                return Task::perform(
+                    host::load_timeline(profile, self.archive.clone()),
+                    |timeline| Message::Loaded(Arc::new(timeline)),
+                );
+            }
+            Message::Loaded(timeline) => {
+                self.loading = false;
+                // Show the newest session:
+                self.position = timeline.snapshots.len().saturating_sub(1);
+                self.timeline = Some(timeline);
+            }
+            Message::SetPosition(position) => self.position = position,
+            Message::SetQuery(query) => self.query = query,
+            Message::Archive => {
+                let (Some(profile), Some(archive)) = (self.profile.clone(), self.archive.clone())
+                else {
+                    return Task::none();
+                };
+                self.status = None;
+                return Task::perform(
+                    host::archive_session_files(profile, archive),
+                    Message::Archived,
+                );
+            }
+            Message::Archived(result) => {
+                self.status = Some(match result {
+                    Ok(copied) => Ok(format!("Archived {copied} session files")),
+                    Err(e) => Err(format!("Failed to archive session files: {e}")),
+                });
+                if let Some(profile) = self.profile.clone() {
+                    return self.update(Message::Load(profile));
+                }
+            }
+            Message::Open(_) => self.active = false,
+        }
+        Task::none()
+    }
+
+    /// Bars with the number of tabs in each snapshot, click one to select it.
+    fn view_chart<'a>(&self, timeline: &'a Timeline) -> Element<'a, Message> {
+        let most_tabs = timeline
+            .snapshots
+            .iter()
+            .map(|snapshot| snapshot.tab_count)
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        let bars = timeline.snapshots.iter().enumerate().map(|(ix, snapshot)| {
+            let selected = ix == self.position;
+            let height = BAR_HEIGHT * snapshot.tab_count as f32 / most_tabs as f32;
+            mouse_area(
+                container(vertical_space().height(height.max(2.0)))
+                    .width(12)
+                    .style(move |theme: &iced::Theme| {
+                        let palette = theme.extended_palette();
+                        container::Style::default().background(if selected {
+                            palette.primary.strong.color
+                        } else {
+                            palette.background.strong.color
+                        })
+                    }),
+            )
+            .on_press(Message::SetPosition(ix))
+            .into()
+        });
+        scrollable(
+            row(bars)
+                .spacing(3)
+                .height(BAR_HEIGHT)
+                .align_y(Alignment::End),
+        )
+        .direction(scrollable::Direction::Horizontal(
+            scrollable::Scrollbar::default(),
+        ))
+        .into()
+    }
+
+    fn view_snapshot<'a>(&self, timeline: &'a Timeline) -> Element<'a, Message> {
+        let Some(snapshot) = timeline.snapshots.get(self.position()) else {
+            return text("No session files were found in the profile.").into();
+        };
+        let changes = self
+            .position
+            .checked_sub(1)
+            .and_then(|ix| timeline.snapshots.get(ix))
+            .map(|previous| {
+                let opened = snapshot.tabs_not_in(previous);
+                let closed = previous.tabs_not_in(snapshot);
+                let list = |sign: &str, tabs: &[(&str, &str)]| {
+                    tabs.iter()
+                        .take(MAX_LISTED_TABS)
+                        .map(|(url, title)| text(format!("{sign} {title} ({url})")).into())
+                        .collect::<Vec<_>>()
+                };
+                column![text(format!(
+                    "{} tabs opened and {} closed since the previous session",
+                    opened.len(),
+                    closed.len()
+                ))]
+                .extend(list("+", &opened))
+                .extend(list("-", &closed))
+                .spacing(2)
+            });
+        column![
+            row![
+                text(format!(
+                    "{}: {}",
+                    format_utc(snapshot.time),
+                    snapshot.file_path.display()
+                )),
+                horizontal_space(),
+                button("Open").on_press(Message::Open(snapshot.file_path.clone())),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            text(format!(
+                "{} windows, {} closed windows and {} tabs",
+                snapshot.windows, snapshot.closed_windows, snapshot.tab_count
+            )),
+        ]
+        .push_maybe(changes)
+        .spacing(5)
+        .into()
+    }
+
+    fn view_search<'a>(&self, timeline: &'a Timeline) -> Element<'a, Message> {
+        let query = self.query.trim();
+        let mut results = column![
+            text_input("Find a tab by URL or title", &self.query).on_input(Message::SetQuery)
+        ]
+        .spacing(5);
+        if query.is_empty() {
+            return results.into();
+        }
+        let found = timeline.find_tabs(query);
+        if found.is_empty() {
+            return results.push(text("No tab matches the search.")).into();
+        }
+        let time = |ix: usize| format_utc(timeline.snapshots[ix].time);
+        for tab in found.iter().take(MAX_LISTED_TABS) {
+            let gone = if tab.last_seen + 1 < timeline.snapshots.len() {
+                format!("gone by {}", time(tab.last_seen + 1))
+            } else {
+                "still open".to_owned()
+            };
+            results = results.push(
+                row![
+                    text(format!(
+                        "{} ({}): first seen {}, {gone}",
+                        tab.title,
+                        tab.url,
+                        time(tab.first_seen)
+                    ))
+                    .width(Length::Fill),
+                    button("Show").on_press(Message::SetPosition(tab.first_seen)),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            );
+        }
+        if found.len() > MAX_LISTED_TABS {
+            results = results.push(text(format!(
+                "And {} more tabs",
+                found.len() - MAX_LISTED_TABS
+            )));
+        }
+        results.into()
+    }
+
+    pub fn view(&self) -> Option<Element<'_, Message>> {
+        if !self.active {
+            return None;
+        }
+        let title = match &self.profile {
+            Some(profile) => format!("Session history of {}", profile.name()),
+            None => "Session history".to_owned(),
+        };
+        let body: Element<'_, Message> = match self.timeline() {
+            _ if self.loading => text("Reading session files...").into(),
+            None => text("No profile was selected.").into(),
+            Some(timeline) => column![self.view_chart(timeline)]
+                .push_maybe((timeline.snapshots.len() > 1).then(|| {
+                    slider(
+                        0.0..=(timeline.snapshots.len() - 1) as f64,
+                        self.position() as f64,
+                        |position| Message::SetPosition(position as usize),
+                    )
+                }))
+                .push(self.view_snapshot(timeline))
+                .push(self.view_search(timeline))
+                .push_maybe((!timeline.failed.is_empty()).then(|| {
+                    text(format!(
+                        "{} files couldn't be read: {}",
+                        timeline.failed.len(),
+                        timeline
+                            .failed
+                            .iter()
+                            .map(|(path, e)| format!("{} ({e})", path.display()))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                    .style(text::danger)
+                }))
+                .spacing(15)
+                .into(),
+        };
+
+        let content = container(
+            column![
+                text(title).size(24),
+                scrollable(body).height(Length::Shrink),
+                row![
+                    button("Archive session files").on_press_maybe(
+                        (self.archive.is_some() && self.profile.is_some() && !self.loading)
+                            .then_some(Message::Archive)
+                    ),
+                    button("Reload").on_press_maybe(
+                        self.profile
+                            .clone()
+                            .filter(|_| !self.loading)
+                            .map(Message::Load)
+                    ),
+                    button("Close")
+                        .style(iced::widget::button::secondary)
+                        .on_press(Message::Hide),
+                ]
+                .spacing(10),
+            ]
+            .push_maybe(self.status.as_ref().map(|status| match status {
+                Ok(done) => text(done),
+                Err(e) => text(e).style(text::danger),
+            }))
+            .spacing(15),
+        )
+        .width(800)
+        .max_height(700)
+        .padding(10)
+        .style(iced::widget::container::bordered_box);
+
+        Some(container(content).padding(30).into())
+    }
+}
@@ -1,6 +1,6 @@
 //! A wizard modal window that helps the user select a Firefox profile.
 
-use iced::widget::{column, container, row, text};
+use iced::widget::{checkbox, column, container, row, text};
 use iced::Element;
 use iced_aw::selection_list;
 use std::path::PathBuf;
@@ -12,6 +12,8 @@ pub struct State {
     profiles: Vec<FirefoxProfileInfo>,
     profile_names: Vec<String>,
     active: bool,
+    /// Open the profile's newest bookmark backup instead of its session.
+    bookmarks: bool,
 }
 impl State {
     pub fn new() -> Self {
@@ -29,6 +31,7 @@ impl State {
             profiles,
             profile_names,
             active: false,
+            bookmarks: false,
         }
     }
 
@@ -40,6 +43,9 @@ impl State {
             Message::Show => {
                 self.active = true;
             }
+            Message::SetBookmarks(bookmarks) => {
+                self.bookmarks = bookmarks;
+            }
             Message::SelectedSessionFile(_) => {}
         }
     }
@@ -58,9 +64,14 @@ impl State {
                         self.profile_names.as_slice(),
                         {
                             let profiles = self.profiles.clone();
+                            let bookmarks = self.bookmarks;
                             move |ix, _name| {
                                 let selected = &profiles[ix];
-                                Message::SelectedSessionFile(selected.find_sessionstore_file())
+                                Message::SelectedSessionFile(if bookmarks {
+                                    selected.find_bookmark_backup()
+                                } else {
+                                    selected.find_sessionstore_file()
+                                })
                             }
                         },
                         16.0,
@@ -79,7 +90,9 @@ impl State {
                         Default::default()
                     ),
                 ]]
-                .spacing(5)
+                .spacing(5),
+                checkbox("Open the newest bookmark backup instead", self.bookmarks)
+                    .on_toggle(Message::SetBookmarks),
             ]
             .spacing(20),
         )
@@ -97,5 +110,6 @@ impl State {
 pub enum Message {
     Hide,
     Show,
+    SetBookmarks(bool),
     SelectedSessionFile(PathBuf),
 }
@@ -0,0 +1,890 @@
+//! Drives the loading pipeline in [`host`] with the small sessionstore files in
+//! `tests/fixtures` and compares the generated output with the snapshots in
+//! `tests/snapshots`.
+//!
+//! A missing snapshot fails the test. Set the environment variable
+//! `UPDATE_SNAPSHOTS=1` to write missing snapshots, or to overwrite them after
+//! an intended change to the output, then review and commit them.
+//!
+//! The fake backend ignores the input files so these tests only run with the
+//! `real_data` feature.
+#![cfg(feature = "real_data")]
+
+use std::path::{Path, PathBuf};
+
+use firefox_session_ui_iced::host::{
+    archive_session_files, load_timeline, save_merged_links, DataSource, Error, FileData, FileInfo,
+    FirefoxProfileInfo, FormatInfo, GenerateOptions, GroupBy, MergeSource, OutputOptions,
+    PreviewCache, TabGroup,
+};
+
+/// Name of each session in the fixtures folder. Every session is stored both
+/// as plain JSON and compressed as `jsonlz4`.
+const FIXTURES: &[&str] = &["plain", "extensions", "edge_cases"];
+
+fn fixture_path(file_name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(file_name)
+}
+
+/// A folder that tests can write files to.
+fn output_folder(test_name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!(
+        "firefox-session-ui-iced-tests-{}-{test_name}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&folder).expect("failed to create temporary folder");
+    folder
+}
+
+/// Compare some output with a snapshot file. The snapshot is only written if
+/// `UPDATE_SNAPSHOTS` is set.
+fn assert_snapshot(name: &str, actual: &str) {
+    let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots");
+    let path = folder.join(format!("{name}.snap"));
+    // Snapshots are checked in with "\n" line endings:
+    let actual = actual.replace("\r\n", "\n");
+
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some_and(|v| v != "0");
+    if update {
+        std::fs::create_dir_all(&folder).expect("failed to create snapshot folder");
+        std::fs::write(&path, actual).expect("failed to write snapshot");
+        eprintln!("Wrote snapshot at {}", path.display());
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "failed to read snapshot at {}, rerun with UPDATE_SNAPSHOTS=1 to create it: {e}",
+            path.display()
+        )
+    });
+    assert_eq!(
+        expected.replace("\r\n", "\n"),
+        actual,
+        "output didn't match snapshot at {}, rerun with UPDATE_SNAPSHOTS=1 if this change was intended",
+        path.display()
+    );
+}
+
+/// Read, decompress and parse a fixture file.
+async fn load(file_name: &str) -> FileInfo {
+    let mut info = FileInfo::new(fixture_path(file_name));
+    info.load_data()
+        .await
+        .unwrap_or_else(|e| panic!("failed to read {file_name}: {e}"));
+    info.decompress_data()
+        .await
+        .unwrap_or_else(|e| panic!("failed to decompress {file_name}: {e}"));
+    info.parse_session_data()
+        .await
+        .unwrap_or_else(|e| panic!("failed to parse {file_name}: {e}"));
+    info
+}
+
+fn raw_data(info: &FileInfo) -> &[u8] {
+    match info.data.as_ref().expect("data should have been loaded") {
+        FileData::Compressed(data) | FileData::Uncompressed(data) => data,
+        FileData::Parsed(_) => panic!("data was already parsed"),
+    }
+}
+
+#[tokio::test]
+async fn compressed_files_decompress_to_plain_json() {
+    for name in FIXTURES {
+        let mut plain = FileInfo::new(fixture_path(&format!("{name}.json")));
+        plain.load_data().await.unwrap();
+        assert!(matches!(plain.data, Some(FileData::Uncompressed(_))));
+
+        let mut compressed = FileInfo::new(fixture_path(&format!("{name}.jsonlz4")));
+        assert!(compressed.is_compressed_file_format());
+        compressed.load_data().await.unwrap();
+        assert!(matches!(compressed.data, Some(FileData::Compressed(_))));
+
+        compressed.decompress_data().await.unwrap();
+        assert_eq!(raw_data(&plain), raw_data(&compressed), "{name}");
+
+        // Already decompressed data is left as is:
+        plain.decompress_data().await.unwrap();
+        assert!(matches!(plain.data, Some(FileData::Uncompressed(_))));
+    }
+}
+
+#[tokio::test]
+async fn tab_groups() {
+    for name in FIXTURES {
+        let plain = load(&format!("{name}.json")).await;
+        let compressed = load(&format!("{name}.jsonlz4")).await;
+
+        for group_by in GroupBy::all() {
+            let options = GenerateOptions {
+                group_by: *group_by,
+                ..Default::default()
+            };
+            let groups = plain.get_groups_from_session(&options).await.unwrap();
+            assert_eq!(
+                groups,
+                compressed.get_groups_from_session(&options).await.unwrap(),
+                "{name}"
+            );
+            assert_snapshot(
+                &format!("groups_{name}_by_{group_by}"),
+                &format!("{groups:#?}"),
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn window_groups_point_to_their_window() {
+    for sort_groups in [false, true] {
+        let options = GenerateOptions {
+            sort_groups,
+            ..Default::default()
+        };
+        let info = load("plain.jsonlz4").await;
+        let json = serde_json::from_slice::<serde_json::Value>(&info.json_data().unwrap()).unwrap();
+        let groups = info.get_groups_from_session(&options).await.unwrap();
+        for (key, groups) in [
+            ("windows", &groups.open),
+            ("_closedWindows", &groups.closed),
+        ] {
+            let mut paths = groups
+                .iter()
+                .map(|group| group.json_path.clone().unwrap())
+                .collect::<Vec<_>>();
+            assert!(paths
+                .iter()
+                .all(|path| path.starts_with(&format!("/{key}/"))));
+            paths.sort();
+            paths.dedup();
+            assert_eq!(paths.len(), groups.len());
+            assert!(paths.iter().all(|path| json.pointer(path).is_some()));
+        }
+
+        // Tree Style Tab and Sidebery data give groups that aren't windows:
+        let info = load("extensions.jsonlz4").await;
+        let groups = info.get_groups_from_session(&options).await.unwrap();
+        assert!(groups
+            .open
+            .iter()
+            .chain(&groups.closed)
+            .all(|group| group.json_path.is_none() && group.favicon.is_none()));
+    }
+}
+
+/// URLs of the tabs in a window of sessionstore JSON.
+fn window_urls(json: &serde_json::Value, window_path: &str) -> Vec<String> {
+    json.pointer(&format!("{window_path}/tabs"))
+        .and_then(serde_json::Value::as_array)
+        .unwrap()
+        .iter()
+        .map(|tab| {
+            let index = tab["index"].as_u64().unwrap() as usize;
+            tab["entries"][index - 1]["url"]
+                .as_str()
+                .unwrap()
+                .to_owned()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn open_tabs_of_selected_windows() {
+    let info = load("plain.jsonlz4").await;
+    let json = serde_json::from_slice::<serde_json::Value>(&info.json_data().unwrap()).unwrap();
+    for sort_groups in [false, true] {
+        let options = GenerateOptions {
+            sort_groups,
+            ..Default::default()
+        };
+        let groups = info.get_groups_from_session(&options).await.unwrap();
+        for group in &groups.open {
+            let selected = GenerateOptions {
+                open_group_indexes: Some(vec![group.index]),
+                ..options.clone()
+            };
+            assert_eq!(
+                info.selected_urls(&groups, &selected).unwrap(),
+                [window_urls(&json, group.json_path.as_deref().unwrap())],
+                "{}",
+                group.name
+            );
+        }
+    }
+
+    // Sidebery and Tree Style Tab panels can't be matched to windows:
+    let info = load("extensions.jsonlz4").await;
+    let options = GenerateOptions::default();
+    let groups = info.get_groups_from_session(&options).await.unwrap();
+    let result = info.selected_urls(&groups, &options);
+    assert!(
+        matches!(result, Err(Error::NotWindows { .. })),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn own_grouping_reads_every_tab() {
+    let by_host = GenerateOptions {
+        group_by: GroupBy::Host,
+        closed_group_indexes: None,
+        ..Default::default()
+    };
+    let info = load("plain.jsonlz4").await;
+    let groups = info.get_groups_from_session(&by_host).await.unwrap();
+    assert!(groups
+        .open
+        .iter()
+        .chain(&groups.closed)
+        .any(|group| group.name.starts_with("www.rust-lang.org (")));
+
+    // Tabs whose history index is 0 show their last page:
+    let info = load("edge_cases.jsonlz4").await;
+    let links = info.to_text_links(by_host).await.unwrap();
+    assert!(links.contains("https://example.com/index-zero"));
+    assert!(!links.contains("https://example.com/history"));
+}
+
+#[tokio::test]
+async fn inspect_tabs_with_the_same_url() {
+    let folder = output_folder("inspect");
+    let path = folder.join("same_url.json");
+    let window = |title: &str| {
+        serde_json::json!({ "tabs": [{
+            "entries": [{ "url": "https://example.com/", "title": title }],
+            "index": 1,
+        }] })
+    };
+    let json = serde_json::json!({ "windows": [window("First"), window("Second")] });
+    std::fs::write(&path, json.to_string()).unwrap();
+    let mut info = FileInfo::new(path);
+    info.load_data().await.unwrap();
+    info.parse_session_data().await.unwrap();
+
+    for group_by in [GroupBy::Window, GroupBy::Host] {
+        let options = GenerateOptions {
+            group_by,
+            ..Default::default()
+        };
+        let groups = info.get_groups_from_session(&options).await.unwrap();
+        let inspect = |occurrence, options: &GenerateOptions| {
+            info.inspect_tab("https://example.com/", occurrence, &groups, options)
+                .map(|tab| (tab.window, tab.title))
+        };
+        assert_eq!(
+            inspect(0, &options),
+            Some(("Window 1".to_owned(), "First".to_owned()))
+        );
+        assert_eq!(
+            inspect(1, &options),
+            Some(("Window 2".to_owned(), "Second".to_owned()))
+        );
+        assert_eq!(inspect(2, &options), None);
+    }
+
+    // Only the second window is in the preview:
+    let options = GenerateOptions {
+        open_group_indexes: Some(vec![1]),
+        ..Default::default()
+    };
+    let groups = info.get_groups_from_session(&options).await.unwrap();
+    let tab = info
+        .inspect_tab("https://example.com/", 0, &groups, &options)
+        .unwrap();
+    assert_eq!(tab.title, "Second");
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[tokio::test]
+async fn text_links() {
+    for name in FIXTURES {
+        let info = load(&format!("{name}.jsonlz4")).await;
+        let groups = info
+            .get_groups_from_session(&GenerateOptions::default())
+            .await
+            .unwrap();
+
+        let all_groups = GenerateOptions {
+            closed_group_indexes: None,
+            ..Default::default()
+        };
+        let only_first = GenerateOptions {
+            open_group_indexes: Some(groups.open.first().map(|g| g.index).into_iter().collect()),
+            ..Default::default()
+        };
+        for (options_name, options) in [
+            ("default", GenerateOptions::default()),
+            ("all", all_groups),
+            ("first", only_first),
+            (
+                "no_toc",
+                GenerateOptions {
+                    table_of_content: false,
+                    ..Default::default()
+                },
+            ),
+            (
+                "by_domain",
+                GenerateOptions {
+                    group_by: GroupBy::Domain,
+                    ..Default::default()
+                },
+            ),
+        ] {
+            let links = info.to_text_links(options).await.unwrap();
+            assert_snapshot(&format!("text_{name}_{options_name}"), &links);
+        }
+    }
+}
+
+/// The links in some text, in order.
+fn links_in(text: &str) -> Vec<&str> {
+    text.split_whitespace()
+        .filter(|word| word.starts_with("https://") || word.starts_with("http://"))
+        .collect()
+}
+
+#[tokio::test]
+async fn preview_caches_each_window() {
+    for name in FIXTURES {
+        let info = load(&format!("{name}.jsonlz4")).await;
+        let options = GenerateOptions {
+            closed_group_indexes: None,
+            ..Default::default()
+        };
+        let groups = info.get_groups_from_session(&options).await.unwrap();
+        let group_count = groups.open.len() + groups.closed.len();
+
+        let cache = PreviewCache::default();
+        let preview = info
+            .to_text_preview(options.clone(), cache.clone())
+            .await
+            .unwrap();
+        assert_eq!(cache.cached_groups(), group_count, "{name}");
+        let links = info.to_text_links(options.clone()).await.unwrap();
+        assert_eq!(links_in(&preview), links_in(&links), "{name}");
+
+        // Fewer windows are already cached:
+        let only_first = GenerateOptions {
+            open_group_indexes: Some(groups.open.first().map(|g| g.index).into_iter().collect()),
+            closed_group_indexes: Some(Vec::new()),
+            ..options
+        };
+        let preview = info
+            .to_text_preview(only_first.clone(), cache.clone())
+            .await
+            .unwrap();
+        assert_eq!(cache.cached_groups(), group_count, "{name}");
+        let links = info.to_text_links(only_first).await.unwrap();
+        assert_eq!(links_in(&preview), links_in(&links), "{name}");
+    }
+}
+
+#[tokio::test]
+async fn save_links_in_every_format() {
+    let folder = output_folder("save_links");
+    for name in FIXTURES {
+        let info = load(&format!("{name}.jsonlz4")).await;
+
+        for format in FormatInfo::all() {
+            let save_path = folder.join(format!("{name}-{}", format.as_str()));
+            info.save_links(
+                save_path.clone(),
+                GenerateOptions::default(),
+                OutputOptions {
+                    format: *format,
+                    overwrite: true,
+                    create_folder: false,
+                    embed_favicons: false,
+                    json: None,
+                    json_selected_windows: false,
+                },
+            )
+            .await
+            .unwrap_or_else(|e| panic!("failed to save {name} as {}: {e}", format.as_str()));
+
+            // The file extension is chosen by the format:
+            let written = std::fs::read_dir(&folder)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .find(|path| path.file_stem() == save_path.file_name())
+                .unwrap_or_else(|| panic!("no file was written for {}", save_path.display()));
+            let data = std::fs::read(&written).unwrap();
+
+            if data.starts_with(b"%PDF") {
+                // PDF files include creation dates so don't compare them:
+                assert!(data.len() > 100, "PDF output was suspiciously small");
+            } else {
+                let text = String::from_utf8(data).expect("output should be valid UTF-8");
+                assert_snapshot(&format!("save_{name}_{}", format.as_str()), &text);
+            }
+        }
+    }
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[tokio::test]
+async fn embed_favicons_for_selected_windows() {
+    let folder = output_folder("favicons");
+    let output_options = FormatInfo::all()
+        .iter()
+        .map(|format| OutputOptions {
+            format: *format,
+            overwrite: true,
+            embed_favicons: true,
+            ..Default::default()
+        })
+        .find(OutputOptions::can_embed_favicons)
+        .expect("HTML should support favicons");
+    let info = load("plain.jsonlz4").await;
+    let json = serde_json::from_slice::<serde_json::Value>(&info.json_data().unwrap()).unwrap();
+    for sort_groups in [false, true] {
+        let options = GenerateOptions {
+            sort_groups,
+            ..Default::default()
+        };
+        let groups = info.get_groups_from_session(&options).await.unwrap();
+        for group in &groups.open {
+            let save_path = folder.join("links.html");
+            info.save_links(
+                save_path.clone(),
+                GenerateOptions {
+                    open_group_indexes: Some(vec![group.index]),
+                    ..options.clone()
+                },
+                output_options.clone(),
+            )
+            .await
+            .unwrap();
+            let html = std::fs::read_to_string(&save_path).unwrap();
+            let urls = window_urls(&json, group.json_path.as_deref().unwrap());
+            for other in groups.open.iter().filter(|other| other != &group) {
+                for url in window_urls(&json, other.json_path.as_deref().unwrap()) {
+                    assert_eq!(html.contains(&url), urls.contains(&url), "{url}");
+                }
+            }
+            assert!(urls.iter().all(|url| html.contains(url)), "{}", group.name);
+        }
+    }
+
+    // Sidebery and Tree Style Tab panels can't be matched to windows:
+    let result = load("extensions.jsonlz4")
+        .await
+        .save_links(
+            folder.join("panels.html"),
+            GenerateOptions::default(),
+            output_options,
+        )
+        .await;
+    assert!(
+        matches!(result, Err(Error::NotWindows { .. })),
+        "{result:?}"
+    );
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[tokio::test]
+async fn save_links_without_overwrite_fails_for_existing_file() {
+    let folder = output_folder("no_overwrite");
+    let info = load("plain.json").await;
+    let save_path = folder.join("links.txt");
+    std::fs::write(&save_path, "existing").unwrap();
+
+    let result = info
+        .save_links(
+            save_path.clone(),
+            GenerateOptions::default(),
+            OutputOptions {
+                format: FormatInfo::TEXT,
+                overwrite: false,
+                create_folder: false,
+                embed_favicons: false,
+                json: None,
+                json_selected_windows: false,
+            },
+        )
+        .await;
+    assert!(
+        matches!(&result, Err(Error::AlreadyExists { path }) if path == &save_path),
+        "{result:?}"
+    );
+    assert_eq!(std::fs::read_to_string(&save_path).unwrap(), "existing");
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[tokio::test]
+async fn bookmark_backups() {
+    let plain = load("bookmarks.json").await;
+    let info = load("bookmarks.jsonlz4").await;
+    assert_eq!(info.source(), DataSource::Bookmarks);
+    // Only our own code reads bookmarks so the data isn't parsed by
+    // firefox_session_data:
+    assert!(info.is_parsed());
+    assert!(matches!(info.data, Some(FileData::Uncompressed(_))));
+
+    let groups = info
+        .get_groups_from_session(&GenerateOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        groups,
+        plain
+            .get_groups_from_session(&GenerateOptions::default())
+            .await
+            .unwrap()
+    );
+    assert!(groups.closed.is_empty());
+    let folders = groups
+        .open
+        .iter()
+        .map(|group| (group.depth, group.name.split(" (").next().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        folders,
+        [
+            (0, "Bookmarks Menu"),
+            (1, "Docs"),
+            (2, "Iced"),
+            (0, "Bookmarks Toolbar"),
+            (0, "Other Bookmarks"),
+            (0, "Mobile Bookmarks"),
+        ]
+    );
+    assert_eq!(
+        groups.open[2].json_path.as_deref(),
+        Some("/children/0/children/1/children/1")
+    );
+
+    let links = info
+        .to_text_links(GenerateOptions::default())
+        .await
+        .unwrap();
+    assert!(links.contains("https://doc.rust-lang.org/book/"));
+    // Untitled bookmarks use their URL and saved searches are skipped:
+    assert!(links.contains("https://example.com/"));
+    assert!(!links.contains("place:"));
+}
+
+#[tokio::test]
+async fn other_session_manager_exports() {
+    for (file_name, source, expected) in [
+        (
+            "tab_session_manager.json",
+            DataSource::TabSessionManager,
+            &[
+                "Research - Window 1 (1)",
+                "Research - Window 2 (2)",
+                "Session 2 (1)",
+            ][..],
+        ),
+        (
+            "session_buddy.json",
+            DataSource::SessionBuddy,
+            &["Reading - Window 1 (2)", "Reading - Docs (1)"],
+        ),
+        (
+            "onetab.txt",
+            DataSource::OneTab,
+            &["Group 1 (2)", "Group 2 (2)"],
+        ),
+    ] {
+        let info = load(file_name).await;
+        assert_eq!(info.source(), source, "{file_name}");
+        let groups = info
+            .get_groups_from_session(&GenerateOptions::default())
+            .await
+            .unwrap();
+        let names = groups
+            .open
+            .iter()
+            .map(|group| group.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected, "{file_name}");
+
+        let links = info
+            .to_text_links(GenerateOptions::default())
+            .await
+            .unwrap();
+        assert_snapshot(&format!("text_{}", file_name.replace('.', "_")), &links);
+    }
+
+    // Tabs are sorted by their index, not their id:
+    let info = load("tab_session_manager.json").await;
+    let links = info
+        .to_text_links(GenerateOptions::default())
+        .await
+        .unwrap();
+    let rust = links.find("https://www.rust-lang.org/").unwrap();
+    assert!(rust < links.find("https://doc.rust-lang.org/book/").unwrap());
+
+    // OneTab exports aren't JSON:
+    let info = load("onetab.txt").await;
+    assert!(info.json_data().is_none());
+}
+
+#[tokio::test]
+async fn chromium_session_files() {
+    let info = load("chromium_session").await;
+    assert_eq!(info.source(), DataSource::Chromium);
+    assert!(info.json_data().is_none());
+    let groups = info
+        .get_groups_from_session(&GenerateOptions::default())
+        .await
+        .unwrap();
+    let names = |groups: &[TabGroup]| {
+        groups
+            .iter()
+            .map(|group| group.name.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&groups.open), ["Work (2)"]);
+    assert_eq!(names(&groups.closed), ["Closed window 1 (1)"]);
+
+    let all_groups = GenerateOptions {
+        closed_group_indexes: None,
+        ..Default::default()
+    };
+    let links = info.to_text_links(all_groups).await.unwrap();
+    assert_snapshot("text_chromium_session", &links);
+    // Tabs are ordered by their index and show the selected history entry:
+    let rust = links.find("https://www.rust-lang.org/").unwrap();
+    assert!(rust < links.find("https://doc.rust-lang.org/book/").unwrap());
+    assert!(!links.contains("https://iced.rs/"));
+    // Closed tabs are left out:
+    assert!(!links.contains("https://example.com/closed"));
+
+    // Unknown versions of the format:
+    let folder = output_folder("chromium");
+    let path = folder.join("Current Session");
+    std::fs::write(&path, b"SNSS\x63\0\0\0").unwrap();
+    let mut info = FileInfo::new(path);
+    info.load_data().await.unwrap();
+    let result = info.parse_session_data().await;
+    assert!(
+        matches!(result, Err(Error::NotSessionData { .. })),
+        "{result:?}"
+    );
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+/// Load a fixture and remember the groups that `options` selects for merging.
+async fn merge_source(file_name: &str, options: &GenerateOptions) -> Result<MergeSource, Error> {
+    let info = load(file_name).await;
+    let groups = info.get_groups_from_session(options).await.unwrap();
+    info.merge_source(&groups, options)
+}
+
+#[tokio::test]
+async fn merge_selected_windows() {
+    let info = load("plain.jsonlz4").await;
+    let json = serde_json::from_slice::<serde_json::Value>(&info.json_data().unwrap()).unwrap();
+    for sort_groups in [false, true] {
+        let options = GenerateOptions {
+            sort_groups,
+            ..Default::default()
+        };
+        let groups = info.get_groups_from_session(&options).await.unwrap();
+        for group in &groups.open {
+            let selected = GenerateOptions {
+                open_group_indexes: Some(vec![group.index]),
+                ..options.clone()
+            };
+            let source = info.merge_source(&groups, &selected).unwrap();
+            assert_eq!(source.group_count(), 1);
+            assert_eq!(
+                source.tab_count(),
+                window_urls(&json, group.json_path.as_deref().unwrap()).len(),
+                "{}",
+                group.name
+            );
+        }
+    }
+
+    // Sidebery and Tree Style Tab panels can't be matched to windows:
+    let result = merge_source("extensions.jsonlz4", &GenerateOptions::default()).await;
+    assert!(
+        matches!(result, Err(Error::NotWindows { .. })),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn merged_sessions() {
+    let folder = output_folder("merged");
+    let options = GenerateOptions::default();
+    let sources = vec![
+        merge_source("plain.json", &options).await.unwrap(),
+        merge_source("plain.jsonlz4", &options).await.unwrap(),
+        merge_source("onetab.txt", &options).await.unwrap(),
+    ];
+    assert_eq!(sources[0].name, "fixtures/plain.json");
+    assert_eq!(sources[0].tab_count(), sources[1].tab_count());
+    let output_options = OutputOptions {
+        format: FormatInfo::TEXT,
+        overwrite: true,
+        create_folder: false,
+        embed_favicons: false,
+        json: None,
+        json_selected_windows: false,
+    };
+
+    let save_path = folder.join("merged.txt");
+    save_merged_links(
+        sources.clone(),
+        false,
+        save_path.clone(),
+        options.clone(),
+        output_options.clone(),
+    )
+    .await
+    .unwrap();
+    let all = std::fs::read_to_string(&save_path).unwrap();
+    for source in &sources {
+        assert!(all.contains(&format!("=== {} ===", source.name)));
+    }
+
+    // The same session loaded twice only has its tabs written once:
+    save_merged_links(
+        sources.clone(),
+        true,
+        save_path.clone(),
+        options.clone(),
+        output_options.clone(),
+    )
+    .await
+    .unwrap();
+    let deduplicated = std::fs::read_to_string(&save_path).unwrap();
+    assert!(deduplicated.len() < all.len());
+    assert_snapshot("save_merged_deduplicated", &deduplicated);
+
+    // Only firefox_session_data writes PDF files:
+    let result = save_merged_links(
+        sources,
+        true,
+        folder.join("merged.pdf"),
+        options,
+        OutputOptions {
+            format: FormatInfo::PDF,
+            ..output_options
+        },
+    )
+    .await;
+    assert!(
+        matches!(result, Err(Error::UnsupportedFormat { .. })),
+        "{result:?}"
+    );
+    assert!(!folder.join("merged.pdf").exists());
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[tokio::test]
+async fn session_history_of_profile() {
+    let folder = output_folder("history");
+    let profile_path = folder.join("abc.default-release");
+    let backups = profile_path.join("sessionstore-backups");
+    std::fs::create_dir_all(&backups).unwrap();
+    for (fixture, target) in [
+        ("plain.jsonlz4", profile_path.join("sessionstore.jsonlz4")),
+        ("extensions.jsonlz4", backups.join("recovery.jsonlz4")),
+        (
+            "edge_cases.jsonlz4",
+            backups.join("upgrade.jsonlz4-20240101000000"),
+        ),
+    ] {
+        std::fs::copy(fixture_path(fixture), target).unwrap();
+    }
+    let profile = FirefoxProfileInfo::for_session_file(&backups.join("recovery.jsonlz4")).unwrap();
+    let archive = folder.join("archive");
+
+    let timeline = load_timeline(profile.clone(), Some(archive.clone())).await;
+    assert!(timeline.failed.is_empty(), "{:?}", timeline.failed);
+    assert_eq!(timeline.snapshots.len(), 3);
+    for tab in timeline.find_tabs("") {
+        assert!(timeline.snapshots[tab.first_seen].contains(&tab.url));
+        assert!(timeline.snapshots[tab.last_seen].contains(&tab.url));
+    }
+
+    // Backups from before upgrades are left to Firefox:
+    assert_eq!(
+        archive_session_files(profile.clone(), archive.clone())
+            .await
+            .unwrap(),
+        2
+    );
+    // Files that didn't change since they were archived aren't copied again:
+    assert_eq!(
+        archive_session_files(profile.clone(), archive.clone())
+            .await
+            .unwrap(),
+        0
+    );
+    let timeline = load_timeline(profile, Some(archive)).await;
+    assert_eq!(timeline.snapshots.len(), 5);
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[test]
+fn profile_of_session_file() {
+    let folder = output_folder("profile_of_session_file");
+    let profile_path = folder.join("abc.default-release");
+    std::fs::create_dir_all(profile_path.join("sessionstore-backups")).unwrap();
+
+    // Files in a backup folder belong to the folder above it:
+    let profile = FirefoxProfileInfo::for_session_file(
+        &profile_path.join("sessionstore-backups/recovery.jsonlz4"),
+    )
+    .unwrap();
+    assert_eq!(profile.name(), "abc.default-release");
+
+    // Other folders need to look like a profile:
+    let session_file = profile_path.join("sessionstore.jsonlz4");
+    assert!(FirefoxProfileInfo::for_session_file(&session_file).is_none());
+    std::fs::write(profile_path.join("times.json"), b"{}").unwrap();
+    let profile = FirefoxProfileInfo::for_session_file(&session_file).unwrap();
+    assert_eq!(profile.name(), "abc.default-release");
+
+    // Such as a download folder:
+    assert!(FirefoxProfileInfo::for_session_file(&folder.join("session.jsonlz4")).is_none());
+    std::fs::remove_dir_all(&folder).ok();
+}
+
+#[tokio::test]
+async fn errors_for_invalid_files() {
+    let mut missing = FileInfo::new(fixture_path("does-not-exist.jsonlz4"));
+    let result = missing.load_data().await;
+    assert!(matches!(result, Err(Error::NotFound { .. })), "{result:?}");
+
+    // Plain JSON with a compressed file extension:
+    let folder = output_folder("invalid");
+    let not_compressed = folder.join("session.jsonlz4");
+    std::fs::copy(fixture_path("plain.json"), &not_compressed).unwrap();
+    let mut info = FileInfo::new(not_compressed.clone());
+    info.load_data().await.unwrap();
+    let result = info.decompress_data().await;
+    assert!(
+        matches!(&result, Err(Error::NotMozlz4 { path }) if path == &not_compressed),
+        "{result:?}"
+    );
+
+    // Valid JSON that isn't a sessionstore file:
+    let not_session = folder.join("other.json");
+    std::fs::write(&not_session, "[1, 2, 3]").unwrap();
+    let mut info = FileInfo::new(not_session);
+    info.load_data().await.unwrap();
+    let result = info.parse_session_data().await;
+    assert!(
+        matches!(result, Err(Error::NotSessionData { .. })),
+        "{result:?}"
+    );
+
+    // Can't list groups before parsing:
+    let mut info = FileInfo::new(fixture_path("plain.json"));
+    info.load_data().await.unwrap();
+    let result = info
+        .get_groups_from_session(&GenerateOptions::default())
+        .await;
+    assert!(matches!(result, Err(Error::NotReady { .. })), "{result:?}");
+
+    std::fs::remove_dir_all(&folder).ok();
+}